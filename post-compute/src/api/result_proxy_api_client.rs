@@ -1,5 +1,21 @@
-use reqwest::blocking::Client;
+use crate::compute::{
+    computed_file::ComputedFile,
+    errors::ReplicateStatusCause,
+    result_storage::{ResultLink, ResultStorage},
+};
+use log::{error, info};
+use reqwest::{
+    Method, StatusCode,
+    blocking::{Body, Client},
+    header::ALLOW,
+};
 use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io,
+    path::Path,
+};
+use thiserror::Error;
 
 const EMPTY_HEX_STRING_32: &str =
     "0x0000000000000000000000000000000000000000000000000000000000000000";
@@ -43,6 +59,27 @@ impl Default for ResultModel {
     }
 }
 
+/// Path of the legacy endpoint receiving a JSON encoded [`ResultModel`].
+const RESULTS_PATH: &str = "/v1/results";
+/// Path of the endpoint receiving the result archive as a raw request body.
+const RESULTS_STREAM_PATH: &str = "/v2/results";
+
+pub const CHAIN_TASK_ID_HEADER: &str = "X-Iexec-Chain-Task-Id";
+pub const DEAL_ID_HEADER: &str = "X-Iexec-Deal-Id";
+pub const TASK_INDEX_HEADER: &str = "X-Iexec-Task-Index";
+pub const DETERMINIST_HASH_HEADER: &str = "X-Iexec-Determinist-Hash";
+pub const ENCLAVE_SIGNATURE_HEADER: &str = "X-Iexec-Enclave-Signature";
+pub const CONTENT_SHA256_HEADER: &str = "X-Content-Sha256";
+
+/// Errors raised while uploading a result archive stored on disk.
+#[derive(Debug, Error)]
+pub enum ResultUploadError {
+    #[error("failed to read result archive: {0}")]
+    Io(#[from] io::Error),
+    #[error("result proxy request failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
 pub struct ResultProxyApiClient {
     base_url: String,
     client: Client,
@@ -140,7 +177,7 @@ impl ResultProxyApiClient {
         authorization: &str,
         result_model: &ResultModel,
     ) -> Result<String, reqwest::Error> {
-        let url = format!("{}/v1/results", self.base_url);
+        let url = format!("{}{RESULTS_PATH}", self.base_url);
         let response = self
             .client
             .post(&url)
@@ -154,6 +191,171 @@ impl ResultProxyApiClient {
             Err(response.error_for_status().unwrap_err())
         }
     }
+
+    /// Uploads a result archive to IPFS, streaming it from disk to the result proxy.
+    ///
+    /// The archive is sent as the raw body of a POST request to the `/v2/results` endpoint,
+    /// without being loaded in memory. Its size is sent as `Content-Length` and its SHA-256
+    /// digest in the `X-Content-Sha256` header, so that the result proxy can check it received
+    /// the whole archive. Task metadata are sent in `X-Iexec-*` headers.
+    ///
+    /// Support of the streaming endpoint is checked before sending the archive, see
+    /// [`ResultProxyApiClient::supports_streaming_upload`]. When it is not supported, the
+    /// archive is read in memory and uploaded with [`ResultProxyApiClient::upload_to_ipfs`].
+    /// A failed streaming upload is reported as is, without falling back to the JSON upload.
+    ///
+    /// # Arguments
+    ///
+    /// * `authorization` - The bearer token for authenticating with the result proxy
+    /// * `result_model` - The task metadata, its `zip` field is ignored
+    /// * `zip_path` - Path of the result archive to upload
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The IPFS link where the result was uploaded
+    /// * `Err(ResultUploadError)` - The archive could not be read or the upload failed
+    pub fn upload_file_to_ipfs(
+        &self,
+        authorization: &str,
+        result_model: ResultModel,
+        zip_path: &Path,
+    ) -> Result<String, ResultUploadError> {
+        let size = fs::metadata(zip_path)?.len();
+        let checksum = sha256::try_digest(zip_path)?;
+        let file = File::open(zip_path)?;
+
+        if !self.supports_streaming_upload(authorization)? {
+            info!("Streaming upload not supported by result proxy, falling back to JSON upload");
+            let result_model = ResultModel {
+                zip: fs::read(zip_path)?,
+                ..result_model
+            };
+            return Ok(self.upload_to_ipfs(authorization, &result_model)?);
+        }
+
+        let url = format!("{}{RESULTS_STREAM_PATH}", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", authorization)
+            .header("Content-Type", "application/zip")
+            .header(CHAIN_TASK_ID_HEADER, &result_model.chain_task_id)
+            .header(DEAL_ID_HEADER, &result_model.deal_id)
+            .header(TASK_INDEX_HEADER, result_model.task_index.to_string())
+            .header(DETERMINIST_HASH_HEADER, &result_model.determinist_hash)
+            .header(ENCLAVE_SIGNATURE_HEADER, &result_model.enclave_signature)
+            .header(CONTENT_SHA256_HEADER, &checksum)
+            .body(Body::sized(file, size))
+            .send()?;
+
+        if response.status().is_success() {
+            Ok(response.text()?)
+        } else {
+            Err(response.error_for_status().unwrap_err().into())
+        }
+    }
+
+    /// Returns whether the result proxy exposes the streaming endpoint, without sending any
+    /// archive.
+    ///
+    /// An `OPTIONS` request is sent to `/v2/results`. The endpoint is supported when the
+    /// request succeeds and its `Allow` header, if any, lists `POST`. It is not supported when
+    /// the result proxy answers 404, 405 or 501, any other status being an error.
+    pub fn supports_streaming_upload(&self, authorization: &str) -> Result<bool, reqwest::Error> {
+        let url = format!("{}{RESULTS_STREAM_PATH}", self.base_url);
+        let response = self
+            .client
+            .request(Method::OPTIONS, &url)
+            .header("Authorization", authorization)
+            .send()?;
+
+        match response.status() {
+            status if status.is_success() => Ok(response
+                .headers()
+                .get(ALLOW)
+                .and_then(|allow| allow.to_str().ok())
+                .is_none_or(|allow| {
+                    allow
+                        .split(',')
+                        .any(|method| method.trim().eq_ignore_ascii_case("POST"))
+                })),
+            StatusCode::NOT_FOUND
+            | StatusCode::METHOD_NOT_ALLOWED
+            | StatusCode::NOT_IMPLEMENTED => Ok(false),
+            _ => Err(response.error_for_status().unwrap_err()),
+        }
+    }
+}
+
+impl ResultStorage for ResultProxyApiClient {
+    /// Uploads a result archive to IPFS using the iExec result proxy service.
+    ///
+    /// It creates a [`ResultModel`] with the necessary metadata and streams the file to the
+    /// proxy service for IPFS storage, falling back to the legacy JSON upload when the proxy
+    /// does not support streaming.
+    ///
+    /// # Arguments
+    ///
+    /// * `computed_file` - The [`ComputedFile`] containing task metadata
+    /// * `token` - Authentication token for the result proxy
+    /// * `file_to_upload_path` - Path to the file that should be uploaded
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ResultLink)` - The IPFS link where the result was stored
+    /// * `Err(ReplicateStatusCause)` - Upload failed
+    fn upload(
+        &self,
+        computed_file: &ComputedFile,
+        token: &str,
+        file_to_upload_path: &str,
+    ) -> Result<ResultLink, ReplicateStatusCause> {
+        let task_id = computed_file
+            .task_id
+            .as_ref()
+            .ok_or(ReplicateStatusCause::PostComputeTaskIdMissing)?;
+        if self.base_url.is_empty() {
+            error!(
+                "Can't upload_to_ipfs_with_iexec_proxy (missing result proxy) [task_id:{task_id}]"
+            );
+            return Err(ReplicateStatusCause::PostComputeFailedUnknownIssue); //TODO Define better error
+        }
+        let result_digest = computed_file.result_digest.as_ref().ok_or_else(|| {
+            error!(
+                "Can't upload_to_ipfs_with_iexec_proxy (missing result digest) [task_id:{task_id}]"
+            );
+            ReplicateStatusCause::PostComputeResultDigestComputationFailed
+        })?;
+        let enclave_signature = computed_file.enclave_signature.as_ref().ok_or_else(|| {
+            error!(
+                "Can't upload_to_ipfs_with_iexec_proxy (missing enclave signature) [task_id:{task_id}]"
+            );
+            ReplicateStatusCause::PostComputeInvalidTeeSignature
+        })?;
+
+        let result_model = ResultModel {
+            chain_task_id: task_id.clone(),
+            determinist_hash: result_digest.clone(),
+            enclave_signature: enclave_signature.clone(),
+            ..Default::default()
+        };
+
+        match self.upload_file_to_ipfs(token, result_model, Path::new(file_to_upload_path)) {
+            Ok(ipfs_link) => Ok(ResultLink::Ipfs(ipfs_link)),
+            Err(ResultUploadError::Io(e)) => {
+                error!(
+                    "Can't upload_to_ipfs_with_iexec_proxy (missing file_path to upload) [task_id:{task_id}, file_to_upload_path:{file_to_upload_path}]: {e}"
+                );
+                Err(ReplicateStatusCause::PostComputeResultFileNotFound)
+            }
+            Err(e) => {
+                error!(
+                    "Can't upload_to_ipfs_with_iexec_proxy (result proxy issue) [task_id:{task_id}]: {e}"
+                );
+                Err(ReplicateStatusCause::PostComputeIpfsUploadFailed)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -162,7 +364,7 @@ mod tests {
     use serde_json::json;
     use wiremock::{
         Mock, MockServer, ResponseTemplate,
-        matchers::{body_json, header, method, path},
+        matchers::{body_bytes, body_json, header, method, path},
     };
 
     // Test constants
@@ -173,6 +375,15 @@ mod tests {
     const TEST_IPFS_LINK: &str = "ipfs://QmHash123";
     const TEST_TOKEN: &str = "test-token";
 
+    fn create_test_computed_file(task_id: &str) -> ComputedFile {
+        ComputedFile {
+            task_id: Some(String::from(task_id)),
+            result_digest: Some(String::from("0xabc123")),
+            enclave_signature: Some(String::from("0xdef456")),
+            ..Default::default()
+        }
+    }
+
     // region ResultModel
     #[test]
     fn result_model_default_returns_correct_values_when_created() {
@@ -315,4 +526,352 @@ mod tests {
         }
     }
     // endregion
+
+    // region upload_file_to_ipfs
+    const TEST_ZIP_CONTENT: &[u8] = b"test zip content";
+
+    fn create_zip_file() -> (tempfile::TempDir, std::path::PathBuf) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let zip_path = temp_dir.path().join("result.zip");
+        fs::write(&zip_path, TEST_ZIP_CONTENT).unwrap();
+        (temp_dir, zip_path)
+    }
+
+    async fn mount_streaming_support(mock_server: &MockServer, response: ResponseTemplate) {
+        Mock::given(method("OPTIONS"))
+            .and(path("/v2/results"))
+            .and(header("Authorization", TEST_TOKEN))
+            .respond_with(response)
+            .expect(1)
+            .mount(mock_server)
+            .await;
+    }
+
+    fn create_result_model() -> ResultModel {
+        ResultModel {
+            chain_task_id: TEST_TASK_ID.to_string(),
+            deal_id: TEST_DEAL_ID.to_string(),
+            task_index: 2,
+            determinist_hash: TEST_DETERMINIST_HASH.to_string(),
+            enclave_signature: TEST_ENCLAVE_SIGNATURE.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_file_to_ipfs_streams_archive_with_metadata_headers() {
+        let (_temp_dir, zip_path) = create_zip_file();
+        let mock_server = MockServer::start().await;
+        mount_streaming_support(
+            &mock_server,
+            ResponseTemplate::new(204).insert_header("Allow", "OPTIONS, POST"),
+        )
+        .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/results"))
+            .and(header("Authorization", TEST_TOKEN))
+            .and(header("Content-Type", "application/zip"))
+            .and(header("Content-Length", "16"))
+            .and(header(CHAIN_TASK_ID_HEADER, TEST_TASK_ID))
+            .and(header(DEAL_ID_HEADER, TEST_DEAL_ID))
+            .and(header(TASK_INDEX_HEADER, "2"))
+            .and(header(DETERMINIST_HASH_HEADER, TEST_DETERMINIST_HASH))
+            .and(header(ENCLAVE_SIGNATURE_HEADER, TEST_ENCLAVE_SIGNATURE))
+            .and(header(
+                CONTENT_SHA256_HEADER,
+                sha256::digest(TEST_ZIP_CONTENT).as_str(),
+            ))
+            .and(body_bytes(TEST_ZIP_CONTENT))
+            .respond_with(ResponseTemplate::new(200).set_body_string(TEST_IPFS_LINK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/results"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = tokio::task::spawn_blocking(move || {
+            let client = ResultProxyApiClient::new(&mock_server.uri());
+            client.upload_file_to_ipfs(TEST_TOKEN, create_result_model(), &zip_path)
+        })
+        .await
+        .expect("Task panicked");
+
+        assert_eq!(result.unwrap(), TEST_IPFS_LINK);
+    }
+
+    #[tokio::test]
+    async fn upload_file_to_ipfs_falls_back_to_json_when_streaming_not_supported() {
+        for (status_code, allow) in [(404, None), (405, None), (501, None), (200, Some("GET"))] {
+            let (_temp_dir, zip_path) = create_zip_file();
+            let mock_server = MockServer::start().await;
+            let mut probe_response = ResponseTemplate::new(status_code);
+            if let Some(allow) = allow {
+                probe_response = probe_response.insert_header("Allow", allow);
+            }
+            mount_streaming_support(&mock_server, probe_response).await;
+            Mock::given(method("POST"))
+                .and(path("/v2/results"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&mock_server)
+                .await;
+            let expected_model = ResultModel {
+                zip: TEST_ZIP_CONTENT.to_vec(),
+                ..create_result_model()
+            };
+            Mock::given(method("POST"))
+                .and(path("/v1/results"))
+                .and(header("Authorization", TEST_TOKEN))
+                .and(body_json(serde_json::to_value(&expected_model).unwrap()))
+                .respond_with(ResponseTemplate::new(200).set_body_string(TEST_IPFS_LINK))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let result = tokio::task::spawn_blocking(move || {
+                let client = ResultProxyApiClient::new(&mock_server.uri());
+                client.upload_file_to_ipfs(TEST_TOKEN, create_result_model(), &zip_path)
+            })
+            .await
+            .expect("Task panicked");
+
+            assert_eq!(
+                result.unwrap(),
+                TEST_IPFS_LINK,
+                "Expected fallback for status code {status_code}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_file_to_ipfs_returns_error_without_fallback_when_streaming_upload_fails() {
+        for status_code in [404, 500] {
+            let (_temp_dir, zip_path) = create_zip_file();
+            let mock_server = MockServer::start().await;
+            mount_streaming_support(&mock_server, ResponseTemplate::new(200)).await;
+            Mock::given(method("POST"))
+                .and(path("/v2/results"))
+                .respond_with(ResponseTemplate::new(status_code))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/v1/results"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&mock_server)
+                .await;
+
+            let result = tokio::task::spawn_blocking(move || {
+                let client = ResultProxyApiClient::new(&mock_server.uri());
+                client.upload_file_to_ipfs(TEST_TOKEN, create_result_model(), &zip_path)
+            })
+            .await
+            .expect("Task panicked");
+
+            assert!(
+                matches!(result, Err(ResultUploadError::Http(_))),
+                "Expected no fallback for status code {status_code}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_file_to_ipfs_returns_error_without_upload_when_probe_fails() {
+        let (_temp_dir, zip_path) = create_zip_file();
+        let mock_server = MockServer::start().await;
+        mount_streaming_support(&mock_server, ResponseTemplate::new(500)).await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = tokio::task::spawn_blocking(move || {
+            let client = ResultProxyApiClient::new(&mock_server.uri());
+            client.upload_file_to_ipfs(TEST_TOKEN, create_result_model(), &zip_path)
+        })
+        .await
+        .expect("Task panicked");
+
+        assert!(matches!(result, Err(ResultUploadError::Http(_))));
+    }
+
+    #[test]
+    fn upload_file_to_ipfs_returns_io_error_when_archive_missing() {
+        let client = ResultProxyApiClient::new("http://localhost");
+
+        let result = client.upload_file_to_ipfs(
+            TEST_TOKEN,
+            create_result_model(),
+            Path::new("/this/file/does/not/exist"),
+        );
+
+        assert!(matches!(result, Err(ResultUploadError::Io(_))));
+    }
+    // endregion
+
+    // region ResultStorage
+    async fn actually_upload(
+        computed_file: ComputedFile,
+        mock_server: MockServer,
+        file_path: std::path::PathBuf,
+    ) -> Result<ResultLink, ReplicateStatusCause> {
+        tokio::task::spawn_blocking(move || {
+            ResultProxyApiClient::new(&mock_server.uri()).upload(
+                &computed_file,
+                "test-token",
+                file_path.to_str().unwrap(),
+            )
+        })
+        .await
+        .expect("Task panicked")
+    }
+
+    #[tokio::test]
+    async fn upload_returns_link_when_upload_succeeds() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("fileToUpload.zip");
+        fs::write(&file_path, b"test zip content").unwrap();
+
+        let computed_file = ComputedFile {
+            task_id: Some(String::from("0x0")),
+            result_digest: Some(String::from("0xdigest")),
+            enclave_signature: Some(String::from("0xsignature")),
+            ..Default::default()
+        };
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/results"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ipfs://QmHash123"))
+            .mount(&mock_server)
+            .await;
+
+        let result = actually_upload(computed_file, mock_server, file_path).await;
+        assert_eq!(
+            result,
+            Ok(ResultLink::Ipfs(String::from("ipfs://QmHash123")))
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_streams_file_when_proxy_supports_it() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("fileToUpload.zip");
+        fs::write(&file_path, b"test zip content").unwrap();
+        let computed_file = create_test_computed_file("0x0");
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("OPTIONS"))
+            .and(path("/v2/results"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/results"))
+            .and(header("X-Iexec-Chain-Task-Id", "0x0"))
+            .and(body_bytes(b"test zip content"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ipfs://QmHash123"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = actually_upload(computed_file, mock_server, file_path).await;
+        assert_eq!(
+            result,
+            Ok(ResultLink::Ipfs(String::from("ipfs://QmHash123")))
+        );
+    }
+
+    #[test]
+    fn upload_returns_error_when_file_not_found() {
+        let computed_file = create_test_computed_file("0x0");
+        let non_existent_file = "/this/file/does/not/exist";
+        let token = "IPFS_TOKEN";
+
+        let result = ResultProxyApiClient::new("http://localhost").upload(
+            &computed_file,
+            token,
+            non_existent_file,
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeResultFileNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_returns_error_when_api_request_fails() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("fileToUpload.zip");
+        fs::write(&file_path, b"test content").unwrap();
+        let computed_file = create_test_computed_file("0x0");
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/results"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+            .mount(&mock_server)
+            .await;
+
+        let result = actually_upload(computed_file, mock_server, file_path).await;
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeIpfsUploadFailed)
+        );
+    }
+
+    #[test]
+    fn upload_returns_error_when_result_proxy_missing() {
+        let result = ResultProxyApiClient::new("").upload(
+            &create_test_computed_file("0x0"),
+            "IPFS_TOKEN",
+            "/tmp/fileToUpload.zip",
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeFailedUnknownIssue)
+        );
+    }
+
+    #[test]
+    fn upload_returns_error_when_task_metadata_missing() {
+        let test_cases = [
+            (
+                ComputedFile {
+                    task_id: None,
+                    ..create_test_computed_file("0x0")
+                },
+                ReplicateStatusCause::PostComputeTaskIdMissing,
+            ),
+            (
+                ComputedFile {
+                    result_digest: None,
+                    ..create_test_computed_file("0x0")
+                },
+                ReplicateStatusCause::PostComputeResultDigestComputationFailed,
+            ),
+            (
+                ComputedFile {
+                    enclave_signature: None,
+                    ..create_test_computed_file("0x0")
+                },
+                ReplicateStatusCause::PostComputeInvalidTeeSignature,
+            ),
+        ];
+        for (computed_file, expected_cause) in test_cases {
+            let result = ResultProxyApiClient::new("http://localhost").upload(
+                &computed_file,
+                "IPFS_TOKEN",
+                "/tmp/fileToUpload.zip",
+            );
+            assert_eq!(result, Err(expected_cause));
+        }
+    }
+    // endregion
 }
@@ -3,6 +3,9 @@ pub mod computed_file;
 pub mod dropbox;
 pub mod encryption;
 pub mod errors;
+pub mod kubo;
+pub mod result_storage;
+pub mod s3;
 pub mod signer;
 pub mod utils;
 pub mod web2_result;
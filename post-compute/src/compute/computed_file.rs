@@ -5,6 +5,7 @@ use crate::compute::{
         env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error},
         hash_utils::concatenate_and_hash,
         result_utils::{compute_web2_result_digest, compute_web3_result_digest},
+        secret_utils::SecretString,
     },
 };
 use log::{error, info};
@@ -291,12 +292,14 @@ pub fn sign_computed_file(computed_file: &mut ComputedFile) -> Result<(), Replic
     let result_seal = concatenate_and_hash(&[&worker_address, task_id, result_digest]);
     let message_hash = concatenate_and_hash(&[&result_hash, &result_seal]);
 
-    let tee_challenge_private_key: String = get_env_var_or_error(
+    let tee_challenge_private_key: SecretString = get_env_var_or_error(
         TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey,
         ReplicateStatusCause::PostComputeTeeChallengePrivateKeyMissing,
-    )?;
+    )
+    .map(SecretString::from)?;
 
-    let enclave_signature = sign_enclave_challenge(&message_hash, &tee_challenge_private_key)?;
+    let enclave_signature =
+        sign_enclave_challenge(&message_hash, tee_challenge_private_key.expose_secret())?;
 
     computed_file.enclave_signature = Some(enclave_signature);
     info!("Signer stage completed");
@@ -1,29 +1,274 @@
 //! Dropbox upload service for handling file uploads to Dropbox storage.
 //!
 //! This module provides a small utility for uploading computation results to
-//! Dropbox using the Content API "files/upload" HTTPS endpoint. It focuses on
-//! correctness, explicit error mapping to `ReplicateStatusCause`, and testability
-//! (the base URL is injectable for mocking).
+//! Dropbox using the Content API. Files fitting in a single chunk are sent to the
+//! "files/upload" HTTPS endpoint, larger files go through an upload session
+//! ("upload_session/start", "append_v2" and "finish"), a finish whose response was lost being
+//! checked through "files/get_metadata". It focuses on correctness,
+//! explicit error mapping to `ReplicateStatusCause`, and testability (the base URL
+//! is injectable for mocking).
 
-use crate::compute::errors::ReplicateStatusCause;
-use log::{error, info};
+use crate::compute::{
+    computed_file::ComputedFile,
+    errors::ReplicateStatusCause,
+    result_storage::{ResultLink, ResultStorage},
+    utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var},
+};
+use log::{error, info, warn};
 #[cfg(test)]
 use mockall::automock;
-use reqwest::blocking::Client;
+use reqwest::{
+    StatusCode,
+    blocking::{Client, Response},
+};
 use serde::Deserialize;
-use std::{fs, path::Path};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::Path,
+    thread,
+    time::Duration,
+};
 
 /// Default Dropbox Content API base URL used for uploads.
 pub const DROPBOX_CONTENT_BASE_URL: &str = "https://content.dropboxapi.com";
+/// Host of the Dropbox Content API, replaced by [`DROPBOX_API_HOST`] to call the RPC API.
+const DROPBOX_CONTENT_HOST: &str = "://content.dropboxapi.com";
+/// Host of the Dropbox RPC API, serving file metadata.
+const DROPBOX_API_HOST: &str = "://api.dropboxapi.com";
 
 /// REST path for the Dropbox "files/upload" endpoint.
 const FILES_UPLOAD_PATH: &str = "/2/files/upload";
+/// REST paths for the Dropbox upload session endpoints.
+const UPLOAD_SESSION_START_PATH: &str = "/2/files/upload_session/start";
+const UPLOAD_SESSION_APPEND_PATH: &str = "/2/files/upload_session/append_v2";
+const UPLOAD_SESSION_FINISH_PATH: &str = "/2/files/upload_session/finish";
+/// REST path for the Dropbox "files/get_metadata" endpoint of the RPC API.
+const GET_METADATA_PATH: &str = "/2/files/get_metadata";
+
+/// Largest request body accepted by Dropbox, for both single uploads and session chunks.
+pub const MAX_CHUNK_SIZE: usize = 150 * 1024 * 1024;
+/// Default size of the chunks sent in an upload session.
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Size of the blocks hashed by the Dropbox `content_hash` algorithm.
+const CONTENT_HASH_BLOCK_SIZE: usize = 4 * 1024 * 1024;
 
 /// Service for handling Dropbox file uploads.
 ///
-/// This is a lightweight utility type. Use with `DropboxService` and call
-/// [`DropboxService::upload_file`].
-pub struct DropboxService;
+/// Files up to `chunk_size` bytes are uploaded in a single request, larger files are
+/// uploaded in chunks of `chunk_size` bytes through an upload session. Each request is
+/// attempted up to `max_attempts` times when it fails with a network error, a 429 or a
+/// 5xx status.
+pub struct DropboxService {
+    chunk_size: usize,
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+impl Default for DropboxService {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_CHUNK_SIZE,
+            DEFAULT_MAX_ATTEMPTS,
+            DEFAULT_RETRY_DELAY,
+        )
+    }
+}
+
+impl DropboxService {
+    /// Creates a Dropbox service, the chunk size is clamped between 1 byte and [`MAX_CHUNK_SIZE`].
+    pub fn new(chunk_size: usize, max_attempts: u32, retry_delay: Duration) -> Self {
+        Self {
+            chunk_size: chunk_size.clamp(1, MAX_CHUNK_SIZE),
+            max_attempts: max_attempts.max(1),
+            retry_delay,
+        }
+    }
+
+    /// Creates a Dropbox service using the chunk size read from `RESULT_STORAGE_DROPBOX_CHUNK_SIZE`.
+    ///
+    /// The default chunk size is used when the variable is missing or is not a number of bytes
+    /// between 1 and [`MAX_CHUNK_SIZE`].
+    pub fn from_env() -> Self {
+        let value = get_env_var(TeeSessionEnvironmentVariable::ResultStorageDropboxChunkSize);
+        let chunk_size = match value.parse::<usize>() {
+            Ok(size) if (1..=MAX_CHUNK_SIZE).contains(&size) => size,
+            _ if value.is_empty() => DEFAULT_CHUNK_SIZE,
+            _ => {
+                warn!(
+                    "Invalid Dropbox chunk size, using default [value:{value}, default:{DEFAULT_CHUNK_SIZE}]"
+                );
+                DEFAULT_CHUNK_SIZE
+            }
+        };
+        Self {
+            chunk_size,
+            ..Self::default()
+        }
+    }
+
+    fn upload_single(
+        &self,
+        client: &Client,
+        access_token: &str,
+        path: &Path,
+        dropbox_path: &str,
+        content_base_url: &str,
+    ) -> Result<String, ReplicateStatusCause> {
+        let content = fs::read(path).map_err(|e| {
+            error!(
+                "Failed to read file for Dropbox upload [path:{}, error:{e}]",
+                path.display()
+            );
+            ReplicateStatusCause::PostComputeDropboxUploadFailed
+        })?;
+        let mut hasher = ContentHasher::default();
+        hasher.update(&content);
+
+        let api_arg_header = commit_info(dropbox_path).to_string();
+        let url = format!("{content_base_url}{FILES_UPLOAD_PATH}");
+        let response =
+            self.post_with_retry(client, &url, access_token, &api_arg_header, &content)?;
+        let response = check_response(response, "upload")?;
+        parse_upload_response(response, dropbox_path, &hasher.finalize())
+    }
+
+    fn upload_session(
+        &self,
+        client: &Client,
+        access_token: &str,
+        path: &Path,
+        file_size: u64,
+        dropbox_path: &str,
+        content_base_url: &str,
+    ) -> Result<String, ReplicateStatusCause> {
+        let mut file = File::open(path).map_err(|e| {
+            error!(
+                "Failed to open file for Dropbox upload [path:{}, error:{e}]",
+                path.display()
+            );
+            ReplicateStatusCause::PostComputeDropboxUploadFailed
+        })?;
+        let mut hasher = ContentHasher::default();
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+        let mut read_chunk = |chunk: &mut Vec<u8>| {
+            chunk.clear();
+            (&mut file)
+                .take(self.chunk_size as u64)
+                .read_to_end(chunk)
+                .map_err(|e| {
+                    error!(
+                        "Failed to read file for Dropbox upload [path:{}, error:{e}]",
+                        path.display()
+                    );
+                    ReplicateStatusCause::PostComputeDropboxUploadFailed
+                })?;
+            hasher.update(chunk);
+            Ok::<(), ReplicateStatusCause>(())
+        };
+
+        read_chunk(&mut chunk)?;
+        let url = format!("{content_base_url}{UPLOAD_SESSION_START_PATH}");
+        let api_arg = json!({ "close": false }).to_string();
+        let response = self.post_with_retry(client, &url, access_token, &api_arg, &chunk)?;
+        let session_id = check_response(response, "upload_session/start")?
+            .json::<UploadSessionStartResponse>()
+            .map_err(|e| {
+                error!("Failed to parse Dropbox upload session response: {e}");
+                ReplicateStatusCause::PostComputeDropboxUploadFailed
+            })?
+            .session_id;
+        info!("Dropbox upload session started [path:{dropbox_path}, size:{file_size}]");
+
+        let mut offset = chunk.len() as u64;
+        read_chunk(&mut chunk)?;
+        while !chunk.is_empty() && offset + (chunk.len() as u64) < file_size {
+            let url = format!("{content_base_url}{UPLOAD_SESSION_APPEND_PATH}");
+            let api_arg = json!({
+                "close": false,
+                "cursor": { "offset": offset, "session_id": session_id },
+            })
+            .to_string();
+            let response = self.post_with_retry(client, &url, access_token, &api_arg, &chunk)?;
+            offset += chunk.len() as u64;
+            check_append_response(response, offset)?;
+            read_chunk(&mut chunk)?;
+        }
+
+        let url = format!("{content_base_url}{UPLOAD_SESSION_FINISH_PATH}");
+        let api_arg = json!({
+            "commit": commit_info(dropbox_path),
+            "cursor": { "offset": offset, "session_id": session_id },
+        })
+        .to_string();
+        let response = self.post_with_retry(client, &url, access_token, &api_arg, &chunk)?;
+        let expected_content_hash = hasher.finalize();
+        if response.status() == StatusCode::CONFLICT {
+            return check_finished_upload(
+                client,
+                access_token,
+                response,
+                dropbox_path,
+                content_base_url,
+                &expected_content_hash,
+            );
+        }
+        let response = check_response(response, "upload_session/finish")?;
+        parse_upload_response(response, dropbox_path, &expected_content_hash)
+    }
+
+    /// Sends a request to the Content API, retrying on network errors, 429 and 5xx statuses.
+    ///
+    /// Any other response is returned to the caller, which is in charge of checking its status.
+    fn post_with_retry(
+        &self,
+        client: &Client,
+        url: &str,
+        access_token: &str,
+        api_arg: &str,
+        body: &[u8],
+    ) -> Result<Response, ReplicateStatusCause> {
+        for attempt in 1..=self.max_attempts {
+            let result = client
+                .post(url)
+                .header("Authorization", format!("Bearer {access_token}"))
+                .header("Content-Type", "application/octet-stream")
+                .header("Dropbox-API-Arg", api_arg)
+                .body(body.to_vec())
+                .send();
+            match result {
+                Ok(response)
+                    if response.status() != StatusCode::TOO_MANY_REQUESTS
+                        && !response.status().is_server_error() =>
+                {
+                    return Ok(response);
+                }
+                Ok(response) => warn!(
+                    "Dropbox request failed [url:{url}, attempt:{attempt}/{}, status:{}]",
+                    self.max_attempts,
+                    response.status()
+                ),
+                Err(e) => warn!(
+                    "HTTP error calling Dropbox API [url:{url}, attempt:{attempt}/{}]: {e}",
+                    self.max_attempts
+                ),
+            }
+            if attempt < self.max_attempts {
+                thread::sleep(self.retry_delay);
+            }
+        }
+        error!(
+            "Dropbox request failed after {} attempts [url:{url}]",
+            self.max_attempts
+        );
+        Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+    }
+}
 
 #[cfg_attr(test, automock)]
 pub trait DropboxUploader {
@@ -39,13 +284,171 @@ pub trait DropboxUploader {
 #[derive(Deserialize, Debug)]
 struct UploadResponse {
     path_display: Option<String>,
+    content_hash: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct UploadSessionStartResponse {
+    session_id: String,
+}
+
+/// Incremental implementation of the Dropbox `content_hash` algorithm.
+///
+/// The content is split in blocks of 4 MiB, each block is hashed with SHA-256, and the
+/// `content_hash` is the hex encoded SHA-256 of the concatenated block hashes.
+#[derive(Default)]
+pub struct ContentHasher {
+    overall: Sha256,
+    block: Sha256,
+    block_len: usize,
+}
+
+impl ContentHasher {
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let take = data.len().min(CONTENT_HASH_BLOCK_SIZE - self.block_len);
+            self.block.update(&data[..take]);
+            self.block_len += take;
+            data = &data[take..];
+            if self.block_len == CONTENT_HASH_BLOCK_SIZE {
+                self.overall.update(self.block.finalize_reset());
+                self.block_len = 0;
+            }
+        }
+    }
+
+    pub fn finalize(mut self) -> String {
+        if self.block_len > 0 {
+            self.overall.update(self.block.finalize());
+        }
+        format!("{:x}", self.overall.finalize())
+    }
+}
+
+fn commit_info(dropbox_path: &str) -> Value {
+    json!({
+        "autorename": false,
+        "mode": "add",
+        "mute": false,
+        "path": dropbox_path,
+        "strict_conflict": false
+    })
+}
+
+fn check_response(response: Response, step: &str) -> Result<Response, ReplicateStatusCause> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else if status == StatusCode::UNAUTHORIZED {
+        error!("Authentication failed - invalid or expired token");
+        Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+    } else {
+        let body = response.text().unwrap_or_default();
+        error!("Dropbox {step} failed [status:{status}, body:{body}]");
+        Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+    }
+}
+
+/// Checks the response of an append, accepting a chunk already stored by a previous attempt.
+///
+/// This happens when a response is lost after Dropbox processed the request: the retried
+/// append is rejected with an `incorrect_offset` error whose `correct_offset` is the end of
+/// the chunk.
+fn check_append_response(response: Response, next_offset: u64) -> Result<(), ReplicateStatusCause> {
+    if response.status() != StatusCode::CONFLICT {
+        return check_response(response, "upload_session/append_v2").map(|_| ());
+    }
+    let body = response.text().unwrap_or_default();
+    let correct_offset = serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|error| error["error"]["correct_offset"].as_u64());
+    if correct_offset == Some(next_offset) {
+        info!("Dropbox chunk already appended by a previous attempt [offset:{next_offset}]");
+        Ok(())
+    } else {
+        error!("Dropbox upload_session/append_v2 failed [status:409, body:{body}]");
+        Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+    }
+}
+
+/// Checks the conflict returned by a finish, accepting a file already committed by a previous
+/// attempt.
+///
+/// This happens when a response is lost after Dropbox processed the request: the retried
+/// finish is rejected with a `lookup_failed` error, the session being closed. The metadata of
+/// the file is then fetched from the RPC API, and its `content_hash` checked like the response
+/// of the finish.
+fn check_finished_upload(
+    client: &Client,
+    access_token: &str,
+    response: Response,
+    dropbox_path: &str,
+    content_base_url: &str,
+    expected_content_hash: &str,
+) -> Result<String, ReplicateStatusCause> {
+    let body = response.text().unwrap_or_default();
+    let is_lookup_failed = serde_json::from_str::<Value>(&body)
+        .is_ok_and(|error| error["error"][".tag"] == "lookup_failed");
+    if !is_lookup_failed {
+        error!("Dropbox upload_session/finish failed [status:409, body:{body}]");
+        return Err(ReplicateStatusCause::PostComputeDropboxUploadFailed);
+    }
+    info!(
+        "Dropbox upload session already finished by a previous attempt, checking file [path:{dropbox_path}]"
+    );
+    let api_base_url = content_base_url.replacen(DROPBOX_CONTENT_HOST, DROPBOX_API_HOST, 1);
+    let response = client
+        .post(format!("{api_base_url}{GET_METADATA_PATH}"))
+        .header("Authorization", format!("Bearer {access_token}"))
+        .json(&json!({ "path": dropbox_path }))
+        .send()
+        .map_err(|e| {
+            error!("HTTP error calling Dropbox API [path:{dropbox_path}]: {e}");
+            ReplicateStatusCause::PostComputeDropboxUploadFailed
+        })?;
+    let response = check_response(response, "get_metadata")?;
+    parse_upload_response(response, dropbox_path, expected_content_hash)
+}
+
+/// Reads the metadata of an uploaded file, which must hold the `content_hash` of the local file.
+fn parse_upload_response(
+    response: Response,
+    dropbox_path: &str,
+    expected_content_hash: &str,
+) -> Result<String, ReplicateStatusCause> {
+    let meta = response.json::<UploadResponse>().map_err(|e| {
+        error!("Failed to parse Dropbox response: {e}");
+        ReplicateStatusCause::PostComputeDropboxUploadFailed
+    })?;
+    match &meta.content_hash {
+        Some(content_hash) if content_hash == expected_content_hash => {}
+        Some(content_hash) => {
+            error!(
+                "Dropbox content hash mismatch [path:{dropbox_path}, expected:{expected_content_hash}, actual:{content_hash}]"
+            );
+            return Err(ReplicateStatusCause::PostComputeDropboxUploadFailed);
+        }
+        None => {
+            error!("Dropbox response has no content hash [path:{dropbox_path}]");
+            return Err(ReplicateStatusCause::PostComputeDropboxUploadFailed);
+        }
+    }
+    let path = meta
+        .path_display
+        .unwrap_or_else(|| dropbox_path.to_string());
+    info!("Successfully uploaded to Dropbox [path:{path}]");
+    Ok(path)
 }
 
 impl DropboxUploader for DropboxService {
     /// Uploads a file to Dropbox.
     ///
-    /// Optimized for small to medium-sized files that can be sent in a single request.
-    /// For very large files (> 150 MiB), use Dropbox upload sessions (chunked upload).
+    /// Files up to the configured chunk size are sent in a single request to "files/upload".
+    /// Larger files are streamed from disk through an upload session, one chunk at a time, so
+    /// that files above the 150 MiB limit of a single request can be uploaded. In both cases,
+    /// the `content_hash` returned by Dropbox is checked against the hash of the local file, and
+    /// the upload fails when Dropbox returns none. A finish whose response was lost is accepted
+    /// once the metadata of the committed file is checked the same way.
     ///
     /// # Arguments
     ///
@@ -62,7 +465,9 @@ impl DropboxUploader for DropboxService {
     /// # Errors
     ///
     /// Returns `PostComputeResultFileNotFound` if the local file does not exist.
-    /// Returns `PostComputeDropboxUploadFailed` for any HTTP or API error (including 401).
+    /// Returns `PostComputeDropboxUploadFailed` for any HTTP or API error (including 401),
+    /// when a request still fails after all attempts, or when the content hash is missing or
+    /// does not match.
     ///
     /// # Example
     ///
@@ -73,7 +478,7 @@ impl DropboxUploader for DropboxService {
     ///     DropboxUploader,
     /// };
     ///
-    /// let result = DropboxService.upload_file(
+    /// let result = DropboxService::default().upload_file(
     ///     "access-token",
     ///     "/tmp/file.zip",
     ///     "/results/file.zip",
@@ -95,73 +500,129 @@ impl DropboxUploader for DropboxService {
             return Err(ReplicateStatusCause::PostComputeResultFileNotFound);
         }
 
-        let content = fs::read(path).map_err(|e| {
+        let file_size = fs::metadata(path).map(|m| m.len()).map_err(|e| {
             error!("Failed to read file for Dropbox upload [path:{local_file_path}, error:{e}]");
             ReplicateStatusCause::PostComputeDropboxUploadFailed
         })?;
 
-        let api_arg_header = serde_json::json!({
-            "autorename": false,
-            "mode": "add",
-            "mute": false,
-            "path": dropbox_path,
-            "strict_conflict": false
-        })
-        .to_string();
-
-        let url = format!("{content_base_url}{FILES_UPLOAD_PATH}");
-        let response = Client::new()
-            .post(url)
-            .header("Authorization", format!("Bearer {access_token}"))
-            .header("Content-Type", "application/octet-stream")
-            .header("Dropbox-API-Arg", api_arg_header)
-            .body(content)
-            .send();
-
-        match response {
-            Ok(resp) => {
-                let status = resp.status();
-                if status.is_success() {
-                    match resp.json::<UploadResponse>() {
-                        Ok(meta) => {
-                            let path = meta
-                                .path_display
-                                .unwrap_or_else(|| dropbox_path.to_string());
-                            info!("Successfully uploaded to Dropbox [path:{path}]");
-                            Ok(path)
-                        }
-                        Err(e) => {
-                            error!("Failed to parse Dropbox response: {e}");
-                            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
-                        }
-                    }
-                } else if status.as_u16() == 401 {
-                    error!("Authentication failed - invalid or expired token");
-                    Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
-                } else {
-                    let body = resp.text().unwrap_or_default();
-                    error!("Dropbox upload failed [status:{status}, body:{body}]");
-                    Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
-                }
-            }
-            Err(e) => {
-                error!("HTTP error calling Dropbox upload API: {e}");
-                Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
-            }
+        let client = Client::new();
+        if file_size <= self.chunk_size as u64 {
+            self.upload_single(&client, access_token, path, dropbox_path, content_base_url)
+        } else {
+            self.upload_session(
+                &client,
+                access_token,
+                path,
+                file_size,
+                dropbox_path,
+                content_base_url,
+            )
         }
     }
 }
 
+impl ResultStorage for DropboxService {
+    /// Uploads the result archive of a task to `/results/{task_id}.zip` in the Dropbox account.
+    ///
+    /// # Arguments
+    ///
+    /// * `computed_file` - The computed file metadata
+    /// * `token` - The Dropbox access token
+    /// * `file_to_upload_path` - Path to the local file to upload
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ResultLink)` - The Dropbox path where the file was uploaded
+    /// * `Err(ReplicateStatusCause)` - Upload error
+    fn upload(
+        &self,
+        computed_file: &ComputedFile,
+        token: &str,
+        file_to_upload_path: &str,
+    ) -> Result<ResultLink, ReplicateStatusCause> {
+        upload_result_with_uploader(
+            computed_file,
+            token,
+            file_to_upload_path,
+            self,
+            DROPBOX_CONTENT_BASE_URL,
+        )
+    }
+}
+
+/// Internal implementation of the Dropbox [`ResultStorage`] with dependency injection.
+/// This allows testing with mocked uploaders.
+fn upload_result_with_uploader<T: DropboxUploader>(
+    computed_file: &ComputedFile,
+    token: &str,
+    file_to_upload_path: &str,
+    uploader: &T,
+    content_base_url: &str,
+) -> Result<ResultLink, ReplicateStatusCause> {
+    let task_id = computed_file
+        .task_id
+        .as_ref()
+        .ok_or(ReplicateStatusCause::PostComputeTaskIdMissing)?;
+    let remote_filename = format!("{task_id}.zip");
+    let dropbox_path = format!("/results/{remote_filename}");
+
+    if !Path::new(file_to_upload_path).exists() {
+        error!("File to upload not found [task_id:{task_id}, path:{file_to_upload_path}]");
+        return Err(ReplicateStatusCause::PostComputeResultFileNotFound);
+    }
+
+    info!(
+        "Uploading to Dropbox [task_id:{task_id}, local:{file_to_upload_path}, remote:{dropbox_path}]"
+    );
+
+    uploader
+        .upload_file(token, file_to_upload_path, &dropbox_path, content_base_url)
+        .map(ResultLink::Dropbox)
+        .map_err(|e| {
+            error!("Dropbox upload failed [task_id:{task_id}, error:{e:?}]");
+            e
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mockall::predicate::eq;
     use std::io::Write;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
     use wiremock::{
         Mock, MockServer, ResponseTemplate,
-        matchers::{header, method, path},
+        matchers::{body_bytes, body_json, header, method, path},
     };
 
+    const TEST_FILE_CONTENT: &[u8] = b"test file content\n";
+
+    fn create_test_task(task_id: &str) -> ComputedFile {
+        ComputedFile {
+            task_id: Some(String::from(task_id)),
+            ..Default::default()
+        }
+    }
+
+    fn service() -> DropboxService {
+        DropboxService::new(DEFAULT_CHUNK_SIZE, 3, Duration::ZERO)
+    }
+
+    fn chunked_service() -> DropboxService {
+        DropboxService::new(5, 3, Duration::ZERO)
+    }
+
+    fn content_hash(content: &[u8]) -> String {
+        let mut hasher = ContentHasher::default();
+        hasher.update(content);
+        hasher.finalize()
+    }
+
+    fn api_arg(request: &wiremock::Request) -> Value {
+        let header = request.headers.get("Dropbox-API-Arg").unwrap();
+        serde_json::from_str(header.to_str().unwrap()).unwrap()
+    }
+
     fn create_test_computed_file() -> String {
         let mut temp_file = NamedTempFile::new().unwrap();
         writeln!(temp_file, "test file content").unwrap();
@@ -175,6 +636,7 @@ mod tests {
 
         let response_body = serde_json::json!({
             "path_display": "/results/uploaded.zip",
+            "content_hash": content_hash(TEST_FILE_CONTENT),
         });
 
         Mock::given(method("POST"))
@@ -193,7 +655,7 @@ mod tests {
 
         let base = mock_server.uri();
         let result = tokio::task::spawn_blocking(move || {
-            DropboxService.upload_file("valid-token", &file_path, "/results/uploaded.zip", &base)
+            service().upload_file("valid-token", &file_path, "/results/uploaded.zip", &base)
         })
         .await
         .expect("The upload_file task panicked. Expected the DropboxService to successfully upload the file and return the Dropbox path, but the task did not complete as expected.");
@@ -222,8 +684,7 @@ mod tests {
 
     #[test]
     fn upload_file_returns_error_when_local_file_not_found() {
-        let service = DropboxService;
-        let result = service.upload_file(
+        let result = service().upload_file(
             "fake-token",
             "/non/existent/file.zip",
             "/results/test.zip",
@@ -259,7 +720,7 @@ mod tests {
 
         let base = mock_server.uri();
         let result = tokio::task::spawn_blocking(move || {
-            DropboxService.upload_file("invalid-token", &file_path, "/results/uploaded.zip", &base)
+            service().upload_file("invalid-token", &file_path, "/results/uploaded.zip", &base)
         })
         .await
         .expect("Task panicked: expected DropboxService.upload_file to return an error indicating unauthorized access (PostComputeDropboxUploadFailed), but the task did not complete successfully");
@@ -289,7 +750,7 @@ mod tests {
 
         let base = mock_server.uri();
         let result = tokio::task::spawn_blocking(move || {
-            DropboxService.upload_file("token", &file_path, "/results/uploaded.zip", &base)
+            service().upload_file("token", &file_path, "/results/uploaded.zip", &base)
         })
         .await
         .expect("Task panicked: expected DropboxService.upload_file to return an error indicating a server error (PostComputeDropboxUploadFailed), but the task did not complete successfully");
@@ -319,7 +780,7 @@ mod tests {
 
         let base = mock_server.uri();
         let result = tokio::task::spawn_blocking(move || {
-            DropboxService.upload_file("token", &file_path, "/results/bad.json", &base)
+            service().upload_file("token", &file_path, "/results/bad.json", &base)
         })
         .await
         .expect("Task panicked: expected upload_file to return an error due to invalid JSON response, but the task did not complete successfully");
@@ -329,4 +790,447 @@ mod tests {
             Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
         );
     }
+
+    // region ContentHasher
+    #[test]
+    fn content_hasher_hashes_block_hashes_when_content_fits_in_one_block() {
+        let block_hash = Sha256::digest(TEST_FILE_CONTENT);
+
+        assert_eq!(
+            content_hash(TEST_FILE_CONTENT),
+            format!("{:x}", Sha256::digest(block_hash))
+        );
+    }
+
+    #[test]
+    fn content_hasher_splits_content_in_4_mib_blocks_whatever_the_updates() {
+        let content: Vec<u8> = (0..CONTENT_HASH_BLOCK_SIZE + 10)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let mut block_hashes = Sha256::digest(&content[..CONTENT_HASH_BLOCK_SIZE]).to_vec();
+        block_hashes.extend(Sha256::digest(&content[CONTENT_HASH_BLOCK_SIZE..]));
+        let expected = format!("{:x}", Sha256::digest(&block_hashes));
+
+        let mut hasher = ContentHasher::default();
+        for part in content.chunks(1_000_003) {
+            hasher.update(part);
+        }
+
+        assert_eq!(hasher.finalize(), expected);
+        assert_eq!(content_hash(&content), expected);
+        assert_eq!(content_hash(b""), format!("{:x}", Sha256::digest(b"")));
+    }
+    // endregion
+
+    // region from_env
+    #[test]
+    fn from_env_reads_chunk_size_when_valid() {
+        temp_env::with_var("RESULT_STORAGE_DROPBOX_CHUNK_SIZE", Some("4194304"), || {
+            assert_eq!(DropboxService::from_env().chunk_size, 4194304);
+        });
+    }
+
+    #[test]
+    fn from_env_uses_default_chunk_size_when_missing_or_invalid() {
+        for value in [None, Some("abc"), Some("0"), Some("157286401")] {
+            temp_env::with_var("RESULT_STORAGE_DROPBOX_CHUNK_SIZE", value, || {
+                assert_eq!(DropboxService::from_env().chunk_size, DEFAULT_CHUNK_SIZE);
+            });
+        }
+    }
+    // endregion
+
+    // region upload session
+    async fn mount_session_start(mock_server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_START_PATH))
+            .and(header("Authorization", "Bearer token"))
+            .and(body_bytes(&TEST_FILE_CONTENT[..5]))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "session_id": "session-1" })),
+            )
+            .expect(1)
+            .mount(mock_server)
+            .await;
+    }
+
+    async fn mount_session_finish(mock_server: &MockServer, content_hash: &str) {
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_FINISH_PATH))
+            .and(body_bytes(&TEST_FILE_CONTENT[15..]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "path_display": "/results/uploaded.zip",
+                "content_hash": content_hash,
+            })))
+            .expect(1)
+            .mount(mock_server)
+            .await;
+    }
+
+    async fn run_chunked_upload(mock_server: &MockServer) -> Result<String, ReplicateStatusCause> {
+        let file_path = create_test_computed_file();
+        let base = mock_server.uri();
+        tokio::task::spawn_blocking(move || {
+            chunked_service().upload_file("token", &file_path, "/results/uploaded.zip", &base)
+        })
+        .await
+        .expect("Task panicked")
+    }
+
+    #[tokio::test]
+    async fn upload_uses_session_when_file_larger_than_chunk_size() {
+        let mock_server = MockServer::start().await;
+        mount_session_start(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        mount_session_finish(&mock_server, &content_hash(TEST_FILE_CONTENT)).await;
+
+        let result = run_chunked_upload(&mock_server).await;
+
+        assert_eq!(result, Ok(String::from("/results/uploaded.zip")));
+        let requests = mock_server.received_requests().await.unwrap();
+        let paths: Vec<&str> = requests.iter().map(|r| r.url.path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                UPLOAD_SESSION_START_PATH,
+                UPLOAD_SESSION_APPEND_PATH,
+                UPLOAD_SESSION_APPEND_PATH,
+                UPLOAD_SESSION_FINISH_PATH
+            ]
+        );
+        assert_eq!(requests[1].body, &TEST_FILE_CONTENT[5..10]);
+        assert_eq!(
+            api_arg(&requests[1])["cursor"],
+            json!({ "offset": 5, "session_id": "session-1" })
+        );
+        assert_eq!(api_arg(&requests[2])["cursor"]["offset"], 10);
+        assert_eq!(
+            api_arg(&requests[3]),
+            json!({
+                "commit": commit_info("/results/uploaded.zip"),
+                "cursor": { "offset": 15, "session_id": "session-1" },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_retries_chunk_when_append_fails_temporarily() {
+        let mock_server = MockServer::start().await;
+        mount_session_start(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        mount_session_finish(&mock_server, &content_hash(TEST_FILE_CONTENT)).await;
+
+        let result = run_chunked_upload(&mock_server).await;
+
+        assert_eq!(result, Ok(String::from("/results/uploaded.zip")));
+    }
+
+    #[tokio::test]
+    async fn upload_accepts_chunk_already_appended_by_previous_attempt() {
+        let mock_server = MockServer::start().await;
+        mount_session_start(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "incorrect_offset/..",
+                "error": { ".tag": "incorrect_offset", "correct_offset": 10 }
+            })))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        mount_session_finish(&mock_server, &content_hash(TEST_FILE_CONTENT)).await;
+
+        let result = run_chunked_upload(&mock_server).await;
+
+        assert_eq!(result, Ok(String::from("/results/uploaded.zip")));
+    }
+
+    #[tokio::test]
+    async fn upload_returns_error_when_append_keeps_failing() {
+        let mock_server = MockServer::start().await;
+        mount_session_start(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+
+        let result = run_chunked_upload(&mock_server).await;
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_returns_error_when_session_content_hash_mismatches() {
+        let mock_server = MockServer::start().await;
+        mount_session_start(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+            .mount(&mock_server)
+            .await;
+        mount_session_finish(&mock_server, &content_hash(b"other content")).await;
+
+        let result = run_chunked_upload(&mock_server).await;
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_accepts_session_finished_by_previous_attempt() {
+        let mock_server = MockServer::start().await;
+        mount_session_start(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_FINISH_PATH))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_FINISH_PATH))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "lookup_failed/not_found/..",
+                "error": { ".tag": "lookup_failed", "lookup_failed": { ".tag": "not_found" } }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(GET_METADATA_PATH))
+            .and(header("Authorization", "Bearer token"))
+            .and(body_json(json!({ "path": "/results/uploaded.zip" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "path_display": "/results/uploaded.zip",
+                "content_hash": content_hash(TEST_FILE_CONTENT),
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = run_chunked_upload(&mock_server).await;
+
+        assert_eq!(result, Ok(String::from("/results/uploaded.zip")));
+    }
+
+    #[tokio::test]
+    async fn upload_returns_error_when_finish_conflicts() {
+        let mock_server = MockServer::start().await;
+        mount_session_start(&mock_server).await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_APPEND_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(UPLOAD_SESSION_FINISH_PATH))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "path/conflict/file/..",
+                "error": { ".tag": "path", "path": { ".tag": "conflict" } }
+            })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(GET_METADATA_PATH))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let result = run_chunked_upload(&mock_server).await;
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_returns_error_when_content_hash_missing() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(FILES_UPLOAD_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "path_display": "/results/uploaded.zip",
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let file_path = create_test_computed_file();
+        let base = mock_server.uri();
+        let result = tokio::task::spawn_blocking(move || {
+            service().upload_file("token", &file_path, "/results/uploaded.zip", &base)
+        })
+        .await
+        .expect("Task panicked");
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_verifies_content_hash_when_single_upload() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(FILES_UPLOAD_PATH))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "path_display": "/results/uploaded.zip",
+                "content_hash": content_hash(b"other content"),
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let file_path = create_test_computed_file();
+        let base = mock_server.uri();
+        let result = tokio::task::spawn_blocking(move || {
+            service().upload_file("token", &file_path, "/results/uploaded.zip", &base)
+        })
+        .await
+        .expect("Task panicked");
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+        );
+    }
+    // endregion
+
+    // region ResultStorage
+    #[test]
+    fn upload_result_returns_error_when_task_id_missing() {
+        let computed_file = ComputedFile {
+            task_id: None,
+            ..Default::default()
+        };
+
+        let result = service().upload(&computed_file, "token", "/no/file");
+        assert_eq!(result, Err(ReplicateStatusCause::PostComputeTaskIdMissing));
+    }
+
+    #[test]
+    fn upload_result_returns_error_when_file_not_found() {
+        let computed_file = create_test_task("0xdeadbeef");
+
+        let result = service().upload(&computed_file, "token", "/path/does/not/exist");
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeResultFileNotFound)
+        );
+    }
+
+    #[test]
+    fn upload_result_returns_error_when_local_path_is_directory() {
+        let computed_file = create_test_task("0xdir");
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = service().upload(&computed_file, "token", temp_dir.path().to_str().unwrap());
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+        );
+    }
+
+    #[test]
+    fn upload_result_returns_ok_when_upload_succeeds() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), b"content").unwrap();
+        let computed_file = create_test_task("0xsucc");
+        let file_path = temp_file.path().to_str().unwrap().to_string();
+
+        let mut mock_uploader = MockDropboxUploader::new();
+        mock_uploader
+            .expect_upload_file()
+            .with(
+                eq("test-token"),
+                eq(file_path.clone()),
+                eq("/results/0xsucc.zip"),
+                eq(DROPBOX_CONTENT_BASE_URL),
+            )
+            .times(1)
+            .returning(|_, _, _, _| Ok("/results/0xsucc.zip".to_string()));
+
+        let result = upload_result_with_uploader(
+            &computed_file,
+            "test-token",
+            &file_path,
+            &mock_uploader,
+            DROPBOX_CONTENT_BASE_URL,
+        );
+
+        assert_eq!(
+            result,
+            Ok(ResultLink::Dropbox(String::from("/results/0xsucc.zip")))
+        );
+    }
+
+    #[test]
+    fn upload_result_propagates_error_when_upload_fails() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), b"content").unwrap();
+        let computed_file = create_test_task("0xerr");
+        let file_path = temp_file.path().to_str().unwrap().to_string();
+
+        let mut mock_uploader = MockDropboxUploader::new();
+        mock_uploader
+            .expect_upload_file()
+            .with(
+                eq("test-token"),
+                eq(file_path.clone()),
+                eq("/results/0xerr.zip"),
+                eq(DROPBOX_CONTENT_BASE_URL),
+            )
+            .times(1)
+            .returning(|_, _, _, _| Err(ReplicateStatusCause::PostComputeDropboxUploadFailed));
+
+        let result = upload_result_with_uploader(
+            &computed_file,
+            "test-token",
+            &file_path,
+            &mock_uploader,
+            DROPBOX_CONTENT_BASE_URL,
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
+        );
+    }
+    // endregion
 }
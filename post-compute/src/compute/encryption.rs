@@ -1,4 +1,5 @@
 use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::utils::secret_utils::SecretBytes;
 use crate::compute::web2_result::{Web2ResultInterface, Web2ResultService};
 use aes::{
     Aes256,
@@ -10,6 +11,7 @@ use rand::{RngCore, rngs::OsRng};
 use rsa::{Pkcs1v15Encrypt, RsaPublicKey, pkcs8::DecodePublicKey};
 use sha3::{Digest, Sha3_256};
 use std::{fs, path::Path};
+use zeroize::Zeroizing;
 
 /// 256-bit key (32 bytes)
 const AES_KEY_LENGTH: usize = 32;
@@ -159,7 +161,7 @@ pub fn encrypt_data(
     })?;
 
     // Encrypt data with Base64 AES key
-    let encrypted_data = aes_encrypt(&data, &aes_key).map_err(|e| {
+    let encrypted_data = aes_encrypt(&data, aes_key.expose_secret()).map_err(|e| {
         error!("Failed to encrypt_data (aes_encrypt error) [in_data_file_path:{in_data_file_path}]: {e}");
         ReplicateStatusCause::PostComputeEncryptionFailed
     })?;
@@ -189,7 +191,7 @@ pub fn encrypt_data(
             error!("Failed to parse RSA public key: {e}");
             ReplicateStatusCause::PostComputeEncryptionFailed
         })?
-        .encrypt(&mut OsRng, Pkcs1v15Encrypt, &aes_key)
+        .encrypt(&mut OsRng, Pkcs1v15Encrypt, aes_key.expose_secret())
         .map_err(|e| {
             error!("RSA encryption failed: {e}");
             ReplicateStatusCause::PostComputeEncryptionFailed
@@ -235,8 +237,8 @@ pub fn encrypt_data(
 ///
 /// # Returns
 ///
-/// * `Result<Vec<u8>, ReplicateStatusCause>` - On success, returns `AES_KEY_LENGTH` bytes
-///   vector containing the AES-256 key. On failure, returns `PostComputeEncryptionFailed`.
+/// * `Result<SecretBytes, ReplicateStatusCause>` - On success, returns the `AES_KEY_LENGTH` bytes
+///   of the AES-256 key, zeroized when dropped. On failure, returns `PostComputeEncryptionFailed`.
 ///
 /// # Security
 ///
@@ -252,13 +254,13 @@ pub fn encrypt_data(
 /// # Note
 ///
 /// This is an internal helper method used by the public [`encrypt_data`] function.
-pub fn generate_aes_key() -> Result<Vec<u8>, ReplicateStatusCause> {
-    let mut key_bytes = [0u8; AES_KEY_LENGTH];
-    if let Err(e) = OsRng.try_fill_bytes(&mut key_bytes) {
+pub fn generate_aes_key() -> Result<SecretBytes, ReplicateStatusCause> {
+    let mut key_bytes = Zeroizing::new([0u8; AES_KEY_LENGTH]);
+    if let Err(e) = OsRng.try_fill_bytes(key_bytes.as_mut()) {
         error!("Failed to generate AES key: {e}");
         return Err(ReplicateStatusCause::PostComputeEncryptionFailed);
     }
-    Ok(key_bytes.to_vec())
+    Ok(SecretBytes::from(key_bytes.as_slice()))
 }
 
 /// Encrypts data using AES-256 in CBC mode with PKCS#7 padding.
@@ -745,7 +747,7 @@ FQIDAQAB
         let data = b"This is some test data.";
         let key = generate_aes_key().expect("Failed to generate AES key for test");
 
-        let encrypted_result = aes_encrypt(data, &key);
+        let encrypted_result = aes_encrypt(data, key.expose_secret());
         assert!(encrypted_result.is_ok());
 
         let encrypted_data = encrypted_result.unwrap();
@@ -780,7 +782,7 @@ FQIDAQAB
         let data = b"";
         let key = generate_aes_key().expect("Failed to generate AES key for test");
 
-        let encrypted_result = aes_encrypt(data, &key);
+        let encrypted_result = aes_encrypt(data, key.expose_secret());
         assert!(encrypted_result.is_err());
         assert_eq!(
             encrypted_result,
@@ -805,8 +807,8 @@ FQIDAQAB
         let data = b"test data";
         let key = generate_aes_key().unwrap();
 
-        let encrypted1 = aes_encrypt(data, &key).unwrap();
-        let encrypted2 = aes_encrypt(data, &key).unwrap();
+        let encrypted1 = aes_encrypt(data, key.expose_secret()).unwrap();
+        let encrypted2 = aes_encrypt(data, key.expose_secret()).unwrap();
         assert_ne!(encrypted1, encrypted2);
     }
 
@@ -18,16 +18,26 @@ pub enum ReplicateStatusCause {
     PostComputeFailedUnknownIssue,
     #[error("Invalid TEE signature")]
     PostComputeInvalidTeeSignature,
+    #[error("Failed to pin result on IPFS pinning service")]
+    PostComputeIpfsPinningFailed,
     #[error("Failed to upload to IPFS")]
     PostComputeIpfsUploadFailed,
+    #[error("Kubo API URL not found in TEE session")]
+    PostComputeKuboApiUrlMissing,
     #[error("Encryption public key is malformed")]
     PostComputeMalformedEncryptionPublicKey,
+    #[error("S3 credentials are malformed")]
+    PostComputeMalformedS3Credentials,
     #[error("Failed to zip result folder")]
     PostComputeOutFolderZipFailed,
     #[error("Empty resultDigest")]
     PostComputeResultDigestComputationFailed,
     #[error("Result file not found")]
     PostComputeResultFileNotFound,
+    #[error("S3 bucket not found in TEE session")]
+    PostComputeS3BucketMissing,
+    #[error("Failed to upload to S3")]
+    PostComputeS3UploadFailed,
     #[error("Failed to send computed file")]
     PostComputeSendComputedFileFailed,
     #[error("Storage token not found in TEE session")]
@@ -38,6 +48,8 @@ pub enum ReplicateStatusCause {
     PostComputeTeeChallengePrivateKeyMissing,
     #[error("Result file name too long")]
     PostComputeTooLongResultFileName,
+    #[error("Unknown result storage provider")]
+    PostComputeUnknownStorageProvider,
     #[error("Worker address not found in TEE session")]
     PostComputeWorkerAddressMissing,
 }
@@ -0,0 +1,653 @@
+//! Kubo upload service for adding results directly to an IPFS node.
+//!
+//! This module uploads computation results to the `/api/v0/add` endpoint of a Kubo-compatible
+//! IPFS HTTP API, without going through the iExec result proxy. The file is added and pinned
+//! with the default UnixFS parameters, so that the CID returned by the node can be checked
+//! against the CID recomputed locally with [`compute_file_cid`].
+//!
+//! When a pinning service is configured, the CID is also submitted to it through the IPFS
+//! Pinning Service API (`POST /pins`), for the result to remain available once the node is
+//! garbage collected or stopped.
+
+use crate::compute::{
+    computed_file::ComputedFile,
+    errors::ReplicateStatusCause,
+    result_storage::{ResultLink, ResultStorage},
+    utils::{
+        cid_utils::compute_file_cid,
+        env_utils::{TeeSessionEnvironmentVariable, get_env_var},
+        secret_utils::SecretString,
+    },
+};
+use log::{error, info};
+#[cfg(test)]
+use mockall::automock;
+use reqwest::{
+    blocking::{Body, Client},
+    header::{AUTHORIZATION, CONTENT_TYPE},
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+/// REST path for the Kubo "add" endpoint.
+const ADD_PATH: &str = "/api/v0/add";
+/// REST path for the Pinning Service API "pins" endpoint.
+const PINS_PATH: &str = "/pins";
+/// Parameters of the "add" endpoint, set explicitly to the defaults used by
+/// [`compute_file_cid`] so that the node configuration cannot change the resulting CID.
+const ADD_QUERY: [(&str, &str); 6] = [
+    ("pin", "true"),
+    ("cid-version", "0"),
+    ("raw-leaves", "false"),
+    ("chunker", "size-262144"),
+    ("hash", "sha2-256"),
+    ("trickle", "false"),
+];
+
+/// Pinning Service API endpoint the results are submitted to after being added.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinningServiceConfig {
+    pub url: String,
+    pub token: SecretString,
+}
+
+/// Location of the Kubo node, and optional pinning service, read from the TEE session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KuboConfig {
+    pub api_url: String,
+    pub pinning_service: Option<PinningServiceConfig>,
+}
+
+impl KuboConfig {
+    /// Reads the Kubo configuration from the `RESULT_STORAGE_KUBO_*` environment variables.
+    ///
+    /// The API URL is mandatory. The pinning service is only used when
+    /// `RESULT_STORAGE_KUBO_PINNING_SERVICE_URL` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PostComputeKuboApiUrlMissing` if `RESULT_STORAGE_KUBO_API_URL` is not set.
+    pub fn from_env() -> Result<Self, ReplicateStatusCause> {
+        let api_url = get_env_var(TeeSessionEnvironmentVariable::ResultStorageKuboApiUrl);
+        if api_url.is_empty() {
+            return Err(ReplicateStatusCause::PostComputeKuboApiUrlMissing);
+        }
+        let pinning_service =
+            match get_env_var(TeeSessionEnvironmentVariable::ResultStorageKuboPinningServiceUrl) {
+                url if url.is_empty() => None,
+                url => Some(PinningServiceConfig {
+                    url: url.trim_end_matches('/').to_string(),
+                    token: SecretString::from(get_env_var(
+                        TeeSessionEnvironmentVariable::ResultStorageKuboPinningServiceToken,
+                    )),
+                }),
+            };
+        Ok(Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            pinning_service,
+        })
+    }
+}
+
+/// Entry of the newline-delimited JSON response of the "add" endpoint.
+#[derive(Deserialize, Debug)]
+struct AddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+/// Service for handling uploads to a Kubo node.
+#[derive(Default)]
+pub struct KuboService;
+
+#[cfg_attr(test, automock)]
+pub trait KuboUploader {
+    fn upload_file(
+        &self,
+        token: &str,
+        local_file_path: &str,
+        file_name: &str,
+        config: &KuboConfig,
+    ) -> Result<String, ReplicateStatusCause>;
+}
+
+impl KuboService {
+    /// Streams the file to the "add" endpoint as a `multipart/form-data` request and returns
+    /// the CID reported by the node.
+    fn add(
+        &self,
+        client: &Client,
+        token: &str,
+        path: &Path,
+        file_name: &str,
+        config: &KuboConfig,
+    ) -> Result<String, ReplicateStatusCause> {
+        let file = File::open(path).map_err(|e| {
+            error!(
+                "Failed to open file for IPFS upload [path:{}, error:{e}]",
+                path.display()
+            );
+            ReplicateStatusCause::PostComputeIpfsUploadFailed
+        })?;
+        let file_size = file.metadata().map(|m| m.len()).map_err(|e| {
+            error!(
+                "Failed to read file for IPFS upload [path:{}, error:{e}]",
+                path.display()
+            );
+            ReplicateStatusCause::PostComputeIpfsUploadFailed
+        })?;
+        let boundary = format!("{:032x}", rand::random::<u128>());
+        let head = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+        );
+        let tail = format!("\r\n--{boundary}--\r\n");
+        let content_length = head.len() as u64 + file_size + tail.len() as u64;
+        let body = Cursor::new(head.into_bytes())
+            .chain(file)
+            .chain(Cursor::new(tail.into_bytes()));
+
+        let response = client
+            .post(format!("{}{ADD_PATH}", config.api_url))
+            .query(&ADD_QUERY)
+            .header(AUTHORIZATION, format!("Bearer {token}"))
+            .header(
+                CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(Body::sized(body, content_length))
+            .send()
+            .map_err(|e| {
+                error!("HTTP error calling Kubo API [file:{file_name}]: {e}");
+                ReplicateStatusCause::PostComputeIpfsUploadFailed
+            })?;
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        if !status.is_success() {
+            error!("Kubo add failed [status:{status}, body:{body}]");
+            return Err(ReplicateStatusCause::PostComputeIpfsUploadFailed);
+        }
+        body.lines()
+            .rev()
+            .find_map(|line| serde_json::from_str::<AddResponse>(line).ok())
+            .map(|entry| entry.hash)
+            .ok_or_else(|| {
+                error!("Missing Hash in Kubo add response [body:{body}]");
+                ReplicateStatusCause::PostComputeIpfsUploadFailed
+            })
+    }
+
+    /// Submits a CID to the pinning service, without waiting for the pin to complete.
+    fn pin(
+        &self,
+        client: &Client,
+        cid: &str,
+        file_name: &str,
+        pinning_service: &PinningServiceConfig,
+    ) -> Result<(), ReplicateStatusCause> {
+        let mut request = client
+            .post(format!("{}{PINS_PATH}", pinning_service.url))
+            .json(&json!({ "cid": cid, "name": file_name }));
+        if !pinning_service.token.is_empty() {
+            request = request.bearer_auth(pinning_service.token.expose_secret());
+        }
+        let response = request.send().map_err(|e| {
+            error!("HTTP error calling pinning service [cid:{cid}]: {e}");
+            ReplicateStatusCause::PostComputeIpfsPinningFailed
+        })?;
+        let status = response.status();
+        if status.is_success() {
+            info!("CID submitted to pinning service [cid:{cid}]");
+            Ok(())
+        } else {
+            let body = response.text().unwrap_or_default();
+            error!("Pinning service rejected CID [cid:{cid}, status:{status}, body:{body}]");
+            Err(ReplicateStatusCause::PostComputeIpfsPinningFailed)
+        }
+    }
+}
+
+impl KuboUploader for KuboService {
+    /// Adds a file to a Kubo node, pins it, and checks the CID returned by the node.
+    ///
+    /// # Arguments
+    ///
+    /// - `token`: Bearer token sent to the Kubo API
+    /// - `local_file_path`: Local path to the file to upload
+    /// - `file_name`: Name of the file in the upload request and on the pinning service
+    /// - `config`: URLs of the Kubo API and of the optional pinning service
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(String)`: The CIDv0 of the uploaded file (e.g., "QmHash...")
+    /// - `Err(ReplicateStatusCause)`: When any step of the upload fails
+    ///
+    /// # Errors
+    ///
+    /// Returns `PostComputeResultFileNotFound` if the local file does not exist.
+    /// Returns `PostComputeIpfsUploadFailed` for any HTTP or API error, or when the CID
+    /// returned by the node does not match the one recomputed locally.
+    /// Returns `PostComputeIpfsPinningFailed` if the pinning service rejects the CID.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tee_worker_post_compute::compute::kubo::{KuboConfig, KuboService, KuboUploader};
+    ///
+    /// let config = KuboConfig {
+    ///     api_url: "http://localhost:5001".to_string(),
+    ///     pinning_service: None,
+    /// };
+    /// let result = KuboService.upload_file("token", "/tmp/file.zip", "0x0.zip", &config);
+    /// // Handle result: Ok(cid) | Err(cause)
+    /// ```
+    fn upload_file(
+        &self,
+        token: &str,
+        local_file_path: &str,
+        file_name: &str,
+        config: &KuboConfig,
+    ) -> Result<String, ReplicateStatusCause> {
+        let path = Path::new(local_file_path);
+        if !path.exists() {
+            error!("Local file not found for IPFS upload [path:{local_file_path}]");
+            return Err(ReplicateStatusCause::PostComputeResultFileNotFound);
+        }
+        let expected_cid = compute_file_cid(path).map_err(|e| {
+            error!("Failed to compute CID of file [path:{local_file_path}, error:{e}]");
+            ReplicateStatusCause::PostComputeIpfsUploadFailed
+        })?;
+
+        let client = Client::new();
+        let cid = self.add(&client, token, path, file_name, config)?;
+        if cid != expected_cid {
+            error!("Kubo returned unexpected CID [expected:{expected_cid}, actual:{cid}]");
+            return Err(ReplicateStatusCause::PostComputeIpfsUploadFailed);
+        }
+        if let Some(pinning_service) = &config.pinning_service {
+            self.pin(&client, &cid, file_name, pinning_service)?;
+        }
+        info!("Successfully uploaded to IPFS [cid:{cid}, file:{file_name}]");
+        Ok(cid)
+    }
+}
+
+impl ResultStorage for KuboService {
+    /// Uploads the result archive of a task to the Kubo node as `{task_id}.zip`.
+    ///
+    /// The Kubo API URL and the optional pinning service are read from the
+    /// `RESULT_STORAGE_KUBO_*` variables of the TEE session.
+    ///
+    /// # Arguments
+    ///
+    /// * `computed_file` - The computed file metadata
+    /// * `token` - The bearer token of the Kubo API
+    /// * `file_to_upload_path` - Path to the local file to upload
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ResultLink)` - The IPFS path of the uploaded file (e.g. "/ipfs/QmHash...")
+    /// * `Err(ReplicateStatusCause)` - Upload error
+    fn upload(
+        &self,
+        computed_file: &ComputedFile,
+        token: &str,
+        file_to_upload_path: &str,
+    ) -> Result<ResultLink, ReplicateStatusCause> {
+        upload_result_with_uploader(computed_file, token, file_to_upload_path, self)
+    }
+}
+
+/// Internal implementation of the Kubo [`ResultStorage`] with dependency injection.
+/// This allows testing with mocked uploaders.
+fn upload_result_with_uploader<T: KuboUploader>(
+    computed_file: &ComputedFile,
+    token: &str,
+    file_to_upload_path: &str,
+    uploader: &T,
+) -> Result<ResultLink, ReplicateStatusCause> {
+    let task_id = computed_file
+        .task_id
+        .as_ref()
+        .ok_or(ReplicateStatusCause::PostComputeTaskIdMissing)?;
+    let config = KuboConfig::from_env()?;
+
+    if !Path::new(file_to_upload_path).exists() {
+        error!("File to upload not found [task_id:{task_id}, path:{file_to_upload_path}]");
+        return Err(ReplicateStatusCause::PostComputeResultFileNotFound);
+    }
+
+    info!(
+        "Uploading to IPFS node [task_id:{task_id}, local:{file_to_upload_path}, api:{}]",
+        config.api_url
+    );
+
+    let cid = uploader
+        .upload_file(
+            token,
+            file_to_upload_path,
+            &format!("{task_id}.zip"),
+            &config,
+        )
+        .map_err(|e| {
+            error!("IPFS upload failed [task_id:{task_id}, error:{e:?}]");
+            e
+        })?;
+    Ok(ResultLink::Ipfs(format!("/ipfs/{cid}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockall::predicate::eq;
+    use std::fs;
+    use tempfile::TempDir;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_json, header, method, path, query_param},
+    };
+
+    const TEST_TOKEN: &str = "kubo-token";
+    const CONTENT: &[u8] = b"hello world\n";
+    const CONTENT_CID: &str = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+
+    fn create_test_task(task_id: &str) -> ComputedFile {
+        ComputedFile {
+            task_id: Some(String::from(task_id)),
+            ..Default::default()
+        }
+    }
+
+    fn create_file() -> (TempDir, String) {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("result.zip");
+        fs::write(&file_path, CONTENT).unwrap();
+        (temp_dir, file_path.to_str().unwrap().to_string())
+    }
+
+    fn add_response(cid: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_string(format!(
+            "{{\"Name\":\"0x0.zip\",\"Hash\":\"{cid}\",\"Size\":\"20\"}}\n"
+        ))
+    }
+
+    async fn run_upload(
+        config: KuboConfig,
+        file_path: String,
+    ) -> Result<String, ReplicateStatusCause> {
+        tokio::task::spawn_blocking(move || {
+            KuboService.upload_file(TEST_TOKEN, &file_path, "0x0.zip", &config)
+        })
+        .await
+        .expect("Task panicked")
+    }
+
+    // region KuboConfig
+    #[test]
+    fn from_env_reads_full_configuration() {
+        temp_env::with_vars(
+            vec![
+                ("RESULT_STORAGE_KUBO_API_URL", Some("http://kubo:5001/")),
+                (
+                    "RESULT_STORAGE_KUBO_PINNING_SERVICE_URL",
+                    Some("https://pinning.example.com/psa/"),
+                ),
+                ("RESULT_STORAGE_KUBO_PINNING_SERVICE_TOKEN", Some("secret")),
+            ],
+            || {
+                assert_eq!(
+                    KuboConfig::from_env(),
+                    Ok(KuboConfig {
+                        api_url: "http://kubo:5001".to_string(),
+                        pinning_service: Some(PinningServiceConfig {
+                            url: "https://pinning.example.com/psa".to_string(),
+                            token: SecretString::from("secret"),
+                        }),
+                    })
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn from_env_skips_pinning_service_when_url_missing() {
+        temp_env::with_vars(
+            vec![
+                ("RESULT_STORAGE_KUBO_API_URL", Some("http://kubo:5001")),
+                ("RESULT_STORAGE_KUBO_PINNING_SERVICE_URL", None),
+                ("RESULT_STORAGE_KUBO_PINNING_SERVICE_TOKEN", Some("secret")),
+            ],
+            || {
+                let config = KuboConfig::from_env().unwrap();
+                assert_eq!(config.pinning_service, None);
+            },
+        );
+    }
+
+    #[test]
+    fn from_env_returns_error_when_api_url_missing() {
+        temp_env::with_var_unset("RESULT_STORAGE_KUBO_API_URL", || {
+            assert_eq!(
+                KuboConfig::from_env(),
+                Err(ReplicateStatusCause::PostComputeKuboApiUrlMissing)
+            );
+        });
+    }
+    // endregion
+
+    // region upload_file
+    #[test]
+    fn upload_file_returns_error_when_local_file_not_found() {
+        let config = KuboConfig {
+            api_url: "http://localhost".to_string(),
+            pinning_service: None,
+        };
+        let result =
+            KuboService.upload_file(TEST_TOKEN, "/non/existent/file.zip", "0x0.zip", &config);
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeResultFileNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_file_adds_file_and_returns_verified_cid() {
+        let (_temp_dir, file_path) = create_file();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ADD_PATH))
+            .and(query_param("pin", "true"))
+            .and(query_param("cid-version", "0"))
+            .and(query_param("chunker", "size-262144"))
+            .and(header("authorization", "Bearer kubo-token"))
+            .respond_with(add_response(CONTENT_CID))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let config = KuboConfig {
+            api_url: mock_server.uri(),
+            pinning_service: None,
+        };
+
+        let result = run_upload(config, file_path).await;
+
+        assert_eq!(result, Ok(String::from(CONTENT_CID)));
+        let requests = mock_server.received_requests().await.unwrap();
+        let content_type = requests[0].headers["content-type"].to_str().unwrap();
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let body = String::from_utf8(requests[0].body.clone()).unwrap();
+        assert_eq!(
+            body,
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"0x0.zip\"\r\nContent-Type: application/octet-stream\r\n\r\nhello world\n\r\n--{boundary}--\r\n"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_file_returns_error_when_cid_mismatches() {
+        let (_temp_dir, file_path) = create_file();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ADD_PATH))
+            .respond_with(add_response(
+                "QmUVhChbLFiuzNK1g2GsWyWEiad7SXPqARnWzGumgziwEp",
+            ))
+            .mount(&mock_server)
+            .await;
+        let config = KuboConfig {
+            api_url: mock_server.uri(),
+            pinning_service: None,
+        };
+
+        let result = run_upload(config, file_path).await;
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeIpfsUploadFailed)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_file_returns_error_when_node_rejects_file() {
+        let (_temp_dir, file_path) = create_file();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ADD_PATH))
+            .respond_with(ResponseTemplate::new(403).set_body_string("403 - Forbidden"))
+            .mount(&mock_server)
+            .await;
+        let config = KuboConfig {
+            api_url: mock_server.uri(),
+            pinning_service: None,
+        };
+
+        let result = run_upload(config, file_path).await;
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeIpfsUploadFailed)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_file_submits_cid_to_pinning_service() {
+        let (_temp_dir, file_path) = create_file();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ADD_PATH))
+            .respond_with(add_response(CONTENT_CID))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/psa/pins"))
+            .and(header("authorization", "Bearer pinning-token"))
+            .and(body_json(json!({ "cid": CONTENT_CID, "name": "0x0.zip" })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "requestid": "1",
+                "status": "queued",
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let config = KuboConfig {
+            api_url: mock_server.uri(),
+            pinning_service: Some(PinningServiceConfig {
+                url: format!("{}/psa", mock_server.uri()),
+                token: SecretString::from("pinning-token"),
+            }),
+        };
+
+        let result = run_upload(config, file_path).await;
+
+        assert_eq!(result, Ok(String::from(CONTENT_CID)));
+    }
+
+    #[tokio::test]
+    async fn upload_file_returns_error_when_pinning_service_rejects_cid() {
+        let (_temp_dir, file_path) = create_file();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(ADD_PATH))
+            .respond_with(add_response(CONTENT_CID))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/pins"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+        let config = KuboConfig {
+            api_url: mock_server.uri(),
+            pinning_service: Some(PinningServiceConfig {
+                url: mock_server.uri(),
+                token: SecretString::from("invalid-token"),
+            }),
+        };
+
+        let result = run_upload(config, file_path).await;
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeIpfsPinningFailed)
+        );
+    }
+    // endregion
+
+    // region ResultStorage
+    #[test]
+    fn upload_result_returns_ipfs_link_when_upload_succeeds() {
+        let (_temp_dir, file_path) = create_file();
+        let computed_file = create_test_task("0xsucc");
+        let expected_config = KuboConfig {
+            api_url: String::from("http://kubo:5001"),
+            pinning_service: None,
+        };
+
+        let mut mock_uploader = MockKuboUploader::new();
+        mock_uploader
+            .expect_upload_file()
+            .with(
+                eq(TEST_TOKEN),
+                eq(file_path.clone()),
+                eq("0xsucc.zip"),
+                eq(expected_config),
+            )
+            .times(1)
+            .returning(|_, _, _, _| Ok(CONTENT_CID.to_string()));
+
+        let result = temp_env::with_vars(
+            vec![
+                ("RESULT_STORAGE_KUBO_API_URL", Some("http://kubo:5001")),
+                ("RESULT_STORAGE_KUBO_PINNING_SERVICE_URL", None),
+            ],
+            || upload_result_with_uploader(&computed_file, TEST_TOKEN, &file_path, &mock_uploader),
+        );
+
+        assert_eq!(result, Ok(ResultLink::Ipfs(format!("/ipfs/{CONTENT_CID}"))));
+    }
+
+    #[test]
+    fn upload_result_returns_error_when_api_url_missing() {
+        let (_temp_dir, file_path) = create_file();
+        let computed_file = create_test_task("0x0");
+        let mut mock_uploader = MockKuboUploader::new();
+        mock_uploader.expect_upload_file().times(0);
+
+        let result = temp_env::with_var_unset("RESULT_STORAGE_KUBO_API_URL", || {
+            upload_result_with_uploader(&computed_file, TEST_TOKEN, &file_path, &mock_uploader)
+        });
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PostComputeKuboApiUrlMissing)
+        );
+    }
+    // endregion
+}
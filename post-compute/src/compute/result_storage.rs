@@ -0,0 +1,226 @@
+//! Result storage providers and their registry.
+//!
+//! Each storage backend implements [`ResultStorage`] and is registered in a
+//! [`ResultStorageRegistry`] under the name used in `RESULT_STORAGE_PROVIDER`.
+//! New providers can be added by registering them, without changing the upload
+//! workflow of [`crate::compute::web2_result`].
+
+use crate::api::result_proxy_api_client::ResultProxyApiClient;
+use crate::compute::{
+    computed_file::ComputedFile,
+    dropbox::DropboxService,
+    errors::ReplicateStatusCause,
+    kubo::KuboService,
+    s3::S3Service,
+    utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var},
+};
+use log::error;
+#[cfg(test)]
+use mockall::automock;
+use std::{collections::HashMap, fmt};
+
+pub const IPFS_RESULT_STORAGE_PROVIDER: &str = "ipfs";
+pub const DROPBOX_RESULT_STORAGE_PROVIDER: &str = "dropbox";
+pub const KUBO_RESULT_STORAGE_PROVIDER: &str = "kubo";
+pub const S3_RESULT_STORAGE_PROVIDER: &str = "s3";
+
+/// Location of a result uploaded to a storage provider.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResultLink {
+    /// IPFS path of the result, returned by the iExec result proxy or a Kubo node
+    /// (e.g. "/ipfs/QmHash...")
+    Ipfs(String),
+    /// Display path of the file in the Dropbox account (e.g. "/results/0x0.zip")
+    Dropbox(String),
+    /// Object stored in an S3 bucket
+    S3 { bucket: String, key: String },
+    /// Link returned by a third-party provider
+    Other(String),
+}
+
+impl fmt::Display for ResultLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResultLink::Ipfs(link) | ResultLink::Dropbox(link) | ResultLink::Other(link) => {
+                f.write_str(link)
+            }
+            ResultLink::S3 { bucket, key } => write!(f, "s3://{bucket}/{key}"),
+        }
+    }
+}
+
+/// Storage backend receiving the result archive of a task.
+#[cfg_attr(test, automock)]
+pub trait ResultStorage: Send + Sync {
+    /// Uploads the result archive of a task.
+    ///
+    /// # Arguments
+    ///
+    /// * `computed_file` - The [`ComputedFile`] containing task metadata
+    /// * `token` - The storage token read from the TEE session
+    /// * `file_to_upload_path` - Path to the file that should be uploaded
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ResultLink)` - The location of the uploaded result
+    /// * `Err(ReplicateStatusCause)` - Upload failed
+    fn upload(
+        &self,
+        computed_file: &ComputedFile,
+        token: &str,
+        file_to_upload_path: &str,
+    ) -> Result<ResultLink, ReplicateStatusCause>;
+}
+
+/// Result storage providers, keyed by the name used in `RESULT_STORAGE_PROVIDER`.
+///
+/// # Example
+///
+/// ```rust
+/// use tee_worker_post_compute::compute::{
+///     computed_file::ComputedFile,
+///     errors::ReplicateStatusCause,
+///     result_storage::{ResultLink, ResultStorage, ResultStorageRegistry},
+/// };
+///
+/// struct LocalStorage;
+///
+/// impl ResultStorage for LocalStorage {
+///     fn upload(
+///         &self,
+///         _computed_file: &ComputedFile,
+///         _token: &str,
+///         file_to_upload_path: &str,
+///     ) -> Result<ResultLink, ReplicateStatusCause> {
+///         Ok(ResultLink::Other(format!("file://{file_to_upload_path}")))
+///     }
+/// }
+///
+/// let mut storages = ResultStorageRegistry::from_env();
+/// storages.register("local", LocalStorage);
+/// assert!(storages.get("local").is_ok());
+/// assert!(storages.get("unknown").is_err());
+/// ```
+#[derive(Default)]
+pub struct ResultStorageRegistry {
+    storages: HashMap<String, Box<dyn ResultStorage>>,
+}
+
+impl ResultStorageRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry holding the built-in providers: `ipfs`, `kubo`, `dropbox` and `s3`.
+    ///
+    /// The `ipfs` provider uploads through the result proxy read from `RESULT_STORAGE_PROXY`,
+    /// while the `kubo` provider adds results directly to the node read from
+    /// `RESULT_STORAGE_KUBO_API_URL`.
+    pub fn from_env() -> Self {
+        let mut registry = Self::new();
+        registry.register(
+            IPFS_RESULT_STORAGE_PROVIDER,
+            ResultProxyApiClient::new(&get_env_var(
+                TeeSessionEnvironmentVariable::ResultStorageProxy,
+            )),
+        );
+        registry.register(DROPBOX_RESULT_STORAGE_PROVIDER, DropboxService::from_env());
+        registry.register(KUBO_RESULT_STORAGE_PROVIDER, KuboService);
+        registry.register(S3_RESULT_STORAGE_PROVIDER, S3Service::default());
+        registry
+    }
+
+    /// Registers a storage under a provider name, replacing any storage already registered
+    /// under this name.
+    pub fn register(&mut self, provider: &str, storage: impl ResultStorage + 'static) {
+        self.storages
+            .insert(provider.to_string(), Box::new(storage));
+    }
+
+    /// Returns the storage registered under a provider name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PostComputeUnknownStorageProvider` if no storage is registered under this name.
+    pub fn get(&self, provider: &str) -> Result<&dyn ResultStorage, ReplicateStatusCause> {
+        match self.storages.get(provider) {
+            Some(storage) => Ok(storage.as_ref()),
+            None => {
+                error!(
+                    "Unknown storage provider [provider:{provider}, supported:{}]",
+                    self.providers().join(",")
+                );
+                Err(ReplicateStatusCause::PostComputeUnknownStorageProvider)
+            }
+        }
+    }
+
+    /// Returns the names of the registered providers, sorted alphabetically.
+    pub fn providers(&self) -> Vec<&str> {
+        let mut providers: Vec<&str> = self.storages.keys().map(String::as_str).collect();
+        providers.sort();
+        providers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_link_displays_storage_location() {
+        assert_eq!(
+            ResultLink::Ipfs(String::from("/ipfs/QmHash")).to_string(),
+            "/ipfs/QmHash"
+        );
+        assert_eq!(
+            ResultLink::Dropbox(String::from("/results/0x0.zip")).to_string(),
+            "/results/0x0.zip"
+        );
+        assert_eq!(
+            ResultLink::S3 {
+                bucket: String::from("results"),
+                key: String::from("iexec/0x0.zip"),
+            }
+            .to_string(),
+            "s3://results/iexec/0x0.zip"
+        );
+    }
+
+    #[test]
+    fn from_env_registers_built_in_providers() {
+        let registry = ResultStorageRegistry::from_env();
+
+        assert_eq!(registry.providers(), vec!["dropbox", "ipfs", "kubo", "s3"]);
+    }
+
+    #[test]
+    fn get_returns_registered_storage() {
+        let mut storage = MockResultStorage::new();
+        storage
+            .expect_upload()
+            .returning(|_, _, _| Ok(ResultLink::Other(String::from("custom://0x0"))));
+        let mut registry = ResultStorageRegistry::new();
+        registry.register("custom", storage);
+
+        let result = registry.get("custom").unwrap().upload(
+            &ComputedFile::default(),
+            "token",
+            "/tmp/result.zip",
+        );
+
+        assert_eq!(result, Ok(ResultLink::Other(String::from("custom://0x0"))));
+    }
+
+    #[test]
+    fn get_returns_error_when_provider_unknown() {
+        let registry = ResultStorageRegistry::from_env();
+
+        assert!(matches!(
+            registry.get("unknown-provider"),
+            Err(ReplicateStatusCause::PostComputeUnknownStorageProvider)
+        ));
+        assert!(registry.get("").is_err());
+    }
+}
@@ -3,6 +3,7 @@ use crate::compute::{
     utils::{
         env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error},
         hash_utils::{concatenate_and_hash, hex_string_to_byte_array},
+        secret_utils::SecretString,
     },
 };
 use alloy_signer::{Signature, SignerSync};
@@ -106,12 +107,13 @@ pub fn get_challenge(chain_task_id: &str) -> Result<String, ReplicateStatusCause
         TeeSessionEnvironmentVariable::SignWorkerAddress,
         ReplicateStatusCause::PostComputeWorkerAddressMissing,
     )?;
-    let tee_challenge_private_key: String = get_env_var_or_error(
+    let tee_challenge_private_key: SecretString = get_env_var_or_error(
         TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey,
         ReplicateStatusCause::PostComputeTeeChallengePrivateKeyMissing,
-    )?;
+    )
+    .map(SecretString::from)?;
     let message_hash: String = concatenate_and_hash(&[chain_task_id, &worker_address]);
-    sign_enclave_challenge(&message_hash, &tee_challenge_private_key)
+    sign_enclave_challenge(&message_hash, tee_challenge_private_key.expose_secret())
 }
 
 #[cfg(test)]
@@ -1,3 +1,5 @@
+pub mod cid_utils;
 pub mod env_utils;
 pub mod hash_utils;
 pub mod result_utils;
+pub mod secret_utils;
@@ -0,0 +1,56 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+use tee_worker_unixfs::{UNIXFS_CHUNK_SIZE, UnixFsFileHasher};
+
+/// Computes the CIDv0 a file gets when added to IPFS with default parameters, encoded in
+/// base58btc (e.g. "QmHash...").
+///
+/// The file is read in chunks, so that memory usage does not depend on its size.
+pub fn compute_file_cid(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = UnixFsFileHasher::default();
+    let mut buffer = vec![0u8; UNIXFS_CHUNK_SIZE];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => return Ok(bs58::encode(hasher.finalize()).into_string()),
+            read => hasher.update(&buffer[..read]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn compute_file_cid_matches_known_cids() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_cases: [(&[u8], &str); 2] = [
+            (
+                b"hello world\n",
+                "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            ),
+            // head -c 1048577 /dev/zero | ipfs add -Q
+            (
+                &vec![0u8; 4 * UNIXFS_CHUNK_SIZE + 1],
+                "Qmeb988ZjF9Ui6AVPR8Sjg5sAv1B6DauS5rUjCoNs7ftZ1",
+            ),
+        ];
+        for (content, expected) in test_cases {
+            let file_path = temp_dir.path().join("result.zip");
+            fs::write(&file_path, content).unwrap();
+            assert_eq!(compute_file_cid(&file_path).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn compute_file_cid_fails_when_file_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(compute_file_cid(&temp_dir.path().join("missing.zip")).is_err());
+    }
+}
@@ -6,8 +6,16 @@ pub enum TeeSessionEnvironmentVariable {
     ResultEncryption,
     ResultEncryptionPublicKey,
     ResultStorageCallback,
+    ResultStorageDropboxChunkSize,
+    ResultStorageKuboApiUrl,
+    ResultStorageKuboPinningServiceToken,
+    ResultStorageKuboPinningServiceUrl,
     ResultStorageProvider,
     ResultStorageProxy,
+    ResultStorageS3Bucket,
+    ResultStorageS3Endpoint,
+    ResultStorageS3Prefix,
+    ResultStorageS3Region,
     ResultStorageToken,
     SignTeeChallengePrivateKey,
     SignWorkerAddress,
@@ -23,8 +31,22 @@ impl TeeSessionEnvironmentVariable {
                 "RESULT_ENCRYPTION_PUBLIC_KEY"
             }
             TeeSessionEnvironmentVariable::ResultStorageCallback => "RESULT_STORAGE_CALLBACK",
+            TeeSessionEnvironmentVariable::ResultStorageDropboxChunkSize => {
+                "RESULT_STORAGE_DROPBOX_CHUNK_SIZE"
+            }
+            TeeSessionEnvironmentVariable::ResultStorageKuboApiUrl => "RESULT_STORAGE_KUBO_API_URL",
+            TeeSessionEnvironmentVariable::ResultStorageKuboPinningServiceToken => {
+                "RESULT_STORAGE_KUBO_PINNING_SERVICE_TOKEN"
+            }
+            TeeSessionEnvironmentVariable::ResultStorageKuboPinningServiceUrl => {
+                "RESULT_STORAGE_KUBO_PINNING_SERVICE_URL"
+            }
             TeeSessionEnvironmentVariable::ResultStorageProvider => "RESULT_STORAGE_PROVIDER",
             TeeSessionEnvironmentVariable::ResultStorageProxy => "RESULT_STORAGE_PROXY",
+            TeeSessionEnvironmentVariable::ResultStorageS3Bucket => "RESULT_STORAGE_S3_BUCKET",
+            TeeSessionEnvironmentVariable::ResultStorageS3Endpoint => "RESULT_STORAGE_S3_ENDPOINT",
+            TeeSessionEnvironmentVariable::ResultStorageS3Prefix => "RESULT_STORAGE_S3_PREFIX",
+            TeeSessionEnvironmentVariable::ResultStorageS3Region => "RESULT_STORAGE_S3_REGION",
             TeeSessionEnvironmentVariable::ResultStorageToken => "RESULT_STORAGE_TOKEN",
             TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey => {
                 "SIGN_TEE_CHALLENGE_PRIVATE_KEY"
@@ -0,0 +1,143 @@
+use std::fmt;
+use zeroize::Zeroize;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Text secret, such as a storage token or a private key, overwritten with zeros when dropped.
+///
+/// The value is redacted when formatted with `Debug` or `Display`, so that it never shows up in
+/// logs. It is only readable through [`SecretString::expose_secret`].
+///
+/// # Example
+///
+/// ```rust
+/// use tee_worker_post_compute::compute::utils::secret_utils::SecretString;
+///
+/// let token = SecretString::from("dropbox-access-token");
+/// assert_eq!(format!("{token:?}"), "SecretString([REDACTED])");
+/// assert_eq!(token.expose_secret(), "dropbox-access-token");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(secret: String) -> Self {
+        SecretString(secret)
+    }
+
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        SecretString::new(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> Self {
+        SecretString::new(secret.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretString({REDACTED})")
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Binary secret, such as a generated AES key, overwritten with zeros when dropped.
+///
+/// The value is redacted when formatted with `Debug`. It is only readable through
+/// [`SecretBytes::expose_secret`].
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn new(secret: Vec<u8>) -> Self {
+        SecretBytes(secret)
+    }
+
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(secret: Vec<u8>) -> Self {
+        SecretBytes::new(secret)
+    }
+}
+
+impl From<&[u8]> for SecretBytes {
+    fn from(secret: &[u8]) -> Self {
+        SecretBytes::new(secret.to_vec())
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes({REDACTED})")
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0xdd3b993ec21c71c1f6d63a5240850e0d4d8dd83ff70d29e49247958548c1d479";
+
+    #[test]
+    fn secret_string_is_redacted_when_formatted() {
+        let secret = SecretString::from(SECRET);
+
+        assert_eq!(format!("{secret:?}"), "SecretString([REDACTED])");
+        assert_eq!(format!("{secret}"), "[REDACTED]");
+        assert_eq!(
+            format!("{:?}", Some(secret.clone())),
+            "Some(SecretString([REDACTED]))"
+        );
+        assert_eq!(secret.expose_secret(), SECRET);
+    }
+
+    #[test]
+    fn secret_bytes_is_redacted_when_formatted() {
+        let secret = SecretBytes::from(vec![1u8, 2, 3]);
+
+        assert_eq!(format!("{secret:?}"), "SecretBytes([REDACTED])");
+        assert_eq!(secret.expose_secret(), &[1, 2, 3]);
+        assert_eq!(secret.len(), 3);
+    }
+}
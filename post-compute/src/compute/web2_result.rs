@@ -1,17 +1,19 @@
-use crate::api::result_proxy_api_client::{ResultModel, ResultProxyApiClient};
 use crate::compute::{
     computed_file::ComputedFile,
-    dropbox::{DROPBOX_CONTENT_BASE_URL, DropboxService, DropboxUploader},
     encryption::encrypt_data,
     errors::ReplicateStatusCause,
-    utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var, get_env_var_or_error},
+    result_storage::{IPFS_RESULT_STORAGE_PROVIDER, ResultLink, ResultStorageRegistry},
+    utils::{
+        env_utils::{TeeSessionEnvironmentVariable, get_env_var, get_env_var_or_error},
+        secret_utils::SecretString,
+    },
 };
 use base64::{Engine as _, engine::general_purpose};
 use log::{debug, error, info};
 #[cfg(test)]
 use mockall::automock;
 use std::{
-    fs::{self, File},
+    fs::File,
     io::{self, Write},
     path::{Path, PathBuf},
 };
@@ -20,8 +22,6 @@ use walkdir::WalkDir;
 use zip::{ZipWriter, write::FileOptions};
 
 const RESULT_FILE_NAME_MAX_LENGTH: usize = 31;
-const IPFS_RESULT_STORAGE_PROVIDER: &str = "ipfs";
-const DROPBOX_RESULT_STORAGE_PROVIDER: &str = "dropbox";
 
 /// Trait defining the interface for Web2 result processing operations.
 ///
@@ -56,20 +56,7 @@ pub trait Web2ResultInterface {
         &self,
         computed_file: &ComputedFile,
         file_to_upload_path: &str,
-    ) -> Result<String, ReplicateStatusCause>;
-    fn upload_to_ipfs_with_iexec_proxy(
-        &self,
-        computed_file: &ComputedFile,
-        base_url: &str,
-        token: &str,
-        file_to_upload_path: &str,
-    ) -> Result<String, ReplicateStatusCause>;
-    fn upload_to_dropbox(
-        &self,
-        computed_file: &ComputedFile,
-        token: &str,
-        file_to_upload_path: &str,
-    ) -> Result<String, ReplicateStatusCause>;
+    ) -> Result<ResultLink, ReplicateStatusCause>;
 }
 
 /// Production implementation of [`Web2ResultInterface`].
@@ -178,42 +165,57 @@ impl Web2ResultService {
             })
     }
 
-    /// Internal implementation of the upload_to_dropbox function for uploading to Dropbox with dependency injection.
-    /// This allows testing with mocked uploaders.
-    fn upload_to_dropbox_with_uploader<T: DropboxUploader>(
+    /// Uploads the compressed result with the storage registered for the configured provider.
+    ///
+    /// The provider is read from `RESULT_STORAGE_PROVIDER` and defaults to `ipfs`, the upload
+    /// through the result proxy, when missing. Uploading to IPFS through a Kubo node must be
+    /// requested explicitly with `kubo`.
+    /// Third-party providers can be supported by registering them in `storages`.
+    ///
+    /// # Arguments
+    ///
+    /// * `computed_file` - The [`ComputedFile`] containing task metadata
+    /// * `file_to_upload_path` - Path to the file that should be uploaded
+    /// * `storages` - The storages available, keyed by provider name
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ResultLink)` - The location where the result was uploaded
+    /// * `Err(ReplicateStatusCause)` - Upload failed, or no storage is registered for the provider
+    pub fn upload_result_with_storages(
         &self,
         computed_file: &ComputedFile,
-        token: &str,
         file_to_upload_path: &str,
-        uploader: &T,
-    ) -> Result<String, ReplicateStatusCause> {
-        let task_id = computed_file
-            .task_id
-            .as_ref()
-            .ok_or(ReplicateStatusCause::PostComputeTaskIdMissing)?;
-        let remote_filename = format!("{task_id}.zip");
-        let dropbox_path = format!("/results/{remote_filename}");
-
-        if !Path::new(file_to_upload_path).exists() {
-            error!("File to upload not found [task_id:{task_id}, path:{file_to_upload_path}]");
-            return Err(ReplicateStatusCause::PostComputeResultFileNotFound);
-        }
+        storages: &ResultStorageRegistry,
+    ) -> Result<ResultLink, ReplicateStatusCause> {
+        info!("Upload stage started");
+        let storage_provider =
+            match get_env_var(TeeSessionEnvironmentVariable::ResultStorageProvider) {
+                provider if provider.is_empty() => {
+                    info!(
+                        "No storage provider configured, falling back to IPFS [task_id:{}]",
+                        computed_file.task_id.as_deref().unwrap_or_default()
+                    );
+                    IPFS_RESULT_STORAGE_PROVIDER.to_string()
+                }
+                provider => provider,
+            };
+        let storage_token = get_env_var_or_error(
+            TeeSessionEnvironmentVariable::ResultStorageToken,
+            ReplicateStatusCause::PostComputeStorageTokenMissing,
+        )
+        .map(SecretString::from)?;
 
-        info!(
-            "Uploading to Dropbox [task_id:{task_id}, local:{file_to_upload_path}, remote:{dropbox_path}]"
-        );
+        let storage = storages.get(&storage_provider)?;
+        info!("Upload stage mode: {storage_provider}");
+        let result_link = storage.upload(
+            computed_file,
+            storage_token.expose_secret(),
+            file_to_upload_path,
+        )?;
 
-        uploader
-            .upload_file(
-                token,
-                file_to_upload_path,
-                &dropbox_path,
-                DROPBOX_CONTENT_BASE_URL,
-            )
-            .map_err(|e| {
-                error!("Dropbox upload failed [task_id:{task_id}, error:{e:?}]");
-                e
-            })
+        info!("Upload stage completed [link:{result_link}]");
+        Ok(result_link)
     }
 }
 
@@ -484,9 +486,9 @@ impl Web2ResultInterface for Web2ResultService {
 
     /// Uploads the compressed result to the configured storage provider.
     ///
-    /// This method handles the upload process to the configured storage system.
-    /// Currently supports IPFS through the iExec result proxy, with the potential
-    /// for additional storage providers in the future.
+    /// This method handles the upload process to the configured storage system, using the
+    /// built-in providers of [`ResultStorageRegistry::from_env`]: IPFS through the iExec
+    /// result proxy (`ipfs`) or directly through a Kubo node (`kubo`), Dropbox and S3.
     ///
     /// # Arguments
     ///
@@ -495,127 +497,17 @@ impl Web2ResultInterface for Web2ResultService {
     ///
     /// # Returns
     ///
-    /// * `Ok(String)` - The storage link where the result was uploaded
+    /// * `Ok(ResultLink)` - The location where the result was uploaded
     /// * `Err(ReplicateStatusCause)` - Upload failed
-    #[allow(clippy::wildcard_in_or_patterns)]
     fn upload_result(
         &self,
         computed_file: &ComputedFile,
         file_to_upload_path: &str,
-    ) -> Result<String, ReplicateStatusCause> {
-        info!("Upload stage started");
-        let storage_provider = get_env_var(TeeSessionEnvironmentVariable::ResultStorageProvider);
-        let storage_token = get_env_var_or_error(
-            TeeSessionEnvironmentVariable::ResultStorageToken,
-            ReplicateStatusCause::PostComputeStorageTokenMissing,
-        )?;
-
-        let result_link = match storage_provider.as_str() {
-            DROPBOX_RESULT_STORAGE_PROVIDER => {
-                info!("Upload stage mode: DROPBOX_STORAGE");
-                self.upload_to_dropbox(computed_file, &storage_token, file_to_upload_path)?
-            }
-            IPFS_RESULT_STORAGE_PROVIDER | _ => {
-                if storage_provider.is_empty() {
-                    info!(
-                        "Unknown storage provider '{storage_provider}', falling back to IPFS [task_id:{}]",
-                        computed_file.task_id.as_ref().unwrap()
-                    );
-                }
-                info!("Upload stage mode: IPFS_STORAGE");
-                let storage_proxy = get_env_var_or_error(
-                    TeeSessionEnvironmentVariable::ResultStorageProxy,
-                    ReplicateStatusCause::PostComputeFailedUnknownIssue, //TODO Define better error
-                )?;
-                self.upload_to_ipfs_with_iexec_proxy(
-                    computed_file,
-                    &storage_proxy,
-                    &storage_token,
-                    file_to_upload_path,
-                )?
-            }
-        };
-
-        info!("Upload stage completed");
-        Ok(result_link)
-    }
-
-    /// Uploads a file to IPFS using the iExec result proxy service.
-    ///
-    /// This method specifically handles uploads to IPFS through the iExec result proxy.
-    /// It creates a [`ResultModel`] with the necessary metadata and sends it to the
-    /// proxy service for IPFS storage.
-    ///
-    /// # Arguments
-    ///
-    /// * `computed_file` - The [`ComputedFile`] containing task metadata
-    /// * `base_url` - The base URL of the result proxy service
-    /// * `token` - Authentication token for the result proxy
-    /// * `file_to_upload_path` - Path to the file that should be uploaded
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(String)` - The IPFS link where the result was stored
-    /// * `Err(ReplicateStatusCause)` - Upload failed
-    fn upload_to_ipfs_with_iexec_proxy(
-        &self,
-        computed_file: &ComputedFile,
-        base_url: &str,
-        token: &str,
-        file_to_upload_path: &str,
-    ) -> Result<String, ReplicateStatusCause> {
-        let task_id = computed_file.task_id.as_ref().unwrap();
-
-        let file_to_upload = fs::read(file_to_upload_path).map_err(|e| {
-            error!(
-                "Can't upload_to_ipfs_with_iexec_proxy (missing file_path to upload) [task_id:{task_id}, file_to_upload_path:{file_to_upload_path}]: {e}"
-            );
-            ReplicateStatusCause::PostComputeResultFileNotFound
-        })?;
-
-        let result_model = ResultModel {
-            chain_task_id: task_id.clone(),
-            determinist_hash: computed_file.result_digest.as_ref().unwrap().clone(),
-            enclave_signature: computed_file.enclave_signature.as_ref().unwrap().clone(),
-            zip: file_to_upload,
-            ..Default::default()
-        };
-
-        let client = ResultProxyApiClient::new(base_url);
-        match client.upload_to_ipfs(token, &result_model) {
-            Ok(ipfs_link) => Ok(ipfs_link),
-            Err(e) => {
-                error!(
-                    "Can't upload_to_ipfs_with_iexec_proxy (result proxy issue) [task_id:{task_id}]: {e}"
-                );
-                Err(ReplicateStatusCause::PostComputeIpfsUploadFailed)
-            }
-        }
-    }
-
-    /// Uploads a file to Dropbox storage.
-    ///
-    /// # Arguments
-    ///
-    /// * `computed_file` - The computed file metadata
-    /// * `token` - The Dropbox access token
-    /// * `file_to_upload_path` - Path to the local file to upload
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(String)` - The Dropbox path where the file was uploaded
-    /// * `Err(ReplicateStatusCause)` - Upload error
-    fn upload_to_dropbox(
-        &self,
-        computed_file: &ComputedFile,
-        token: &str,
-        file_to_upload_path: &str,
-    ) -> Result<String, ReplicateStatusCause> {
-        self.upload_to_dropbox_with_uploader(
+    ) -> Result<ResultLink, ReplicateStatusCause> {
+        self.upload_result_with_storages(
             computed_file,
-            token,
             file_to_upload_path,
-            &DropboxService,
+            &ResultStorageRegistry::from_env(),
         )
     }
 }
@@ -623,15 +515,12 @@ impl Web2ResultInterface for Web2ResultService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::compute::dropbox::MockDropboxUploader;
+    use crate::compute::result_storage::MockResultStorage;
     use mockall::predicate::{eq, function};
+    use std::fs;
     use std::os::unix::fs::symlink;
     use temp_env::{self, with_vars};
     use tempfile::{NamedTempFile, TempDir, tempdir};
-    use wiremock::{
-        Mock, MockServer, ResponseTemplate,
-        matchers::{method, path},
-    };
     use zip::ZipArchive;
 
     fn create_test_computed_file(task_id: &str) -> ComputedFile {
@@ -697,7 +586,11 @@ mod tests {
                 function(|path: &str| path.ends_with("iexec_out.zip")),
             )
             .times(1)
-            .returning(|_, _| Ok(String::from("https://ipfs.io/ipfs/QmHash")));
+            .returning(|_, _| {
+                Ok(ResultLink::Ipfs(String::from(
+                    "https://ipfs.io/ipfs/QmHash",
+                )))
+            });
 
         let result = run_encrypt_and_upload_result(&web2_result_mock, &computed_file);
         assert!(result.is_ok());
@@ -1430,129 +1323,97 @@ mod tests {
     // endregion
 
     // region upload_result
-    #[allow(clippy::wildcard_in_or_patterns)]
-    fn run_upload_result<T: Web2ResultInterface>(
-        service: &T,
-        computed_file: &ComputedFile,
-        file_to_upload_path: &str,
-    ) -> Result<String, ReplicateStatusCause> {
-        info!("Upload stage started");
-        let storage_provider = get_env_var(TeeSessionEnvironmentVariable::ResultStorageProvider);
-        let storage_token = get_env_var_or_error(
-            TeeSessionEnvironmentVariable::ResultStorageToken,
-            ReplicateStatusCause::PostComputeStorageTokenMissing,
-        )?;
-        let result_link = match storage_provider.as_str() {
-            DROPBOX_RESULT_STORAGE_PROVIDER => {
-                info!("Upload stage mode: DROPBOX_STORAGE");
-                service.upload_to_dropbox(computed_file, &storage_token, file_to_upload_path)?
-            }
-            IPFS_RESULT_STORAGE_PROVIDER | _ => {
-                if storage_provider.is_empty() {
-                    info!(
-                        "Unknown storage provider '{storage_provider}', falling back to IPFS [task_id:{}]",
-                        computed_file.task_id.as_ref().unwrap()
-                    );
-                }
-                info!("Upload stage mode: IPFS_STORAGE");
-                let storage_proxy = get_env_var_or_error(
-                    TeeSessionEnvironmentVariable::ResultStorageProxy,
-                    ReplicateStatusCause::PostComputeFailedUnknownIssue, //TODO Define better error
-                )?;
-                service.upload_to_ipfs_with_iexec_proxy(
-                    computed_file,
-                    &storage_proxy,
-                    &storage_token,
-                    file_to_upload_path,
-                )?
-            }
-        };
-
-        info!("Upload stage completed");
-        Ok(result_link)
+    fn storages_with(provider: &str, storage: MockResultStorage) -> ResultStorageRegistry {
+        let mut storages = ResultStorageRegistry::new();
+        storages.register(provider, storage);
+        storages
     }
 
-    fn run_upload_result_ipfs(provider: &str) {
+    fn run_upload_result_with_provider(provider: Option<&str>, registered_provider: &str) {
         temp_env::with_vars(
             vec![
-                ("RESULT_STORAGE_PROVIDER", Some(provider)),
+                ("RESULT_STORAGE_PROVIDER", provider),
                 ("RESULT_STORAGE_TOKEN", Some("storageToken")),
-                ("RESULT_STORAGE_PROXY", Some("https://proxy.example.com")),
             ],
             || {
-                let temp_dir = TempDir::new().unwrap();
-                let file_path = temp_dir.path().join("test.zip");
-                File::create(&file_path)
-                    .unwrap()
-                    .write_all(b"test content")
-                    .unwrap();
-
-                let mut mock_service = MockWeb2ResultInterface::new();
                 let computed_file = create_test_computed_file("0x0");
-                let expected_link = "ipfs://QmHash123";
-
-                mock_service
-                    .expect_upload_to_ipfs_with_iexec_proxy()
+                let expected_link = ResultLink::Other(String::from("link://0x0"));
+                let mut storage = MockResultStorage::new();
+                let returned_link = expected_link.clone();
+                storage
+                    .expect_upload()
                     .with(
                         eq(computed_file.clone()),
-                        eq("https://proxy.example.com"),
                         eq("storageToken"),
                         function(|path: &str| path.ends_with("test.zip")),
                     )
                     .times(1)
-                    .returning(move |_, _, _, _| Ok(String::from(expected_link)));
+                    .returning(move |_, _, _| Ok(returned_link.clone()));
 
-                let result =
-                    run_upload_result(&mock_service, &computed_file, file_path.to_str().unwrap());
-                assert!(result.is_ok());
+                let result = Web2ResultService.upload_result_with_storages(
+                    &computed_file,
+                    "/tmp/test.zip",
+                    &storages_with(registered_provider, storage),
+                );
+                assert_eq!(result, Ok(expected_link));
             },
         );
     }
 
     #[test]
-    fn upload_result_returns_ipfs_link_when_using_ipfs_provider() {
-        run_upload_result_ipfs("ipfs");
+    fn upload_result_uses_storage_registered_for_provider() {
+        run_upload_result_with_provider(Some("dropbox"), "dropbox");
+        run_upload_result_with_provider(Some("custom"), "custom");
     }
 
     #[test]
-    fn upload_result_uses_ipfs_when_provider_not_recognized() {
-        run_upload_result_ipfs("unknown-provider");
+    fn upload_result_defaults_to_ipfs_when_storage_provider_missing() {
+        run_upload_result_with_provider(None, "ipfs");
     }
 
     #[test]
-    fn upload_result_returns_dropbox_link_when_using_dropbox_provider() {
+    fn upload_result_returns_error_when_provider_not_recognized() {
         temp_env::with_vars(
             vec![
-                ("RESULT_STORAGE_PROVIDER", Some("dropbox")),
-                ("RESULT_STORAGE_TOKEN", Some("dropboxToken")),
+                ("RESULT_STORAGE_PROVIDER", Some("unknown-provider")),
+                ("RESULT_STORAGE_TOKEN", Some("storageToken")),
                 ("RESULT_STORAGE_PROXY", Some("https://proxy.example.com")),
             ],
             || {
-                let temp_dir = TempDir::new().unwrap();
-                let file_path = temp_dir.path().join("test.zip");
-                File::create(&file_path)
-                    .unwrap()
-                    .write_all(b"test content")
-                    .unwrap();
-
-                let mut mock_service = MockWeb2ResultInterface::new();
                 let computed_file = create_test_computed_file("0x0");
-                let expected_link = "/results/0x0.zip";
 
-                mock_service
-                    .expect_upload_to_dropbox()
-                    .with(
-                        eq(computed_file.clone()),
-                        eq("dropboxToken"),
-                        function(|path: &str| path.ends_with("test.zip")),
-                    )
+                let result = Web2ResultService.upload_result(&computed_file, "/tmp/test.zip");
+                assert_eq!(
+                    result,
+                    Err(ReplicateStatusCause::PostComputeUnknownStorageProvider)
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn upload_result_propagates_error_when_storage_upload_fails() {
+        temp_env::with_vars(
+            vec![
+                ("RESULT_STORAGE_PROVIDER", Some("ipfs")),
+                ("RESULT_STORAGE_TOKEN", Some("storageToken")),
+            ],
+            || {
+                let mut storage = MockResultStorage::new();
+                storage
+                    .expect_upload()
                     .times(1)
-                    .returning(move |_, _, _| Ok(String::from(expected_link)));
+                    .returning(|_, _, _| Err(ReplicateStatusCause::PostComputeIpfsUploadFailed));
 
-                let result =
-                    run_upload_result(&mock_service, &computed_file, file_path.to_str().unwrap());
-                assert!(result.is_ok());
-                assert_eq!(result.unwrap(), expected_link);
+                let result = Web2ResultService.upload_result_with_storages(
+                    &create_test_computed_file("0x0"),
+                    "/tmp/test.zip",
+                    &storages_with("ipfs", storage),
+                );
+                assert_eq!(
+                    result,
+                    Err(ReplicateStatusCause::PostComputeIpfsUploadFailed)
+                );
             },
         );
     }
@@ -1588,232 +1449,6 @@ mod tests {
             ReplicateStatusCause::PostComputeFailedUnknownIssue,
         );
     }
-
-    #[test]
-    fn upload_result_defaults_to_ipfs_when_storage_provider_missing() {
-        temp_env::with_vars(
-            vec![
-                ("RESULT_STORAGE_TOKEN", Some("token")),
-                ("RESULT_STORAGE_PROXY", Some("proxy")),
-            ],
-            || {
-                let temp_dir = TempDir::new().unwrap();
-                let file_path = temp_dir.path().join("fileToUpload.zip");
-                File::create(&file_path)
-                    .unwrap()
-                    .write_all(b"test content")
-                    .unwrap();
-                let computed_file = create_test_computed_file("0x0");
-
-                let mut mock_service = MockWeb2ResultInterface::new();
-                mock_service
-                    .expect_upload_to_ipfs_with_iexec_proxy()
-                    .with(
-                        eq(computed_file.clone()),
-                        eq("proxy"),
-                        eq("token"),
-                        function(|path: &str| path.ends_with("fileToUpload.zip")),
-                    )
-                    .times(1)
-                    .returning(|_, _, _, _| Ok("any-result".to_string()));
-
-                let _ =
-                    run_upload_result(&mock_service, &computed_file, file_path.to_str().unwrap());
-            },
-        );
-    }
-    // endregion
-
-    // region upload_to_ipfs_with_iexec_proxy
-    async fn actually_upload_to_ipfs_with_iexec_proxy(
-        computed_file: ComputedFile,
-        mock_server: MockServer,
-        file_path: PathBuf,
-    ) -> Result<String, ReplicateStatusCause> {
-        tokio::task::spawn_blocking(move || {
-            Web2ResultService.upload_to_ipfs_with_iexec_proxy(
-                &computed_file,
-                &mock_server.uri(),
-                "test-token",
-                file_path.to_str().unwrap(),
-            )
-        })
-        .await
-        .expect("Task panicked")
-    }
-
-    #[tokio::test]
-    async fn upload_to_ipfs_with_iexec_proxy_returns_link_when_upload_succeeds() {
-        let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("fileToUpload.zip");
-        let mut file = File::create(&file_path).unwrap();
-        file.write_all(b"test zip content").unwrap();
-
-        let computed_file = ComputedFile {
-            task_id: Some(String::from("0x0")),
-            result_digest: Some(String::from("0xdigest")),
-            enclave_signature: Some(String::from("0xsignature")),
-            ..Default::default()
-        };
-
-        let mock_server = MockServer::start().await;
-        Mock::given(method("POST"))
-            .and(path("/v1/results"))
-            .respond_with(ResponseTemplate::new(200).set_body_string("ipfs://QmHash123"))
-            .mount(&mock_server)
-            .await;
-
-        let result =
-            actually_upload_to_ipfs_with_iexec_proxy(computed_file, mock_server, file_path).await;
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "ipfs://QmHash123");
-    }
-
-    #[test]
-    fn upload_to_ipfs_with_iexec_proxy_returns_error_when_file_not_found() {
-        let computed_file = create_test_computed_file("0x0");
-        let non_existent_file = "/this/file/does/not/exist";
-        let base_url = "http://localhost";
-        let token = "IPFS_TOKEN";
-
-        let result = Web2ResultService.upload_to_ipfs_with_iexec_proxy(
-            &computed_file,
-            base_url,
-            token,
-            non_existent_file,
-        );
-        assert_eq!(
-            result,
-            Err(ReplicateStatusCause::PostComputeResultFileNotFound)
-        );
-    }
-
-    #[tokio::test]
-    async fn upload_to_ipfs_with_iexec_proxy_returns_error_when_api_request_fails() {
-        let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("fileToUpload.zip");
-        File::create(&file_path)
-            .unwrap()
-            .write_all(b"test content")
-            .unwrap();
-        let computed_file = create_test_computed_file("0x0");
-
-        let mock_server = MockServer::start().await;
-        Mock::given(method("POST"))
-            .and(path("/v1/results"))
-            .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
-            .mount(&mock_server)
-            .await;
-
-        let result =
-            actually_upload_to_ipfs_with_iexec_proxy(computed_file, mock_server, file_path).await;
-        assert_eq!(
-            result,
-            Err(ReplicateStatusCause::PostComputeIpfsUploadFailed)
-        );
-    }
-    // endregion
-
-    // region upload_to_dropbox
-    #[test]
-    fn upload_to_dropbox_returns_error_when_task_id_missing() {
-        let computed_file = ComputedFile {
-            task_id: None,
-            ..Default::default()
-        };
-
-        let result = Web2ResultService.upload_to_dropbox(&computed_file, "token", "/no/file");
-        assert_eq!(result, Err(ReplicateStatusCause::PostComputeTaskIdMissing));
-    }
-
-    #[test]
-    fn upload_to_dropbox_returns_error_when_file_not_found() {
-        let computed_file = create_test_computed_file("0xdeadbeef");
-
-        let result =
-            Web2ResultService.upload_to_dropbox(&computed_file, "token", "/path/does/not/exist");
-        assert_eq!(
-            result,
-            Err(ReplicateStatusCause::PostComputeResultFileNotFound)
-        );
-    }
-
-    #[test]
-    fn upload_to_dropbox_returns_error_when_local_path_is_directory() {
-        let computed_file = create_test_computed_file("0xdir");
-        let temp_dir = TempDir::new().unwrap();
-
-        let result = Web2ResultService.upload_to_dropbox(
-            &computed_file,
-            "token",
-            temp_dir.path().to_str().unwrap(),
-        );
-        assert_eq!(
-            result,
-            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
-        );
-    }
-
-    #[test]
-    fn upload_to_dropbox_returns_ok_when_upload_succeeds() {
-        let temp_file = NamedTempFile::new().unwrap();
-        fs::write(temp_file.path(), b"content").unwrap();
-        let computed_file = create_test_computed_file("0xsucc");
-        let file_path = temp_file.path().to_str().unwrap().to_string();
-
-        let mut mock_uploader = MockDropboxUploader::new();
-        mock_uploader
-            .expect_upload_file()
-            .with(
-                eq("test-token"),
-                eq(file_path.clone()),
-                eq("/results/0xsucc.zip"),
-                eq(DROPBOX_CONTENT_BASE_URL),
-            )
-            .times(1)
-            .returning(|_, _, _, _| Ok("/results/0xsucc.zip".to_string()));
-
-        let result = Web2ResultService.upload_to_dropbox_with_uploader(
-            &computed_file,
-            "test-token",
-            &file_path,
-            &mock_uploader,
-        );
-
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "/results/0xsucc.zip");
-    }
-
-    #[test]
-    fn upload_to_dropbox_propagates_error_when_upload_fails() {
-        let temp_file = NamedTempFile::new().unwrap();
-        fs::write(temp_file.path(), b"content").unwrap();
-        let computed_file = create_test_computed_file("0xerr");
-        let file_path = temp_file.path().to_str().unwrap().to_string();
-
-        let mut mock_uploader = MockDropboxUploader::new();
-        mock_uploader
-            .expect_upload_file()
-            .with(
-                eq("test-token"),
-                eq(file_path.clone()),
-                eq("/results/0xerr.zip"),
-                eq(DROPBOX_CONTENT_BASE_URL),
-            )
-            .times(1)
-            .returning(|_, _, _, _| Err(ReplicateStatusCause::PostComputeDropboxUploadFailed));
-
-        let result = Web2ResultService.upload_to_dropbox_with_uploader(
-            &computed_file,
-            "test-token",
-            &file_path,
-            &mock_uploader,
-        );
-        assert_eq!(
-            result,
-            Err(ReplicateStatusCause::PostComputeDropboxUploadFailed)
-        );
-    }
     // endregion
 
     // region add_directory_to_zip
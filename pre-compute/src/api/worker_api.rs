@@ -1,9 +1,36 @@
 use crate::compute::{
+    completion::CompletionReport,
     errors::ReplicateStatusCause,
     utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error},
 };
 use log::error;
 use reqwest::{blocking::Client, header::AUTHORIZATION};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Stage reached by a dataset during the pre-compute, reported in a [`ProgressEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProgressStage {
+    Started,
+    Downloading,
+    Verified,
+    Decrypted,
+    Saved,
+}
+
+/// Progress of a dataset, sent to the worker by
+/// [`WorkerApiClient::send_progress_for_pre_compute_stage`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressEvent {
+    /// Filename of the dataset.
+    pub dataset: String,
+    pub stage: ProgressStage,
+    /// Encrypted bytes downloaded so far, or size of the plain dataset once
+    /// [`ProgressStage::Saved`].
+    pub bytes_transferred: u64,
+}
 
 /// Thin wrapper around a [`Client`] that knows how to reach the iExec worker API.
 ///
@@ -23,6 +50,8 @@ pub struct WorkerApiClient {
 }
 
 const DEFAULT_WORKER_HOST: &str = "worker:13100";
+/// Progress is informative, so its requests must not hold the pre-compute back for long.
+const PROGRESS_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
 
 impl WorkerApiClient {
     pub fn new(base_url: &str) -> Self {
@@ -128,6 +157,86 @@ impl WorkerApiClient {
             }
         }
     }
+
+    /// Sends the signed completion report of a successful pre-compute operation to the Worker
+    /// API, listing the files produced for the application.
+    ///
+    /// # Arguments
+    ///
+    /// * `authorization` - The authorization token to use for the API request
+    /// * `chain_task_id` - The chain task ID for which to report the completion
+    /// * `report` - The signed list of produced files, see [`CompletionReport::sign`]
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the completion was successfully reported
+    /// * `Err(ReplicateStatusCause::PreComputeFailedUnknownIssue)` - If the request could not be
+    ///   sent or the server responded with a non-success status
+    pub fn send_completion_for_pre_compute_stage(
+        &self,
+        authorization: &str,
+        chain_task_id: &str,
+        report: &CompletionReport,
+    ) -> Result<(), ReplicateStatusCause> {
+        let url = format!("{}/compute/pre/{chain_task_id}/completion", self.base_url);
+        match self
+            .client
+            .post(&url)
+            .header(AUTHORIZATION, authorization)
+            .json(report)
+            .send()
+        {
+            Ok(resp) => {
+                let status = resp.status();
+                if status.is_success() {
+                    Ok(())
+                } else {
+                    let body = resp.text().unwrap_or_default();
+                    error!("Failed to send completion: [status:{status}, body:{body}]");
+                    Err(ReplicateStatusCause::PreComputeFailedUnknownIssue)
+                }
+            }
+            Err(err) => {
+                error!("HTTP request failed when sending completion to {url}: {err:?}");
+                Err(ReplicateStatusCause::PreComputeFailedUnknownIssue)
+            }
+        }
+    }
+
+    /// Sends the progress of a dataset for a pre-compute operation to the Worker API.
+    ///
+    /// Progress is informative only: a failure is returned without being logged, and is not
+    /// meant to fail the task, see [`ProgressReporter`].
+    ///
+    /// [`ProgressReporter`]: crate::compute::progress::ProgressReporter
+    ///
+    /// # Arguments
+    ///
+    /// * `authorization` - The authorization token to use for the API request
+    /// * `chain_task_id` - The chain task ID for which to report the progress
+    /// * `event` - The progress of the dataset
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the progress was successfully reported
+    /// * `Err(reqwest::Error)` - If the request could not be sent or the server responded with a
+    ///   non-success status
+    pub fn send_progress_for_pre_compute_stage(
+        &self,
+        authorization: &str,
+        chain_task_id: &str,
+        event: &ProgressEvent,
+    ) -> Result<(), reqwest::Error> {
+        let url = format!("{}/compute/pre/{chain_task_id}/progress", self.base_url);
+        self.client
+            .post(&url)
+            .header(AUTHORIZATION, authorization)
+            .timeout(PROGRESS_REQUEST_TIMEOUT)
+            .json(event)
+            .send()?
+            .error_for_status()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -319,4 +428,137 @@ mod tests {
         );
     }
     // endregion
+
+    // region send_completion_for_pre_compute_stage()
+    #[tokio::test]
+    async fn send_completion_succeeds_when_api_returns_success() {
+        let mock_server = MockServer::start().await;
+        let server_url = mock_server.uri();
+        let report = CompletionReport {
+            files: vec![],
+            files_hash: "0x01".to_string(),
+            signature: "0x02".to_string(),
+        };
+
+        let expected_body = json!({
+            "files": [],
+            "filesHash": "0x01",
+            "signature": "0x02"
+        });
+
+        Mock::given(method("POST"))
+            .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/completion")))
+            .and(header("Authorization", CHALLENGE))
+            .and(body_json(&expected_body))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = tokio::task::spawn_blocking(move || {
+            let worker_api_client = WorkerApiClient::new(&server_url);
+            worker_api_client.send_completion_for_pre_compute_stage(
+                CHALLENGE,
+                CHAIN_TASK_ID,
+                &report,
+            )
+        })
+        .await
+        .expect("Task panicked");
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn send_completion_fails_when_http_request_invalid() {
+        let report = CompletionReport {
+            files: vec![],
+            files_hash: "0x01".to_string(),
+            signature: "0x02".to_string(),
+        };
+        let worker_api_client = WorkerApiClient::new("wrong_url");
+        let result = worker_api_client.send_completion_for_pre_compute_stage(
+            CHALLENGE,
+            CHAIN_TASK_ID,
+            &report,
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeFailedUnknownIssue)
+        );
+    }
+    // endregion
+
+    // region send_progress_for_pre_compute_stage()
+    fn get_progress_event() -> ProgressEvent {
+        ProgressEvent {
+            dataset: "dataset.txt".to_string(),
+            stage: ProgressStage::Downloading,
+            bytes_transferred: 1024,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_progress_succeeds_when_api_returns_success() {
+        let mock_server = MockServer::start().await;
+        let server_url = mock_server.uri();
+
+        let expected_body = json!({
+            "dataset": "dataset.txt",
+            "stage": "DOWNLOADING",
+            "bytesTransferred": 1024
+        });
+
+        Mock::given(method("POST"))
+            .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/progress")))
+            .and(header("Authorization", CHALLENGE))
+            .and(body_json(&expected_body))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = tokio::task::spawn_blocking(move || {
+            let worker_api_client = WorkerApiClient::new(&server_url);
+            worker_api_client.send_progress_for_pre_compute_stage(
+                CHALLENGE,
+                CHAIN_TASK_ID,
+                &get_progress_event(),
+            )
+        })
+        .await
+        .expect("Task panicked");
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_progress_fails_when_api_returns_error() {
+        let mock_server = MockServer::start().await;
+        let server_url = mock_server.uri();
+
+        Mock::given(method("POST"))
+            .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/progress")))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let result = tokio::task::spawn_blocking(move || {
+            let worker_api_client = WorkerApiClient::new(&server_url);
+            worker_api_client.send_progress_for_pre_compute_stage(
+                CHALLENGE,
+                CHAIN_TASK_ID,
+                &get_progress_event(),
+            )
+        })
+        .await
+        .expect("Task panicked");
+
+        assert_eq!(
+            result.unwrap_err().status(),
+            Some(reqwest::StatusCode::NOT_FOUND)
+        );
+    }
+    // endregion
 }
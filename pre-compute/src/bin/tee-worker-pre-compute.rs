@@ -1,10 +1,21 @@
 use env_logger::{Builder, Env, Target};
-use std::process;
-use tee_worker_pre_compute::compute::app_runner::start;
+use std::{env, process};
+use tee_worker_pre_compute::compute::app_runner::{start, validate};
+
+/// Flag running the pre-compute in validate-only mode, see [`validate`].
+const VALIDATE_FLAG: &str = "--validate";
 
 fn main() {
+    let validate_only = env::args().skip(1).any(|arg| arg == VALIDATE_FLAG);
+    // The validation report is printed on the standard output, logs must not be mixed with it
+    let target = if validate_only {
+        Target::Stderr
+    } else {
+        Target::Stdout
+    };
     Builder::from_env(Env::default().default_filter_or("info"))
-        .target(Target::Stdout)
+        .target(target)
         .init();
-    process::exit(start() as i32);
+    let exit_mode = if validate_only { validate() } else { start() };
+    process::exit(exit_mode as i32);
 }
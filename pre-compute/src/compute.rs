@@ -1,7 +1,17 @@
 pub mod app_runner;
+pub mod archive;
+pub mod cleanup;
+pub mod completion;
 pub mod dataset;
+pub mod decryption;
 pub mod errors;
+pub mod input_file;
+pub mod key_provider;
 pub mod pre_compute_app;
 pub mod pre_compute_args;
+pub mod progress;
+pub mod session_file;
 pub mod signer;
+pub mod source;
 pub mod utils;
+pub mod validation;
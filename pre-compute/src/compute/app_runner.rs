@@ -1,9 +1,11 @@
 use crate::api::worker_api::WorkerApiClient;
 use crate::compute::pre_compute_app::{PreComputeApp, PreComputeAppTrait};
 use crate::compute::{
+    completion::{CompletionReport, ProducedFile},
     errors::ReplicateStatusCause,
     signer::get_challenge,
     utils::env_utils::{TeeSessionEnvironmentVariable::IexecTaskId, get_env_var_or_error},
+    validation::validate_configuration,
 };
 use log::{error, info};
 
@@ -18,6 +20,7 @@ pub enum ExitMode {
     ReportedFailure = 1,
     UnreportedFailure = 2,
     InitializationFailure = 3,
+    InvalidConfiguration = 4,
 }
 
 /// Executes the pre-compute workflow with a provided PreComputeApp implementation.
@@ -27,6 +30,10 @@ pub enum ExitMode {
 /// It uses the provided app to execute core operations and handles all the
 /// workflow states and transitions.
 ///
+/// On success, the files produced for the application are reported to the worker in a signed
+/// [`CompletionReport`]. This report is best-effort: failing to send it is logged and does not
+/// fail the pre-compute, whose output is already in place.
+///
 /// # Example
 ///
 /// ```rust
@@ -43,8 +50,9 @@ pub fn start_with_app<A: PreComputeAppTrait>(
     chain_task_id: &str,
 ) -> ExitMode {
     let exit_causes = match pre_compute_app.run() {
-        Ok(_) => {
+        Ok(produced_files) => {
             info!("TEE pre-compute completed");
+            report_completion(chain_task_id, produced_files);
             return ExitMode::Success;
         }
         Err(exit_causes) => {
@@ -74,6 +82,32 @@ pub fn start_with_app<A: PreComputeAppTrait>(
     }
 }
 
+/// Sends the signed [`CompletionReport`] of the `produced_files` to the worker, logging failures.
+fn report_completion(chain_task_id: &str, produced_files: Vec<ProducedFile>) {
+    let report = match CompletionReport::sign(chain_task_id, produced_files) {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Failed to sign completion report [chainTaskId:{chain_task_id}, error:{e:?}]");
+            return;
+        }
+    };
+    let authorization = match get_challenge(chain_task_id) {
+        Ok(auth) => auth,
+        Err(e) => {
+            error!(
+                "Failed to get challenge for completion report [chainTaskId:{chain_task_id}, error:{e:?}]"
+            );
+            return;
+        }
+    };
+    if WorkerApiClient::from_env()
+        .send_completion_for_pre_compute_stage(&authorization, chain_task_id, &report)
+        .is_err()
+    {
+        error!("Failed to report completion [chainTaskId:{chain_task_id}]");
+    }
+}
+
 /// Starts the pre-compute process using the [`PreComputeApp`].
 ///
 /// This is a convenience function that creates a [`PreComputeApp`]
@@ -102,13 +136,36 @@ pub fn start() -> ExitMode {
     start_with_app(&mut pre_compute_app, &chain_task_id)
 }
 
+/// Validates the pre-compute configuration without downloading or decrypting anything.
+///
+/// The [`ValidationReport`](crate::compute::validation::ValidationReport) is printed as JSON on
+/// the standard output, and nothing is reported to the worker.
+///
+/// # Returns
+///
+/// * `ExitMode::Success` if the configuration is valid.
+/// * `ExitMode::InvalidConfiguration` otherwise.
+pub fn validate() -> ExitMode {
+    let report = validate_configuration();
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => println!("{json}"),
+        Err(e) => error!("Failed to serialize validation report: {e}"),
+    }
+    if report.valid {
+        ExitMode::Success
+    } else {
+        ExitMode::InvalidConfiguration
+    }
+}
+
 #[cfg(test)]
 mod pre_compute_start_with_app_tests {
     use super::*;
+    use crate::compute::completion::ProducedFileKind;
     use crate::compute::pre_compute_app::MockPreComputeAppTrait;
     use serde_json::json;
     use temp_env;
-    use wiremock::matchers::{body_json, method, path};
+    use wiremock::matchers::{body_json, body_partial_json, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     const CHAIN_TASK_ID: &str = "0x123456789abcdef";
@@ -187,6 +244,66 @@ mod pre_compute_start_with_app_tests {
         });
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn start_succeeds_and_reports_completion_when_run_succeeds() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/completion")))
+            .and(body_partial_json(json!({
+                "files": [{
+                    "kind": "DATASET",
+                    "filename": "dataset.txt",
+                    "size": 22,
+                    "sha256": "0x01"
+                }]
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mock_server_addr_string = mock_server.address().to_string();
+
+        let mut mock = MockPreComputeAppTrait::new();
+        mock.expect_run().times(1).returning(|| {
+            Ok(vec![ProducedFile {
+                kind: ProducedFileKind::Dataset,
+                filename: "dataset.txt".to_string(),
+                size: 22,
+                sha256: "0x01".to_string(),
+            }])
+        });
+
+        let result_code = tokio::task::spawn_blocking(move || {
+            let env_vars = vec![
+                (ENV_SIGN_WORKER_ADDRESS, Some(WORKER_ADDRESS)),
+                (
+                    ENV_SIGN_TEE_CHALLENGE_PRIVATE_KEY,
+                    Some(ENCLAVE_CHALLENGE_PRIVATE_KEY),
+                ),
+                (ENV_WORKER_HOST, Some(mock_server_addr_string.as_str())),
+            ];
+
+            temp_env::with_vars(env_vars, || start_with_app(&mut mock, CHAIN_TASK_ID))
+        })
+        .await
+        .expect("Blocking task panicked");
+
+        mock_server.verify().await;
+        assert_eq!(result_code, ExitMode::Success);
+    }
+
+    #[test]
+    fn start_succeeds_when_completion_cannot_be_reported() {
+        let mut mock = MockPreComputeAppTrait::new();
+        mock.expect_run().returning(|| Ok(vec![]));
+
+        temp_env::with_vars_unset(vec![ENV_SIGN_TEE_CHALLENGE_PRIVATE_KEY], || {
+            assert_eq!(start_with_app(&mut mock, CHAIN_TASK_ID), ExitMode::Success);
+        });
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn start_fails_when_send_exit_cause_api_error() {
         let mock_server = MockServer::start().await;
@@ -0,0 +1,700 @@
+use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::utils::file_utils::{SafeFilename, secure_delete};
+use flate2::read::MultiGzDecoder;
+use log::{error, info};
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tar::EntryType;
+use thiserror::Error;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+/// Maximum size, in bytes, of the content extracted from an archive when none is configured.
+pub const DEFAULT_MAX_EXTRACTED_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+/// Maximum number of entries extracted from an archive when none is configured.
+pub const DEFAULT_MAX_EXTRACTED_ENTRIES: u64 = 10_000;
+
+/// Format of an archive which can be extracted, see [`extract_archive`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// Returns the format matching the extension of `filename`, if any.
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let filename = filename.to_lowercase();
+        [Self::TarGz, Self::TarZst, Self::Tar, Self::Zip]
+            .into_iter()
+            .find(|format| {
+                format
+                    .extensions()
+                    .iter()
+                    .any(|extension| filename.ends_with(extension))
+            })
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Zip => &[".zip"],
+            Self::Tar => &[".tar"],
+            Self::TarGz => &[".tar.gz", ".tgz"],
+            Self::TarZst => &[".tar.zst", ".tzst"],
+        }
+    }
+
+    /// Returns the name of the directory an archive named `filename` is extracted to.
+    ///
+    /// It is `filename` without the extension of the format, for instance `data` for
+    /// `data.tar.gz`, or `filename` followed by `.d` when it has no such extension.
+    pub fn extraction_dir_name(&self, filename: &str) -> String {
+        let lowercase = filename.to_lowercase();
+        self.extensions()
+            .iter()
+            .find(|extension| lowercase.ends_with(*extension))
+            .map(|extension| &filename[..filename.len() - extension.len()])
+            .filter(|stem| SafeFilename::from_str(stem).is_ok())
+            .map(String::from)
+            .unwrap_or_else(|| format!("{filename}.d"))
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "zip" => Ok(Self::Zip),
+            "tar" => Ok(Self::Tar),
+            "tar.gz" | "tgz" => Ok(Self::TarGz),
+            "tar.zst" | "tzst" => Ok(Self::TarZst),
+            _ => Err(format!("unsupported archive format {s:?}")),
+        }
+    }
+}
+
+/// Bounds the content extracted from an archive, to protect the enclave against archive bombs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtractionLimits {
+    /// Maximum size, in bytes, of all the files extracted from an archive.
+    pub max_size: u64,
+    /// Maximum number of files and directories extracted from an archive.
+    pub max_entries: u64,
+}
+
+impl Default for ExtractionLimits {
+    fn default() -> Self {
+        ExtractionLimits {
+            max_size: DEFAULT_MAX_EXTRACTED_SIZE,
+            max_entries: DEFAULT_MAX_EXTRACTED_ENTRIES,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+enum ExtractionError {
+    #[error("unsafe archive entry {0:?}")]
+    UnsafeEntry(String),
+    #[error("extracted content exceeds the maximum size of {0} bytes")]
+    TooLarge(u64),
+    #[error("archive exceeds the maximum number of {0} entries")]
+    TooManyEntries(u64),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Zip(#[from] ZipError),
+}
+
+/// Extracts the archive at `archive_path` to the `destination` directory.
+///
+/// The archive is extracted to a partial directory which is only renamed to `destination`
+/// once every entry has been extracted, and overwritten then deleted otherwise, see
+/// [`secure_delete`]. The extraction fails when
+/// `destination` already exists, and when an entry:
+/// - would be written outside of `destination`, its path being absolute or containing `..`,
+/// - is a symbolic link, a hard link or any special file,
+/// - is listed several times,
+/// - or takes the extracted content beyond `limits`, the size of each file being counted
+///   while it is decompressed rather than trusted from the archive.
+///
+/// Extracted files are created with default permissions, whatever the archive records.
+///
+/// # Returns
+///
+/// * `Ok(())` if the archive is extracted.
+/// * `Err(ReplicateStatusCause::PreComputeDatasetUnsafeArchiveEntry)` if an entry is rejected.
+/// * `Err(ReplicateStatusCause::PreComputeDatasetExtractionLimitExceeded)` if the content
+///   exceeds `limits`.
+/// * `Err(ReplicateStatusCause::PreComputeDatasetExtractionFailed)` if the archive cannot be
+///   read or the content cannot be written.
+pub fn extract_archive(
+    chain_task_id: &str,
+    archive_path: &Path,
+    format: ArchiveFormat,
+    destination: &Path,
+    limits: &ExtractionLimits,
+) -> Result<(), ReplicateStatusCause> {
+    let name = archive_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    info!(
+        "Extracting dataset archive [chainTaskId:{chain_task_id}, archive:{}, format:{format:?}, destination:{}]",
+        archive_path.display(),
+        destination.display()
+    );
+
+    let mut partial_dir = destination.as_os_str().to_owned();
+    partial_dir.push(".part");
+    let partial_dir = PathBuf::from(partial_dir);
+    let mut extractor = Extractor {
+        root: &partial_dir,
+        limits,
+        entries: 0,
+        size: 0,
+    };
+    let result = if destination.symlink_metadata().is_ok() {
+        Err(io::Error::new(ErrorKind::AlreadyExists, "destination already exists").into())
+    } else if let Err(e) = fs::create_dir(&partial_dir) {
+        Err(e.into())
+    } else {
+        let result = extractor
+            .extract(archive_path, format)
+            .and_then(|_| Ok(fs::rename(&partial_dir, destination)?));
+        if result.is_err()
+            && let Err(e) = secure_delete(&partial_dir)
+        {
+            error!(
+                "Failed to delete partially extracted archive [chainTaskId:{chain_task_id}, path:{}, error:{e}]",
+                partial_dir.display()
+            );
+        }
+        result
+    };
+
+    match result {
+        Ok(()) => {
+            info!(
+                "Dataset archive extracted [chainTaskId:{chain_task_id}, archive:{}, entries:{}, size:{}]",
+                archive_path.display(),
+                extractor.entries,
+                extractor.size
+            );
+            Ok(())
+        }
+        Err(e) => {
+            error!(
+                "Failed to extract dataset archive [chainTaskId:{chain_task_id}, archive:{}, error:{e}]",
+                archive_path.display()
+            );
+            Err(match e {
+                ExtractionError::UnsafeEntry(_) => {
+                    ReplicateStatusCause::PreComputeDatasetUnsafeArchiveEntry(name)
+                }
+                ExtractionError::TooLarge(_) | ExtractionError::TooManyEntries(_) => {
+                    ReplicateStatusCause::PreComputeDatasetExtractionLimitExceeded(name)
+                }
+                ExtractionError::Io(_) | ExtractionError::Zip(_) => {
+                    ReplicateStatusCause::PreComputeDatasetExtractionFailed(name)
+                }
+            })
+        }
+    }
+}
+
+/// Writes the entries of an archive below `root`, keeping count of them against `limits`.
+struct Extractor<'a> {
+    root: &'a Path,
+    limits: &'a ExtractionLimits,
+    entries: u64,
+    size: u64,
+}
+
+impl Extractor<'_> {
+    fn extract(
+        &mut self,
+        archive_path: &Path,
+        format: ArchiveFormat,
+    ) -> Result<(), ExtractionError> {
+        let file = File::open(archive_path)?;
+        match format {
+            ArchiveFormat::Zip => self.extract_zip(file),
+            ArchiveFormat::Tar => self.extract_tar(file),
+            ArchiveFormat::TarGz => self.extract_tar(MultiGzDecoder::new(file)),
+            ArchiveFormat::TarZst => self.extract_tar(zstd::Decoder::new(file)?),
+        }
+    }
+
+    fn extract_zip(&mut self, file: File) -> Result<(), ExtractionError> {
+        let mut archive = ZipArchive::new(file)?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let name = entry.name().to_string();
+            if entry.is_symlink() {
+                return Err(ExtractionError::UnsafeEntry(name));
+            } else if entry.is_dir() {
+                self.create_dir(&name)?;
+            } else {
+                let declared_size = entry.size();
+                self.create_file(&name, declared_size, &mut entry)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn extract_tar<R: Read>(&mut self, reader: R) -> Result<(), ExtractionError> {
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+            match entry.header().entry_type() {
+                EntryType::Regular | EntryType::Continuous => {
+                    let declared_size = entry.size();
+                    self.create_file(&name, declared_size, &mut entry)?;
+                }
+                EntryType::Directory => self.create_dir(&name)?,
+                // Global PAX headers only hold metadata, such as the commit of a `git archive`
+                EntryType::XGlobalHeader => {}
+                _ => return Err(ExtractionError::UnsafeEntry(name)),
+            }
+        }
+        Ok(())
+    }
+
+    fn create_dir(&mut self, name: &str) -> Result<(), ExtractionError> {
+        self.count_entry()?;
+        let path = self.entry_path(name, true)?;
+        fs::create_dir_all(path)?;
+        Ok(())
+    }
+
+    fn create_file(
+        &mut self,
+        name: &str,
+        declared_size: u64,
+        content: &mut dyn Read,
+    ) -> Result<(), ExtractionError> {
+        self.count_entry()?;
+        let path = self.entry_path(name, false)?;
+        let remaining = self.limits.max_size - self.size;
+        if declared_size > remaining {
+            return Err(ExtractionError::TooLarge(self.limits.max_size));
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create_new(&path).map_err(|e| match e.kind() {
+            ErrorKind::AlreadyExists => ExtractionError::UnsafeEntry(name.to_string()),
+            _ => e.into(),
+        })?;
+        // One more byte than allowed is read to detect content exceeding the limit
+        let written = io::copy(&mut content.take(remaining + 1), &mut file)?;
+        if written > remaining {
+            return Err(ExtractionError::TooLarge(self.limits.max_size));
+        }
+        self.size += written;
+        Ok(())
+    }
+
+    fn count_entry(&mut self) -> Result<(), ExtractionError> {
+        self.entries += 1;
+        if self.entries > self.limits.max_entries {
+            return Err(ExtractionError::TooManyEntries(self.limits.max_entries));
+        }
+        Ok(())
+    }
+
+    /// Returns the path below `root` of the entry `name`, rejecting paths which could escape it.
+    ///
+    /// Both `/` and `\` are considered separators, as archives created on Windows may use
+    /// either. Empty and `.` components are ignored, so `./data/` is the `data` directory.
+    fn entry_path(&self, name: &str, is_dir: bool) -> Result<PathBuf, ExtractionError> {
+        let unsafe_entry = || ExtractionError::UnsafeEntry(name.to_string());
+        if name.starts_with(['/', '\\']) || name.contains('\0') {
+            return Err(unsafe_entry());
+        }
+        let mut path = self.root.to_path_buf();
+        let mut depth = 0;
+        for component in name.split(['/', '\\']) {
+            match component {
+                "" | "." => {}
+                ".." => return Err(unsafe_entry()),
+                _ => {
+                    path.push(component);
+                    depth += 1;
+                }
+            }
+        }
+        if depth == 0 && !is_dir {
+            return Err(unsafe_entry());
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    const CHAIN_TASK_ID: &str = "0xabc";
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            if name.ends_with('/') {
+                zip.add_directory(*name, SimpleFileOptions::default())
+                    .unwrap();
+            } else {
+                zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+                zip.write_all(content).unwrap();
+            }
+        }
+        zip.finish().unwrap();
+    }
+
+    fn tar_header(name: &str, entry_type: EntryType, size: u64) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        // Names are written as is, as `set_path` rejects the unsafe ones under test
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_cksum();
+        header
+    }
+
+    fn tar_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, content) in entries {
+            let entry_type = if name.ends_with('/') {
+                EntryType::Directory
+            } else {
+                EntryType::Regular
+            };
+            let header = tar_header(name, entry_type, content.len() as u64);
+            builder.append(&header, *content).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn extract(
+        temp_dir: &TempDir,
+        archive: &Path,
+        format: ArchiveFormat,
+        limits: &ExtractionLimits,
+    ) -> Result<PathBuf, ReplicateStatusCause> {
+        let destination = temp_dir.path().join("extracted");
+        extract_archive(CHAIN_TASK_ID, archive, format, &destination, limits)?;
+        Ok(destination)
+    }
+
+    // region ArchiveFormat
+    #[test]
+    fn from_filename_detects_format_from_extension() {
+        assert_eq!(
+            ArchiveFormat::from_filename("data.zip"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(
+            ArchiveFormat::from_filename("data.tar"),
+            Some(ArchiveFormat::Tar)
+        );
+        assert_eq!(
+            ArchiveFormat::from_filename("data.TAR.GZ"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_filename("data.tgz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_filename("data.tar.zst"),
+            Some(ArchiveFormat::TarZst)
+        );
+        assert_eq!(ArchiveFormat::from_filename("data.gz"), None);
+        assert_eq!(ArchiveFormat::from_filename("data.txt"), None);
+    }
+
+    #[test]
+    fn from_str_parses_supported_formats() {
+        assert_eq!("zip".parse(), Ok(ArchiveFormat::Zip));
+        assert_eq!("TAR".parse(), Ok(ArchiveFormat::Tar));
+        assert_eq!("tar.gz".parse(), Ok(ArchiveFormat::TarGz));
+        assert_eq!("tar.zst".parse(), Ok(ArchiveFormat::TarZst));
+        assert!("rar".parse::<ArchiveFormat>().is_err());
+    }
+
+    #[test]
+    fn extraction_dir_name_strips_format_extension() {
+        assert_eq!(ArchiveFormat::Zip.extraction_dir_name("data.zip"), "data");
+        assert_eq!(
+            ArchiveFormat::TarGz.extraction_dir_name("data.tar.gz"),
+            "data"
+        );
+        assert_eq!(ArchiveFormat::Zip.extraction_dir_name("data"), "data.d");
+        assert_eq!(ArchiveFormat::Zip.extraction_dir_name(".zip"), ".zip.d");
+        assert_eq!(ArchiveFormat::Zip.extraction_dir_name("..zip"), "..zip.d");
+    }
+    // endregion
+
+    // region extract_archive
+    #[test]
+    fn extract_archive_extracts_zip() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("data.zip");
+        write_zip(
+            &archive,
+            &[("dir/", b""), ("dir/a.txt", b"a"), ("b.txt", b"bb")],
+        );
+
+        let destination = extract(
+            &temp_dir,
+            &archive,
+            ArchiveFormat::Zip,
+            &ExtractionLimits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(destination.join("dir/a.txt")).unwrap(), b"a");
+        assert_eq!(fs::read(destination.join("b.txt")).unwrap(), b"bb");
+        assert!(!temp_dir.path().join("extracted.part").exists());
+    }
+
+    #[test]
+    fn extract_archive_extracts_compressed_tars() {
+        let tar = tar_bytes(&[("./dir/", b""), ("./dir/a.txt", b"a")]);
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&tar).unwrap();
+        let archives = [
+            (ArchiveFormat::Tar, tar.clone()),
+            (ArchiveFormat::TarGz, gz.finish().unwrap()),
+            (
+                ArchiveFormat::TarZst,
+                zstd::encode_all(&tar[..], 0).unwrap(),
+            ),
+        ];
+
+        for (format, content) in archives {
+            let temp_dir = TempDir::new().unwrap();
+            let archive = temp_dir.path().join("archive");
+            fs::write(&archive, content).unwrap();
+
+            let destination =
+                extract(&temp_dir, &archive, format, &ExtractionLimits::default()).unwrap();
+
+            assert_eq!(fs::read(destination.join("dir/a.txt")).unwrap(), b"a");
+        }
+    }
+
+    #[test]
+    fn extract_archive_rejects_path_traversal() {
+        for name in [
+            "../evil.txt",
+            "dir/../../evil.txt",
+            "/evil.txt",
+            "..\\evil.txt",
+        ] {
+            let temp_dir = TempDir::new().unwrap();
+            let archive = temp_dir.path().join("data.tar");
+            fs::write(&archive, tar_bytes(&[(name, b"evil")])).unwrap();
+
+            let result = extract(
+                &temp_dir,
+                &archive,
+                ArchiveFormat::Tar,
+                &ExtractionLimits::default(),
+            );
+
+            assert_eq!(
+                result,
+                Err(ReplicateStatusCause::PreComputeDatasetUnsafeArchiveEntry(
+                    "data.tar".to_string()
+                )),
+                "{name} should be rejected"
+            );
+            assert!(!temp_dir.path().join("evil.txt").exists());
+            assert!(!temp_dir.path().join("extracted").exists());
+            assert!(!temp_dir.path().join("extracted.part").exists());
+        }
+    }
+
+    #[test]
+    fn extract_archive_rejects_zip_slip() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("data.zip");
+        write_zip(&archive, &[("ok.txt", b"ok"), ("../evil.txt", b"evil")]);
+
+        let result = extract(
+            &temp_dir,
+            &archive,
+            ArchiveFormat::Zip,
+            &ExtractionLimits::default(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetUnsafeArchiveEntry(
+                "data.zip".to_string()
+            ))
+        );
+        assert!(!temp_dir.path().join("evil.txt").exists());
+        assert!(!temp_dir.path().join("extracted.part").exists());
+    }
+
+    #[test]
+    fn extract_archive_rejects_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let tar_archive = temp_dir.path().join("data.tar");
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar_header("passwd", EntryType::Symlink, 0);
+        header.set_link_name("/etc/passwd").unwrap();
+        header.set_cksum();
+        builder.append(&header, io::empty()).unwrap();
+        fs::write(&tar_archive, builder.into_inner().unwrap()).unwrap();
+        let zip_archive = temp_dir.path().join("data.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_archive).unwrap());
+        zip.add_symlink("passwd", "/etc/passwd", SimpleFileOptions::default())
+            .unwrap();
+        zip.finish().unwrap();
+
+        for (archive, format) in [
+            (tar_archive, ArchiveFormat::Tar),
+            (zip_archive, ArchiveFormat::Zip),
+        ] {
+            let result = extract(&temp_dir, &archive, format, &ExtractionLimits::default());
+
+            assert!(matches!(
+                result,
+                Err(ReplicateStatusCause::PreComputeDatasetUnsafeArchiveEntry(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn extract_archive_rejects_duplicate_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("data.tar");
+        fs::write(&archive, tar_bytes(&[("a.txt", b"a"), ("./a.txt", b"b")])).unwrap();
+
+        let result = extract(
+            &temp_dir,
+            &archive,
+            ArchiveFormat::Tar,
+            &ExtractionLimits::default(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetUnsafeArchiveEntry(_))
+        ));
+    }
+
+    #[test]
+    fn extract_archive_fails_when_extracted_size_exceeds_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("data.zip");
+        // Highly compressible content, as found in archive bombs
+        let content = vec![0u8; 100_000];
+        write_zip(&archive, &[("a.bin", &content)]);
+        let limits = ExtractionLimits {
+            max_size: 99_999,
+            ..ExtractionLimits::default()
+        };
+
+        let result = extract(&temp_dir, &archive, ArchiveFormat::Zip, &limits);
+
+        assert_eq!(
+            result,
+            Err(
+                ReplicateStatusCause::PreComputeDatasetExtractionLimitExceeded(
+                    "data.zip".to_string()
+                )
+            )
+        );
+        assert!(!temp_dir.path().join("extracted.part").exists());
+    }
+
+    #[test]
+    fn extract_archive_fails_when_entries_exceed_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("data.tar");
+        fs::write(
+            &archive,
+            tar_bytes(&[("a.txt", b"a"), ("b.txt", b"b"), ("c.txt", b"c")]),
+        )
+        .unwrap();
+        let limits = ExtractionLimits {
+            max_entries: 2,
+            ..ExtractionLimits::default()
+        };
+
+        let result = extract(&temp_dir, &archive, ArchiveFormat::Tar, &limits);
+
+        assert_eq!(
+            result,
+            Err(
+                ReplicateStatusCause::PreComputeDatasetExtractionLimitExceeded(
+                    "data.tar".to_string()
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn extract_archive_fails_when_archive_corrupted() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("data.zip");
+        fs::write(&archive, b"not a zip archive").unwrap();
+
+        let result = extract(
+            &temp_dir,
+            &archive,
+            ArchiveFormat::Zip,
+            &ExtractionLimits::default(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetExtractionFailed(
+                "data.zip".to_string()
+            ))
+        );
+        assert!(!temp_dir.path().join("extracted.part").exists());
+    }
+
+    #[test]
+    fn extract_archive_fails_when_destination_exists() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("data.zip");
+        write_zip(&archive, &[("a.txt", b"a")]);
+        fs::write(temp_dir.path().join("extracted"), b"existing").unwrap();
+
+        let result = extract(
+            &temp_dir,
+            &archive,
+            ArchiveFormat::Zip,
+            &ExtractionLimits::default(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetExtractionFailed(
+                "data.zip".to_string()
+            ))
+        );
+        assert_eq!(
+            fs::read(temp_dir.path().join("extracted")).unwrap(),
+            b"existing"
+        );
+    }
+    // endregion
+}
@@ -0,0 +1,167 @@
+use crate::compute::utils::file_utils::secure_delete;
+use log::{error, info};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// What to do with the files written by the pre-compute when it fails, see [`WrittenPaths`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CleanupPolicy {
+    /// Overwrite then delete the written files, see [`secure_delete`].
+    #[default]
+    Secure,
+    /// Delete the written files without overwriting them.
+    Delete,
+    /// Leave the written files in place.
+    Keep,
+}
+
+impl FromStr for CleanupPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "secure" => Ok(CleanupPolicy::Secure),
+            "delete" => Ok(CleanupPolicy::Delete),
+            "keep" => Ok(CleanupPolicy::Keep),
+            _ => Err(format!("unsupported cleanup policy {s}")),
+        }
+    }
+}
+
+/// Record of the files and directories written by the pre-compute to its output folder.
+///
+/// Paths are recorded before anything is written to them, so that partially written content
+/// is also removed by [`WrittenPaths::cleanup`]. The record can be shared between the threads
+/// processing datasets and input files.
+#[derive(Debug, Default)]
+pub struct WrittenPaths {
+    paths: Mutex<Vec<PathBuf>>,
+}
+
+impl WrittenPaths {
+    pub fn record(&self, path: &Path) {
+        let mut paths = self.paths.lock().unwrap();
+        if !paths.iter().any(|recorded| recorded == path) {
+            paths.push(path.to_path_buf());
+        }
+    }
+
+    /// Returns the recorded paths, in the order they were recorded.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.paths.lock().unwrap().clone()
+    }
+
+    /// Removes every recorded path according to `policy`, then forgets them.
+    ///
+    /// Removal carries on when a path cannot be removed, the failure being logged.
+    pub fn cleanup(&self, chain_task_id: &str, policy: CleanupPolicy) {
+        if policy == CleanupPolicy::Keep {
+            info!("Keeping files written by pre-compute [chainTaskId:{chain_task_id}]");
+            return;
+        }
+        let paths = std::mem::take(&mut *self.paths.lock().unwrap());
+        for path in paths.iter().rev() {
+            let result = match policy {
+                CleanupPolicy::Secure => secure_delete(path),
+                _ => remove(path),
+            };
+            match result {
+                Ok(()) => info!(
+                    "Removed file written by pre-compute [chainTaskId:{chain_task_id}, path:{}, policy:{policy:?}]",
+                    path.display()
+                ),
+                Err(e) => error!(
+                    "Failed to remove file written by pre-compute [chainTaskId:{chain_task_id}, path:{}, error:{e}]",
+                    path.display()
+                ),
+            }
+        }
+    }
+}
+
+/// Deletes a file, or a directory and its content, ignoring a missing path.
+fn remove(path: &Path) -> io::Result<()> {
+    let result = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) => Err(e),
+    };
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CHAIN_TASK_ID: &str = "0x123456789abcdef";
+
+    fn write_files(temp_dir: &TempDir, written_paths: &WrittenPaths) -> (PathBuf, PathBuf) {
+        let file = temp_dir.path().join("plain.txt");
+        fs::write(&file, "secret").unwrap();
+        let dir = temp_dir.path().join("plain");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("entry.txt"), "secret").unwrap();
+        written_paths.record(&file);
+        written_paths.record(&dir);
+        written_paths.record(&temp_dir.path().join("never-written.txt"));
+        (file, dir)
+    }
+
+    #[test]
+    fn from_str_parses_policies() {
+        assert_eq!("secure".parse(), Ok(CleanupPolicy::Secure));
+        assert_eq!(" Delete ".parse(), Ok(CleanupPolicy::Delete));
+        assert_eq!("KEEP".parse(), Ok(CleanupPolicy::Keep));
+        assert!("shred".parse::<CleanupPolicy>().is_err());
+    }
+
+    #[test]
+    fn record_ignores_already_recorded_path() {
+        let written_paths = WrittenPaths::default();
+        written_paths.record(Path::new("/out/a"));
+        written_paths.record(Path::new("/out/b"));
+        written_paths.record(Path::new("/out/a"));
+
+        assert_eq!(
+            written_paths.paths(),
+            vec![PathBuf::from("/out/a"), PathBuf::from("/out/b")]
+        );
+    }
+
+    #[test]
+    fn cleanup_removes_recorded_paths_when_secure_or_delete() {
+        for policy in [CleanupPolicy::Secure, CleanupPolicy::Delete] {
+            let temp_dir = TempDir::new().unwrap();
+            let unrecorded = temp_dir.path().join("unrecorded.txt");
+            fs::write(&unrecorded, "kept").unwrap();
+            let written_paths = WrittenPaths::default();
+            let (file, dir) = write_files(&temp_dir, &written_paths);
+
+            written_paths.cleanup(CHAIN_TASK_ID, policy);
+
+            assert!(!file.exists(), "{policy:?}");
+            assert!(!dir.exists(), "{policy:?}");
+            assert!(unrecorded.exists(), "{policy:?}");
+            assert!(written_paths.paths().is_empty(), "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn cleanup_keeps_recorded_paths_when_keep() {
+        let temp_dir = TempDir::new().unwrap();
+        let written_paths = WrittenPaths::default();
+        let (file, dir) = write_files(&temp_dir, &written_paths);
+
+        written_paths.cleanup(CHAIN_TASK_ID, CleanupPolicy::Keep);
+
+        assert!(file.exists());
+        assert!(dir.join("entry.txt").exists());
+    }
+}
@@ -0,0 +1,242 @@
+use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::signer::sign_enclave_challenge;
+use crate::compute::utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error};
+use crate::compute::utils::hash_utils::concatenate_and_hash;
+use crate::compute::utils::secret_utils::SecretString;
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
+
+/// Kind of a [`ProducedFile`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ProducedFileKind {
+    Dataset,
+    InputFile,
+}
+
+impl ProducedFileKind {
+    /// Returns the name of the kind, as serialized.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProducedFileKind::Dataset => "DATASET",
+            ProducedFileKind::InputFile => "INPUT_FILE",
+        }
+    }
+}
+
+/// File written to the output folder by the pre-compute, as consumed by the application.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProducedFile {
+    pub kind: ProducedFileKind,
+    /// Name of the file in the output folder.
+    pub filename: String,
+    /// Size of the plaintext in bytes.
+    pub size: u64,
+    /// SHA-256 digest of the plaintext, hex-encoded and prefixed with `0x`.
+    pub sha256: String,
+}
+
+/// Completion payload sent to the worker when the pre-compute succeeds.
+///
+/// The payload lists the files produced for the application and is signed with the enclave
+/// challenge private key, so that the worker and the post-compute can prove which inputs the
+/// application consumed. The signed message is the Keccak-256 hash of the chain task ID
+/// concatenated with `files_hash`, which is computed from the fields of `files` independently
+/// of their JSON serialization, see [`hash_files`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionReport {
+    pub files: Vec<ProducedFile>,
+    pub files_hash: String,
+    pub signature: String,
+}
+
+impl CompletionReport {
+    /// Builds the report of the `files` produced for `chain_task_id`, signed with the key of the
+    /// `SIGN_TEE_CHALLENGE_PRIVATE_KEY` environment variable.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CompletionReport)` if the report is signed.
+    /// * `Err(ReplicateStatusCause::PreComputeTeeChallengePrivateKeyMissing)` if the key is
+    ///   missing or invalid.
+    /// * `Err(ReplicateStatusCause::PreComputeInvalidTeeSignature)` if the signature fails.
+    pub fn sign(
+        chain_task_id: &str,
+        files: Vec<ProducedFile>,
+    ) -> Result<Self, ReplicateStatusCause> {
+        let tee_challenge_private_key = get_env_var_or_error(
+            TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey,
+            ReplicateStatusCause::PreComputeTeeChallengePrivateKeyMissing,
+        )
+        .map(SecretString::from)?;
+        let files_hash = hash_files(&files);
+        let message_hash = concatenate_and_hash(&[chain_task_id, &files_hash]);
+        let signature =
+            sign_enclave_challenge(&message_hash, tee_challenge_private_key.expose_secret())?;
+        Ok(CompletionReport {
+            files,
+            files_hash,
+            signature,
+        })
+    }
+}
+
+/// Returns the hash of the produced `files`, signed along with the chain task ID.
+///
+/// Each file is hashed as the Keccak-256 hash of the concatenation of 32-byte words: the
+/// Keccak-256 hashes of its kind name and of its filename, its size as a big-endian integer and
+/// its SHA-256 digest. The result is the Keccak-256 hash of the concatenation of the file
+/// hashes, in order, prefixed with `0x`.
+pub fn hash_files(files: &[ProducedFile]) -> String {
+    let file_hashes: Vec<String> = files
+        .iter()
+        .map(|file| {
+            concatenate_and_hash(&[
+                &keccak256(file.kind.name()),
+                &keccak256(&file.filename),
+                &format!("0x{:064x}", file.size),
+                &file.sha256,
+            ])
+        })
+        .collect();
+    let file_hashes: Vec<&str> = file_hashes.iter().map(String::as_str).collect();
+    concatenate_and_hash(&file_hashes)
+}
+
+fn keccak256(value: &str) -> String {
+    format!("0x{:x}", Keccak256::digest(value.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use temp_env::with_vars;
+
+    const CHAIN_TASK_ID: &str = "0x123456789abcdef";
+    const ENCLAVE_CHALLENGE_PRIVATE_KEY: &str =
+        "0xdd3b993ec21c71c1f6d63a5240850e0d4d8dd83ff70d29e49247958548c1d479";
+
+    fn get_produced_files() -> Vec<ProducedFile> {
+        vec![
+            ProducedFile {
+                kind: ProducedFileKind::Dataset,
+                filename: "dataset.txt".to_string(),
+                size: 22,
+                sha256: "0x01".to_string(),
+            },
+            ProducedFile {
+                kind: ProducedFileKind::InputFile,
+                filename: "input.csv".to_string(),
+                size: 3,
+                sha256: "0x02".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn sign_signs_chain_task_id_and_files_hash() {
+        with_vars(
+            vec![(
+                TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey.name(),
+                Some(ENCLAVE_CHALLENGE_PRIVATE_KEY),
+            )],
+            || {
+                let report = CompletionReport::sign(CHAIN_TASK_ID, get_produced_files()).unwrap();
+
+                let expected_files_hash = hash_files(&get_produced_files());
+                assert_eq!(report.files, get_produced_files());
+                assert_eq!(report.files_hash, expected_files_hash);
+                assert_eq!(
+                    report.signature,
+                    sign_enclave_challenge(
+                        &concatenate_and_hash(&[CHAIN_TASK_ID, &expected_files_hash]),
+                        ENCLAVE_CHALLENGE_PRIVATE_KEY
+                    )
+                    .unwrap()
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn hash_files_hashes_each_field_of_each_file() {
+        let files = get_produced_files();
+        let dataset_hash = concatenate_and_hash(&[
+            &format!("0x{:x}", Keccak256::digest(b"DATASET")),
+            &format!("0x{:x}", Keccak256::digest(b"dataset.txt")),
+            "0x0000000000000000000000000000000000000000000000000000000000000016",
+            "0x01",
+        ]);
+        let input_file_hash = concatenate_and_hash(&[
+            &format!("0x{:x}", Keccak256::digest(b"INPUT_FILE")),
+            &format!("0x{:x}", Keccak256::digest(b"input.csv")),
+            "0x0000000000000000000000000000000000000000000000000000000000000003",
+            "0x02",
+        ]);
+
+        assert_eq!(
+            hash_files(&files),
+            concatenate_and_hash(&[&dataset_hash, &input_file_hash])
+        );
+        for changed in [
+            ProducedFile {
+                kind: ProducedFileKind::InputFile,
+                ..files[0].clone()
+            },
+            ProducedFile {
+                filename: "dataset.csv".to_string(),
+                ..files[0].clone()
+            },
+            ProducedFile {
+                size: 23,
+                ..files[0].clone()
+            },
+            ProducedFile {
+                sha256: "0x03".to_string(),
+                ..files[0].clone()
+            },
+        ] {
+            assert_ne!(hash_files(&[changed, files[1].clone()]), hash_files(&files));
+        }
+    }
+
+    #[test]
+    fn sign_serializes_report_for_worker() {
+        with_vars(
+            vec![(
+                TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey.name(),
+                Some(ENCLAVE_CHALLENGE_PRIVATE_KEY),
+            )],
+            || {
+                let report = CompletionReport::sign(CHAIN_TASK_ID, get_produced_files()).unwrap();
+                let json = serde_json::to_value(&report).unwrap();
+
+                assert_eq!(json["files"][0]["kind"], "DATASET");
+                assert_eq!(json["files"][1]["kind"], "INPUT_FILE");
+                assert_eq!(json["files"][1]["filename"], "input.csv");
+                assert_eq!(json["files"][1]["size"], 3);
+                assert_eq!(json["files"][1]["sha256"], "0x02");
+                assert_eq!(json["filesHash"], report.files_hash);
+                assert_eq!(json["signature"], report.signature);
+            },
+        );
+    }
+
+    #[test]
+    fn sign_fails_when_private_key_missing() {
+        with_vars(
+            vec![(
+                TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey.name(),
+                None::<&str>,
+            )],
+            || {
+                assert_eq!(
+                    CompletionReport::sign(CHAIN_TASK_ID, get_produced_files()),
+                    Err(ReplicateStatusCause::PreComputeTeeChallengePrivateKeyMissing)
+                );
+            },
+        );
+    }
+}
@@ -1,249 +1,1437 @@
+use crate::api::worker_api::ProgressStage;
+use crate::compute::archive::ArchiveFormat;
+use crate::compute::decryption::DatasetDecryptor;
 use crate::compute::errors::ReplicateStatusCause;
-use crate::compute::utils::file_utils::download_from_url;
-use crate::compute::utils::hash_utils::sha256_from_bytes;
-use aes::Aes256;
-use base64::{Engine as _, engine::general_purpose};
-use cbc::{
-    Decryptor,
-    cipher::{BlockDecryptMut, KeyIvInit, block_padding::Pkcs7},
+use crate::compute::progress::ProgressReporter;
+use crate::compute::source::{
+    SourceError, SourceRequest, SourceResolver, SourceResolvers, SourceStream,
+};
+use crate::compute::utils::cid_utils::{
+    CAR_ACCEPT_HEADER, CarFallbackVerifyingReader, CarFileReader, Cid, cid_from_multi_address,
+    is_cid_verification_error,
+};
+use crate::compute::utils::file_utils::{
+    DownloadOptions, PARTIAL_FILE_SUFFIX, SafeFilename, open_download_stream,
 };
-use log::{error, info};
+use crate::compute::utils::hash_utils::{Checksum, HashAlgorithm};
+use crate::compute::utils::quota_utils::{DownloadQuota, SizeLimitError, size_limit_error};
+use crate::compute::utils::retry_utils::{DownloadAttempts, RetryPolicy};
+use crate::compute::utils::secret_utils::{SecretBytes, SecretString};
+use base64::{Engine as _, engine::general_purpose};
+use log::{error, info, warn};
 use multiaddr::Multiaddr;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Seek, Write};
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
 
-type Aes256CbcDec = Decryptor<Aes256>;
-const IPFS_GATEWAYS: &[&str] = &[
+/// IPFS gateways used when none are configured in the TEE session.
+pub const DEFAULT_IPFS_GATEWAYS: &[&str] = &[
     "https://ipfs-gateway.v8-bellecour.iex.ec",
     "https://gateway.ipfs.io",
     "https://gateway.pinata.cloud",
 ];
-const AES_KEY_LENGTH: usize = 32;
-const AES_IV_LENGTH: usize = 16;
+/// Maximum duration a single IPFS gateway is waited for when none is configured.
+pub const DEFAULT_IPFS_GATEWAY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Length in bytes of the AES-256 key of a dataset, once decoded from Base64.
+pub const AES_KEY_LENGTH: usize = 32;
+/// Size of the buffer used to stream a dataset from the network to disk.
+///
+/// Peak memory used by the dataset pipeline is bounded by roughly twice this value,
+/// whatever the size of the dataset.
+pub const DATASET_STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Configuration of the IPFS gateways used to resolve datasets stored on IPFS.
+///
+/// By default, gateways are tried sequentially in order and the first one answering
+/// successfully is used. In race mode, all gateways are queried concurrently and the
+/// content of the first one whose checksum matches the expected dataset checksum is used.
+/// In both modes, a gateway is abandoned when connecting to it, receiving its response headers
+/// or any read of the content takes longer than `timeout`. The total duration of a download is
+/// not limited, so that large datasets can be fetched from slow gateways.
+///
+/// Whatever the mode, the content returned by a gateway is checked against the CID of the
+/// dataset. By default the CID is recomputed from the raw file content, which only matches
+/// for content added to IPFS with Kubo's default parameters (CIDv0, 256 KiB chunks, balanced
+/// layout). Content added with other parameters, or identified by a CIDv1, is then fetched a
+/// second time as a CAR response from the same gateway to be verified, see
+/// [`CarFallbackVerifyingReader`], so such datasets remain downloadable from gateways serving
+/// CAR responses. In trustless mode, gateways are only asked for CAR responses, and every block
+/// is verified as it arrives, see [`CarFileReader`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct IpfsGatewayConfig {
+    pub gateways: Vec<String>,
+    pub timeout: Duration,
+    pub race: bool,
+    pub trustless: bool,
+}
+
+impl Default for IpfsGatewayConfig {
+    fn default() -> Self {
+        IpfsGatewayConfig {
+            gateways: DEFAULT_IPFS_GATEWAYS
+                .iter()
+                .map(|gateway| gateway.to_string())
+                .collect(),
+            timeout: DEFAULT_IPFS_GATEWAY_TIMEOUT,
+            race: false,
+            trustless: false,
+        }
+    }
+}
 
 /// Represents a dataset in a Trusted Execution Environment (TEE).
 ///
 /// This structure contains all the information needed to download, verify, and decrypt
 /// a single dataset.
-#[cfg_attr(test, derive(Debug))]
-#[derive(Clone, Default)]
+#[derive(Clone, Debug)]
 pub struct Dataset {
     pub url: String,
     pub checksum: String,
-    pub filename: String,
-    pub key: String,
+    /// Name of the plain dataset file, validated so that it is always written inside the
+    /// output folder.
+    pub filename: SafeFilename,
+    /// Base64-encoded AES-256 key of the dataset.
+    pub key: SecretString,
+    /// Format of the decrypted dataset when it is an archive to extract, see [`extract_archive`].
+    ///
+    /// [`extract_archive`]: crate::compute::archive::extract_archive
+    pub extract: Option<ArchiveFormat>,
 }
 
 impl Dataset {
-    pub fn new(url: String, checksum: String, filename: String, key: String) -> Self {
+    pub fn new(url: String, checksum: String, filename: SafeFilename, key: SecretString) -> Self {
         Dataset {
             url,
             checksum,
             filename,
             key,
+            extract: None,
         }
     }
 
-    /// Downloads the encrypted dataset file from a URL or IPFS multi-address, and verifies its checksum.
+    /// Opens a download stream for the encrypted dataset file from its location.
+    ///
+    /// The location is opened by the first of `sources` supporting it, e.g. an HTTP(S) URL, an
+    /// IPFS multi-address resolved through IPFS gateways, see [`IpfsSourceResolver`], or a file of
+    /// a local volume, see [`LocalSourceResolver`]. The body of the returned stream is not read,
+    /// so the checksum of the content is verified later while streaming it in
+    /// [`Dataset::download_and_decrypt_dataset`].
+    ///
+    /// Transient failures are retried according to `retry_policy`. The failed attempts are
+    /// reported in the exit cause. Once the content is streaming from an HTTP(S) server, a dropped
+    /// connection is resumed with a range request when the server supports it, see
+    /// [`DownloadStream`](crate::compute::utils::file_utils::DownloadStream).
+    ///
+    /// Reads from the returned stream fail once the content exceeds the limits of `quota`.
+    ///
+    /// [`LocalSourceResolver`]: crate::compute::source::LocalSourceResolver
     ///
     /// # Arguments
     ///
     /// * `chain_task_id` - The chain task ID for logging
+    /// * `sources` - The resolvers opening the location of the dataset
+    /// * `retry_policy` - The retries of transient download failures
+    /// * `quota` - The size limits of downloads
     ///
     /// # Returns
     ///
-    /// * `Ok(Vec<u8>)` containing the dataset's encrypted content if download and verification succeed.
-    /// * `Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed)` if the download fails.
-    /// * `Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum)` if checksum validation fails.
+    /// * `Ok(Box<dyn Read>)` whose content is the dataset's encrypted content.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed)` if the download fails or
+    ///   no resolver supports the location.
+    /// * `Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum)` if, in IPFS race mode,
+    ///   every gateway returned a content with an invalid checksum.
+    /// * `Err(ReplicateStatusCause::PreComputeInvalidDatasetCid)` if the multi-address holds no
+    ///   verifiable CID or if, in IPFS race mode, every gateway returned a content not matching it.
+    /// * `Err(ReplicateStatusCause::PreComputeFileTooLarge)` if, in IPFS race mode, no gateway
+    ///   returned a content within the file size limit.
     pub fn download_encrypted_dataset(
         &self,
         chain_task_id: &str,
-    ) -> Result<Vec<u8>, ReplicateStatusCause> {
+        sources: &SourceResolvers,
+        retry_policy: &RetryPolicy,
+        quota: &DownloadQuota,
+    ) -> Result<Box<dyn Read>, ReplicateStatusCause> {
         info!(
             "Downloading encrypted dataset file [chainTaskId:{chain_task_id}, url:{}]",
             self.url
         );
 
-        let encrypted_content = if is_multi_address(&self.url) {
-            IPFS_GATEWAYS.iter().find_map(|gateway| {
-                let full_url = format!("{gateway}{}", self.url);
-                info!("Attempting to download dataset from {full_url}");
-
-                if let Some(content) = download_from_url(&full_url) {
-                    info!("Successfully downloaded from {full_url}");
-                    Some(content)
-                } else {
-                    error!("Failed to download from {full_url}");
-                    None
+        let request = SourceRequest {
+            chain_task_id,
+            url: &self.url,
+            checksum: Some(&self.checksum),
+            retry_policy,
+            quota,
+        };
+        let filename = self.filename.to_string();
+        sources
+            .open(&request)
+            .map(|stream| stream.reader)
+            .map_err(|e| match e {
+                SourceError::Unavailable(attempts) => {
+                    ReplicateStatusCause::PreComputeDatasetDownloadFailed(filename, attempts)
+                }
+                SourceError::InvalidCid => {
+                    ReplicateStatusCause::PreComputeInvalidDatasetCid(filename)
                 }
+                SourceError::ChecksumMismatch => {
+                    ReplicateStatusCause::PreComputeInvalidDatasetChecksum(filename)
+                }
+                SourceError::TooLarge => ReplicateStatusCause::PreComputeFileTooLarge(filename),
             })
+    }
+
+    /// Downloads, verifies and decrypts the dataset, streaming the plaintext to `plain_dataset_writer`.
+    ///
+    /// The encrypted content is processed chunk by chunk as it arrives from the network:
+    /// each chunk is hashed, decrypted and written to the output before the next one is read.
+    /// The format is detected from the first bytes of the content, either an authenticated
+    /// envelope (AES-256-GCM or ChaCha20-Poly1305) or the legacy header-less AES-256-CBC
+    /// format, see [`DatasetDecryptor`]. Peak memory usage is bounded by
+    /// [`DATASET_STREAM_BUFFER_SIZE`] plus one envelope segment regardless of the dataset size.
+    ///
+    /// Since plaintext is written before the whole content has been verified, the caller
+    /// must discard everything written to `plain_dataset_writer` when an error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain_task_id` - The chain task ID for logging
+    /// * `sources` - The resolvers opening the location of the dataset
+    /// * `retry_policy` - The retries of transient download failures
+    /// * `quota` - The size limits of downloads, applied to the encrypted content
+    /// * `progress` - Receives the downloaded size, then the verified and decrypted stages
+    /// * `plain_dataset_writer` - Destination of the decrypted dataset
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u64)` with the size of the plaintext if download, verification and decryption succeed.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed)` if the download fails.
+    /// * `Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum)` if checksum validation fails.
+    /// * `Err(ReplicateStatusCause::PreComputeInvalidDatasetCid)` if content fetched from IPFS does not match its CID.
+    /// * `Err(ReplicateStatusCause::PreComputeFileTooLarge)` if the content exceeds the file size limit.
+    /// * `Err(ReplicateStatusCause::PreComputeDownloadQuotaExceeded)` if the content exceeds the total size limit.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed)` if the key is invalid or decryption fails.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetUnsupportedFormat)` if the envelope version or algorithm is unknown.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetAuthenticationFailed)` if the envelope content was tampered with.
+    /// * `Err(ReplicateStatusCause::PreComputeSavingPlainDatasetFailed)` if writing the plaintext fails.
+    pub fn download_and_decrypt_dataset<W: Write>(
+        &self,
+        chain_task_id: &str,
+        sources: &SourceResolvers,
+        retry_policy: &RetryPolicy,
+        quota: &DownloadQuota,
+        progress: &ProgressReporter,
+        plain_dataset_writer: &mut W,
+    ) -> Result<u64, ReplicateStatusCause> {
+        let mut decryptor =
+            DatasetDecryptor::new(self.decode_key()?.expose_secret(), self.filename.as_str());
+        let mut encrypted_stream =
+            self.download_encrypted_dataset(chain_task_id, sources, retry_policy, quota)?;
+        let mut hasher = Sha256::new();
+        let mut encrypted_chunk = vec![0u8; DATASET_STREAM_BUFFER_SIZE];
+        let mut plain_chunk = Vec::with_capacity(DATASET_STREAM_BUFFER_SIZE);
+        let mut plain_size: u64 = 0;
+        let mut encrypted_size: u64 = 0;
+
+        loop {
+            let read = match encrypted_stream.read(&mut encrypted_chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(self.read_error_cause(chain_task_id, e)),
+            };
+            hasher.update(&encrypted_chunk[..read]);
+            encrypted_size += read as u64;
+            progress.report(
+                self.filename.as_str(),
+                ProgressStage::Downloading,
+                encrypted_size,
+            );
+            plain_chunk.clear();
+            if let Err(cause) = decryptor.update(&encrypted_chunk[..read], &mut plain_chunk) {
+                // An invalid checksum takes precedence, as the content is not the expected one.
+                self.drain_and_check_checksum(chain_task_id, &mut encrypted_stream, hasher)?;
+                return Err(cause);
+            }
+            plain_size +=
+                self.write_plain_chunk(chain_task_id, plain_dataset_writer, &plain_chunk)?;
+        }
+
+        self.check_checksum(chain_task_id, hasher)?;
+        progress.report(
+            self.filename.as_str(),
+            ProgressStage::Verified,
+            encrypted_size,
+        );
+
+        plain_chunk.clear();
+        decryptor.finalize(&mut plain_chunk)?;
+        progress.report(
+            self.filename.as_str(),
+            ProgressStage::Decrypted,
+            encrypted_size,
+        );
+        plain_size += self.write_plain_chunk(chain_task_id, plain_dataset_writer, &plain_chunk)?;
+        plain_dataset_writer.flush().map_err(|e| {
+            error!("Failed to flush plain dataset [chainTaskId:{chain_task_id}, error:{e}]");
+            ReplicateStatusCause::PreComputeSavingPlainDatasetFailed
+        })?;
+
+        info!("Dataset downloaded, verified and decrypted successfully [size:{plain_size}]");
+        Ok(plain_size)
+    }
+
+    /// Reads the rest of the encrypted stream to complete its hash, then checks the checksum.
+    fn drain_and_check_checksum(
+        &self,
+        chain_task_id: &str,
+        encrypted_stream: &mut dyn Read,
+        mut hasher: Sha256,
+    ) -> Result<(), ReplicateStatusCause> {
+        let mut encrypted_chunk = vec![0u8; DATASET_STREAM_BUFFER_SIZE];
+        loop {
+            match encrypted_stream.read(&mut encrypted_chunk) {
+                Ok(0) => break,
+                Ok(read) => hasher.update(&encrypted_chunk[..read]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if is_cid_verification_error(&e) || size_limit_error(&e).is_some() => {
+                    return Err(self.read_error_cause(chain_task_id, e));
+                }
+                // The checksum cannot be computed, the decryption error is reported instead.
+                Err(_) => return Ok(()),
+            }
+        }
+        self.check_checksum(chain_task_id, hasher)
+    }
+
+    fn read_error_cause(&self, chain_task_id: &str, e: io::Error) -> ReplicateStatusCause {
+        if is_cid_verification_error(&e) {
+            error!(
+                "Encrypted dataset does not match its IPFS CID [chainTaskId:{chain_task_id}, url:{}]",
+                self.url
+            );
+            ReplicateStatusCause::PreComputeInvalidDatasetCid(self.filename.to_string())
+        } else if let Some(limit) = size_limit_error(&e) {
+            error!(
+                "Encrypted dataset exceeds the download size limits [chainTaskId:{chain_task_id}, url:{}, error:{e}]",
+                self.url
+            );
+            match limit {
+                SizeLimitError::FileTooLarge(_) => {
+                    ReplicateStatusCause::PreComputeFileTooLarge(self.filename.to_string())
+                }
+                SizeLimitError::QuotaExceeded(_) => {
+                    ReplicateStatusCause::PreComputeDownloadQuotaExceeded(self.filename.to_string())
+                }
+            }
         } else {
-            download_from_url(&self.url)
+            error!(
+                "Failed to read encrypted dataset stream [chainTaskId:{chain_task_id}, error:{e}]"
+            );
+            let mut attempts = DownloadAttempts::default();
+            attempts.record(&self.url, &format!("failed to read content: {e}"));
+            ReplicateStatusCause::PreComputeDatasetDownloadFailed(
+                self.filename.to_string(),
+                attempts,
+            )
         }
-        .ok_or(ReplicateStatusCause::PreComputeDatasetDownloadFailed(
-            self.filename.clone(),
-        ))?;
+    }
 
+    fn check_checksum(
+        &self,
+        chain_task_id: &str,
+        hasher: Sha256,
+    ) -> Result<(), ReplicateStatusCause> {
         info!("Checking encrypted dataset checksum [chainTaskId:{chain_task_id}]");
-        let actual_checksum = sha256_from_bytes(&encrypted_content);
-
-        if actual_checksum != self.checksum {
+        let actual_checksum = format!("0x{:x}", hasher.finalize());
+        if !matches_dataset_checksum(&actual_checksum, &self.checksum) {
             error!(
                 "Invalid dataset checksum [chainTaskId:{chain_task_id}, expected:{}, actual:{actual_checksum}]",
                 self.checksum
             );
             return Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum(
-                self.filename.clone(),
+                self.filename.to_string(),
             ));
         }
+        Ok(())
+    }
 
-        info!("Dataset downloaded and verified successfully.");
-        Ok(encrypted_content)
+    /// Decodes the Base64 dataset key and checks it is a valid AES-256 key.
+    fn decode_key(&self) -> Result<SecretBytes, ReplicateStatusCause> {
+        general_purpose::STANDARD
+            .decode(self.key.expose_secret())
+            .ok()
+            .map(SecretBytes::from)
+            .filter(|key| key.len() == AES_KEY_LENGTH)
+            .ok_or_else(|| {
+                error!("Invalid dataset key [dataset:{}]", self.filename);
+                ReplicateStatusCause::PreComputeDatasetDecryptionFailed(self.filename.to_string())
+            })
     }
 
-    /// Decrypts the provided encrypted dataset bytes using AES-CBC.
-    ///
-    /// The first 16 bytes of `encrypted_content` are treated as the IV.
-    /// The rest is the ciphertext. The decryption key is decoded from a Base64 string.
-    ///
-    /// # Arguments
-    ///
-    /// * `encrypted_content` - Full encrypted dataset, including the IV prefix.
-    ///
-    /// # Returns
+    fn write_plain_chunk<W: Write>(
+        &self,
+        chain_task_id: &str,
+        plain_dataset_writer: &mut W,
+        plain_chunk: &[u8],
+    ) -> Result<u64, ReplicateStatusCause> {
+        plain_dataset_writer.write_all(plain_chunk).map_err(|e| {
+            error!("Failed to write plain dataset [chainTaskId:{chain_task_id}, error:{e}]");
+            ReplicateStatusCause::PreComputeSavingPlainDatasetFailed
+        })?;
+        Ok(plain_chunk.len() as u64)
+    }
+}
+
+/// Resolves IPFS multi-addresses through IPFS gateways, see [`IpfsGatewayConfig`].
+///
+/// The gateways are tried in order until one of them answers successfully within the
+/// configured timeout. When race mode is enabled and the checksum of the content is known,
+/// gateways are queried concurrently and the returned stream is the content of the first
+/// gateway whose checksum matches.
+///
+/// Content fetched from IPFS gateways is checked against the CID of the multi-address while
+/// it is read: reads from the returned stream fail if the content does not match its CID.
+/// Transient failures are retried on the same URL before moving on to the next gateway. In race
+/// mode, the content of every gateway is bounded by the file size limit of the quota, and only
+/// the content kept is counted in the total.
+pub struct IpfsSourceResolver {
+    config: IpfsGatewayConfig,
+}
+
+impl IpfsSourceResolver {
+    pub fn new(config: IpfsGatewayConfig) -> Self {
+        IpfsSourceResolver { config }
+    }
+
+    /// Queries all IPFS gateways concurrently and keeps the first content matching the checksum.
     ///
-    /// * `Ok(Vec<u8>)` containing the plaintext dataset if decryption succeeds.
-    /// * `Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed)` if the key is missing, decoding fails, or decryption fails.
-    pub fn decrypt_dataset(
+    /// Each gateway streams the content to its own anonymous temporary file while hashing it.
+    /// As soon as one content matches the expected checksum, the other downloads are abandoned
+    /// at their next read, and the winning file is returned rewound to its start. Each content is
+    /// bounded by the file size limit of `quota`, but is not counted in its total.
+    fn race_ipfs_gateways(
         &self,
-        encrypted_content: &[u8],
-    ) -> Result<Vec<u8>, ReplicateStatusCause> {
-        let key = general_purpose::STANDARD.decode(&self.key).map_err(|_| {
-            ReplicateStatusCause::PreComputeDatasetDecryptionFailed(self.filename.clone())
+        request: &SourceRequest,
+        cid: &Cid,
+        checksum: &str,
+    ) -> Result<File, SourceError> {
+        let (chain_task_id, ipfs_gateways) = (request.chain_task_id, &self.config);
+        let (retry_policy, quota) = (request.retry_policy, request.quota);
+        let gateway_quota = quota.file_limit_only();
+        let winner: Mutex<Option<File>> = Mutex::new(None);
+        let attempts: Mutex<DownloadAttempts> = Mutex::new(DownloadAttempts::default());
+        let done = AtomicBool::new(false);
+        let checksum_mismatch = AtomicBool::new(false);
+        let cid_mismatch = AtomicBool::new(false);
+        let too_large = AtomicBool::new(false);
+
+        thread::scope(|scope| {
+            for gateway in &ipfs_gateways.gateways {
+                let (winner, done, attempts) = (&winner, &done, &attempts);
+                let (checksum_mismatch, cid_mismatch) = (&checksum_mismatch, &cid_mismatch);
+                let (too_large, gateway_quota) = (&too_large, &gateway_quota);
+                scope.spawn(move || {
+                    let full_url = format!("{gateway}{}", request.url);
+                    info!("Attempting to download dataset from {full_url}");
+                    let fetch = match open_gateway_stream(
+                        &full_url,
+                        cid,
+                        ipfs_gateways,
+                        retry_policy,
+                        gateway_quota,
+                    ) {
+                        Ok(stream) => {
+                            fetch_from_gateway(chain_task_id, &full_url, stream, checksum, done)
+                        }
+                        Err(attempts) => {
+                            error!("Failed to download from {full_url}");
+                            GatewayFetch::Failed(attempts)
+                        }
+                    };
+                    match fetch {
+                        GatewayFetch::Matching(file) => {
+                            if !done.swap(true, Ordering::SeqCst) {
+                                info!(
+                                    "IPFS gateway won the race [chainTaskId:{chain_task_id}, url:{full_url}]"
+                                );
+                                *winner.lock().unwrap() = Some(file);
+                            }
+                        }
+                        GatewayFetch::ChecksumMismatch => {
+                            checksum_mismatch.store(true, Ordering::SeqCst)
+                        }
+                        GatewayFetch::CidMismatch => cid_mismatch.store(true, Ordering::SeqCst),
+                        GatewayFetch::TooLarge => too_large.store(true, Ordering::SeqCst),
+                        GatewayFetch::Failed(gateway_attempts) => {
+                            attempts.lock().unwrap().extend(gateway_attempts)
+                        }
+                        GatewayFetch::Abandoned => {}
+                    }
+                });
+            }
+        });
+
+        match winner.into_inner().unwrap_or_else(|e| e.into_inner()) {
+            Some(file) => Ok(file),
+            None if checksum_mismatch.load(Ordering::SeqCst) => {
+                error!(
+                    "No IPFS gateway returned a content matching the dataset checksum [chainTaskId:{chain_task_id}]"
+                );
+                Err(SourceError::ChecksumMismatch)
+            }
+            None if cid_mismatch.load(Ordering::SeqCst) => {
+                error!(
+                    "No IPFS gateway returned a content matching the dataset CID [chainTaskId:{chain_task_id}]"
+                );
+                Err(SourceError::InvalidCid)
+            }
+            None if too_large.load(Ordering::SeqCst) => {
+                error!(
+                    "No IPFS gateway returned a content within the file size limit [chainTaskId:{chain_task_id}]"
+                );
+                Err(SourceError::TooLarge)
+            }
+            None => {
+                error!(
+                    "Failed to download dataset from any IPFS gateway [chainTaskId:{chain_task_id}]"
+                );
+                Err(SourceError::Unavailable(
+                    attempts.into_inner().unwrap_or_else(|e| e.into_inner()),
+                ))
+            }
+        }
+    }
+}
+
+impl SourceResolver for IpfsSourceResolver {
+    fn supports(&self, url: &str) -> bool {
+        is_multi_address(url)
+    }
+
+    fn open(&self, request: &SourceRequest) -> Result<SourceStream, SourceError> {
+        let (chain_task_id, url) = (request.chain_task_id, request.url);
+        let cid = cid_from_multi_address(url).ok_or_else(|| {
+            error!(
+                "No verifiable IPFS CID in multi-address [chainTaskId:{chain_task_id}, url:{url}]"
+            );
+            SourceError::InvalidCid
         })?;
+        if self.config.race
+            && let Some(checksum) = request.checksum
+        {
+            return self
+                .race_ipfs_gateways(request, &cid, checksum)
+                .map(|file| {
+                    let size = file.metadata().ok().map(|metadata| metadata.len());
+                    SourceStream {
+                        reader: Box::new(request.quota.reader(file, size)),
+                        filename: None,
+                    }
+                });
+        }
 
-        if encrypted_content.len() < AES_IV_LENGTH || key.len() != AES_KEY_LENGTH {
-            return Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed(
-                self.filename.clone(),
-            ));
+        let mut attempts = DownloadAttempts::default();
+        for gateway in &self.config.gateways {
+            let full_url = format!("{gateway}{url}");
+            info!("Attempting to download from {full_url}");
+
+            match open_gateway_stream(
+                &full_url,
+                &cid,
+                &self.config,
+                request.retry_policy,
+                request.quota,
+            ) {
+                Ok(reader) => {
+                    info!("Successfully reached {full_url}");
+                    return Ok(SourceStream {
+                        reader,
+                        filename: None,
+                    });
+                }
+                Err(gateway_attempts) => {
+                    error!("Failed to download from {full_url}");
+                    attempts.extend(gateway_attempts);
+                }
+            }
         }
+        Err(SourceError::Unavailable(attempts))
+    }
+}
 
-        let key_slice = &key[..AES_KEY_LENGTH];
-        let iv_slice = &encrypted_content[..AES_IV_LENGTH];
-        let ciphertext = &encrypted_content[AES_IV_LENGTH..];
+/// Stores the content of a gateway in a temporary file while checking its checksum.
+fn fetch_from_gateway(
+    chain_task_id: &str,
+    full_url: &str,
+    mut stream: Box<dyn Read>,
+    checksum: &str,
+    done: &AtomicBool,
+) -> GatewayFetch {
+    let mut file = match tempfile::tempfile() {
+        Ok(file) => file,
+        Err(e) => {
+            error!(
+                "Failed to create temporary dataset file [chainTaskId:{chain_task_id}, error:{e}]"
+            );
+            return GatewayFetch::Failed(DownloadAttempts::default());
+        }
+    };
 
-        Aes256CbcDec::new(key_slice.into(), iv_slice.into())
-            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
-            .map_err(|_| {
-                ReplicateStatusCause::PreComputeDatasetDecryptionFailed(self.filename.clone())
-            })
+    let mut hasher = Sha256::new();
+    let mut encrypted_chunk = vec![0u8; DATASET_STREAM_BUFFER_SIZE];
+    loop {
+        if done.load(Ordering::SeqCst) {
+            info!("Abandoning download from {full_url}, another gateway won the race");
+            return GatewayFetch::Abandoned;
+        }
+        let read = match stream.read(&mut encrypted_chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) if is_cid_verification_error(&e) => {
+                warn!(
+                    "IPFS gateway returned a content not matching the dataset CID [url:{full_url}]"
+                );
+                return GatewayFetch::CidMismatch;
+            }
+            Err(e) if size_limit_error(&e).is_some() => {
+                warn!(
+                    "IPFS gateway returned a content exceeding the file size limit [url:{full_url}]"
+                );
+                return GatewayFetch::TooLarge;
+            }
+            Err(e) => {
+                error!("Failed to download from {full_url}: {e}");
+                let mut attempts = DownloadAttempts::default();
+                attempts.record(full_url, &format!("failed to read content: {e}"));
+                return GatewayFetch::Failed(attempts);
+            }
+        };
+        hasher.update(&encrypted_chunk[..read]);
+        if let Err(e) = file.write_all(&encrypted_chunk[..read]) {
+            error!(
+                "Failed to write temporary dataset file [chainTaskId:{chain_task_id}, error:{e}]"
+            );
+            return GatewayFetch::Failed(DownloadAttempts::default());
+        }
+    }
+
+    let actual_checksum = format!("0x{:x}", hasher.finalize());
+    if !matches_dataset_checksum(&actual_checksum, checksum) {
+        warn!(
+            "IPFS gateway returned an invalid dataset checksum [url:{full_url}, expected:{checksum}, actual:{actual_checksum}]"
+        );
+        return GatewayFetch::ChecksumMismatch;
     }
+    match file.rewind() {
+        Ok(()) => GatewayFetch::Matching(file),
+        Err(e) => {
+            error!(
+                "Failed to rewind temporary dataset file [chainTaskId:{chain_task_id}, error:{e}]"
+            );
+            GatewayFetch::Failed(DownloadAttempts::default())
+        }
+    }
+}
+
+/// Opens a stream on an IPFS gateway, verifying the content against `cid` while it is read.
+///
+/// Outside trustless mode, content that does not match the CID computed with default parameters
+/// is checked against a CAR response of the same gateway, see [`CarFallbackVerifyingReader`].
+/// The content received from the gateway is bounded by `quota`.
+fn open_gateway_stream(
+    full_url: &str,
+    cid: &Cid,
+    ipfs_gateways: &IpfsGatewayConfig,
+    retry_policy: &RetryPolicy,
+    quota: &DownloadQuota,
+) -> Result<Box<dyn Read>, DownloadAttempts> {
+    let options = DownloadOptions {
+        accept: ipfs_gateways
+            .trustless
+            .then(|| CAR_ACCEPT_HEADER.to_string()),
+        timeout: Some(ipfs_gateways.timeout),
+        retry_policy: retry_policy.clone(),
+    };
+    let stream = open_download_stream(full_url, &options)?;
+    let content_length = stream.content_length();
+    let response = quota.reader(stream, content_length);
+    if ipfs_gateways.trustless {
+        return Ok(Box::new(CarFileReader::new(response, cid.clone())));
+    }
+
+    // Content added with non-default parameters is verified against a CAR response instead,
+    // bounded by the file size limit only since the content has already been counted.
+    let car_url = full_url.to_string();
+    let car_options = DownloadOptions {
+        accept: Some(CAR_ACCEPT_HEADER.to_string()),
+        ..options
+    };
+    let car_quota = quota.file_limit_only();
+    let open_car = move || {
+        let stream = open_download_stream(&car_url, &car_options).map_err(|attempts| {
+            io::Error::other(format!("failed to fetch CAR response{attempts}"))
+        })?;
+        let content_length = stream.content_length();
+        Ok(Box::new(car_quota.reader(stream, content_length)) as Box<dyn Read>)
+    };
+    Ok(Box::new(CarFallbackVerifyingReader::new(
+        response,
+        cid.clone(),
+        open_car,
+    )))
+}
+
+/// Outcome of a download from one IPFS gateway in race mode.
+enum GatewayFetch {
+    Matching(File),
+    ChecksumMismatch,
+    CidMismatch,
+    TooLarge,
+    Failed(DownloadAttempts),
+    Abandoned,
+}
+
+/// Parses the checksum of an encrypted dataset like the checksums of input files, see
+/// [`Checksum`], only SHA-256 digests being supported for datasets.
+pub fn parse_dataset_checksum(checksum: &str) -> Result<Checksum, String> {
+    let checksum = Checksum::from_str(checksum)?;
+    match checksum.algorithm {
+        HashAlgorithm::Sha256 => Ok(checksum),
+        _ => Err(format!(
+            "unsupported dataset checksum {checksum}, expected a SHA-256 digest"
+        )),
+    }
+}
+
+/// Returns whether `actual`, the lowercase `0x`-prefixed SHA-256 digest of an encrypted dataset,
+/// matches its expected `checksum`, see [`parse_dataset_checksum`].
+fn matches_dataset_checksum(actual: &str, checksum: &str) -> bool {
+    parse_dataset_checksum(checksum).is_ok_and(|checksum| checksum.digest == actual)
+}
+
+/// Returns the names of the files and directories a dataset named `filename` is written to in
+/// the output folder, including the partial ones used while it is downloaded or extracted.
+pub fn dataset_output_names(filename: &str, extract: Option<ArchiveFormat>) -> Vec<String> {
+    let mut names = vec![
+        filename.to_string(),
+        format!("{filename}{PARTIAL_FILE_SUFFIX}"),
+    ];
+    if let Some(format) = extract {
+        let extraction_dir = format.extraction_dir_name(filename);
+        names.push(format!("{extraction_dir}{PARTIAL_FILE_SUFFIX}"));
+        names.push(extraction_dir);
+    }
+    names
 }
 
-fn is_multi_address(uri: &str) -> bool {
-    !uri.trim().is_empty() && Multiaddr::from_str(uri).is_ok()
+/// Returns whether a dataset URL is an IPFS multi-address, resolved through the IPFS gateways.
+///
+/// IPFS paths holding a CIDv1, which the multi-address parser rejects, are accepted as well.
+pub fn is_multi_address(uri: &str) -> bool {
+    !uri.trim().is_empty()
+        && (Multiaddr::from_str(uri).is_ok() || cid_from_multi_address(uri).is_some())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
+    use std::time::Instant;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     const CHAIN_TASK_ID: &str = "0x123456789abcdef";
     const DATASET_CHECKSUM: &str =
         "0x02a12ef127dcfbdb294a090c8f0b69a0ca30b7940fc36cabf971f488efd374d7";
     const ENCRYPTED_DATASET_KEY: &str = "ubA6H9emVPJT91/flYAmnKHC0phSV3cfuqsLxQfgow0=";
-    const HTTP_DATASET_URL: &str = "https://raw.githubusercontent.com/iExecBlockchainComputing/tee-worker-pre-compute-rust/main/src/tests_resources/encrypted-data.bin";
+    const ENCRYPTED_DATASET_PATH: &str = "src/tests_resources/encrypted-data.bin";
     const PLAIN_DATA_FILE: &str = "0xDatasetAddress";
+    const PLAIN_DATA: &str = "Some very useful data.";
     const IPFS_DATASET_URL: &str = "/ipfs/QmUVhChbLFiuzNK1g2GsWyWEiad7SXPqARnWzGumgziwEp";
+    const IPFS_ENCRYPTED_DATASET_URL: &str = "/ipfs/QmbALgVbzW2w9KLJ2zncX8h2yPwpz5vUdbVUGgC4oet4ip";
+    const IPFS_ENCRYPTED_DATASET_CID_V1_URL: &str =
+        "/ipfs/bafybeif6qo6ohh3jojsod2lzz2bpasbbu2sfroj2olt2qrjaz72ypqfmfu";
+    const NO_RETRY: RetryPolicy = RetryPolicy {
+        max_attempts: 1,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+    const TWO_ATTEMPTS: RetryPolicy = RetryPolicy {
+        max_attempts: 2,
+        ..NO_RETRY
+    };
+
+    fn start_dataset_server(content: Vec<u8>) -> (Runtime, MockServer) {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/encrypted-data.bin"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(content))
+                .mount(&server)
+                .await;
+            server
+        });
+        (rt, server)
+    }
 
-    fn get_test_dataset() -> Dataset {
+    fn get_test_dataset(server: &MockServer) -> Dataset {
         Dataset::new(
-            HTTP_DATASET_URL.to_string(),
+            format!("{}/encrypted-data.bin", server.uri()),
             DATASET_CHECKSUM.to_string(),
-            PLAIN_DATA_FILE.to_string(),
-            ENCRYPTED_DATASET_KEY.to_string(),
+            SafeFilename::from_str(PLAIN_DATA_FILE).unwrap(),
+            SecretString::from(ENCRYPTED_DATASET_KEY),
         )
     }
 
     // region download_encrypted_dataset
     #[test]
     fn download_encrypted_dataset_success() {
-        let dataset = get_test_dataset();
-        let actual_content = dataset.download_encrypted_dataset(CHAIN_TASK_ID);
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let dataset = get_test_dataset(&server);
+        let actual_content = dataset.download_encrypted_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+        );
         assert!(actual_content.is_ok());
     }
 
     #[test]
     fn download_encrypted_dataset_failure_with_invalid_dataset_url() {
-        let mut dataset = get_test_dataset();
+        let (_rt, server) = start_dataset_server(vec![]);
+        let mut dataset = get_test_dataset(&server);
         dataset.url = "http://bad-url".to_string();
-        let actual_content = dataset.download_encrypted_dataset(CHAIN_TASK_ID);
-        assert_eq!(
-            actual_content,
-            Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed(
-                PLAIN_DATA_FILE.to_string()
-            ))
+        let actual_content = dataset.download_encrypted_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &NO_RETRY,
+            &DownloadQuota::default(),
         );
+        assert!(matches!(
+            actual_content.err(),
+            Some(ReplicateStatusCause::PreComputeDatasetDownloadFailed(filename, attempts))
+                if filename == PLAIN_DATA_FILE && attempts.len() == 1
+        ));
     }
 
     #[test]
     fn download_encrypted_dataset_success_with_valid_iexec_gateway() {
-        let mut dataset = get_test_dataset();
+        let (_rt, server) = start_dataset_server(vec![]);
+        let mut dataset = get_test_dataset(&server);
         dataset.url = IPFS_DATASET_URL.to_string();
-        dataset.checksum =
-            "0x323b1637c7999942fbebfe5d42fe15dbfe93737577663afa0181938d7ad4a2ac".to_string();
-        let actual_content = dataset.download_encrypted_dataset(CHAIN_TASK_ID);
-        let expected_content = Ok("hello world !\n".as_bytes().to_vec());
-        assert_eq!(actual_content, expected_content);
+        let mut actual_content = Vec::new();
+        dataset
+            .download_encrypted_dataset(
+                CHAIN_TASK_ID,
+                &SourceResolvers::default(),
+                &RetryPolicy::default(),
+                &DownloadQuota::default(),
+            )
+            .unwrap()
+            .read_to_end(&mut actual_content)
+            .unwrap();
+        assert_eq!(actual_content, "hello world !\n".as_bytes().to_vec());
     }
 
     #[test]
     fn download_encrypted_dataset_failure_with_invalid_gateway() {
-        let mut dataset = get_test_dataset();
+        let (_rt, server) = start_dataset_server(vec![]);
+        let mut dataset = get_test_dataset(&server);
         dataset.url = "/ipfs/INVALID_IPFS_DATASET_URL".to_string();
-        let actual_content = dataset.download_encrypted_dataset(CHAIN_TASK_ID);
-        let expected_content = Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed(
-            PLAIN_DATA_FILE.to_string(),
+        let actual_content = dataset.download_encrypted_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+        );
+        assert!(matches!(
+            actual_content.err(),
+            Some(ReplicateStatusCause::PreComputeDatasetDownloadFailed(filename, attempts))
+                if filename == PLAIN_DATA_FILE && attempts.len() == 1
         ));
-        assert_eq!(actual_content, expected_content);
+    }
+    // endregion
+
+    // region IPFS gateways
+    fn start_gateways_server() -> (Runtime, MockServer) {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            let gateways = [
+                ("/hung", b"never delivered".to_vec(), Duration::from_secs(5)),
+                ("/corrupted", b"corrupted".to_vec(), Duration::ZERO),
+                (
+                    "/valid",
+                    fs::read(ENCRYPTED_DATASET_PATH).unwrap(),
+                    Duration::from_millis(300),
+                ),
+            ];
+            for (gateway, content, delay) in gateways {
+                Mock::given(method("GET"))
+                    .and(path(format!("{gateway}{IPFS_ENCRYPTED_DATASET_URL}")))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .set_body_bytes(content)
+                            .set_delay(delay),
+                    )
+                    .mount(&server)
+                    .await;
+            }
+            server
+        });
+        (rt, server)
+    }
+
+    fn get_gateway_dataset(server: &MockServer) -> Dataset {
+        let mut dataset = get_test_dataset(server);
+        dataset.url = IPFS_ENCRYPTED_DATASET_URL.to_string();
+        dataset
+    }
+
+    fn get_gateway_config(server: &MockServer, gateways: &[&str], race: bool) -> IpfsGatewayConfig {
+        IpfsGatewayConfig {
+            gateways: gateways
+                .iter()
+                .map(|gateway| format!("{}{gateway}", server.uri()))
+                .collect(),
+            timeout: Duration::from_secs(1),
+            race,
+            trustless: false,
+        }
     }
 
     #[test]
-    fn download_encrypted_dataset_failure_with_invalid_dataset_checksum() {
-        let mut dataset = get_test_dataset();
-        dataset.checksum = "invalid_dataset_checksum".to_string();
-        let actual_content = dataset.download_encrypted_dataset(CHAIN_TASK_ID);
-        let expected_content = Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum(
-            PLAIN_DATA_FILE.to_string(),
+    fn download_and_decrypt_dataset_skips_hung_gateway_after_timeout() {
+        let (_rt, server) = start_gateways_server();
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/hung", "/valid"], false);
+
+        let mut plain_data = Vec::new();
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut plain_data,
+        );
+
+        assert_eq!(result, Ok(PLAIN_DATA.len() as u64));
+        assert_eq!(plain_data, PLAIN_DATA.as_bytes());
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_verifies_content_when_url_holds_cid_v1() {
+        let (rt, server) = start_gateways_server();
+        rt.block_on(async {
+            Mock::given(method("GET"))
+                .and(path(format!("/valid{IPFS_ENCRYPTED_DATASET_CID_V1_URL}")))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_bytes(fs::read(ENCRYPTED_DATASET_PATH).unwrap()),
+                )
+                .mount(&server)
+                .await;
+        });
+        let mut dataset = get_gateway_dataset(&server);
+        dataset.url = IPFS_ENCRYPTED_DATASET_CID_V1_URL.to_string();
+        let config = get_gateway_config(&server, &["/valid"], false);
+
+        let mut plain_data = Vec::new();
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut plain_data,
+        );
+
+        assert_eq!(result, Ok(PLAIN_DATA.len() as u64));
+        assert_eq!(plain_data, PLAIN_DATA.as_bytes());
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_first_answering_gateway_returns_wrong_content() {
+        let (_rt, server) = start_gateways_server();
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/corrupted", "/valid"], false);
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInvalidDatasetCid(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_multi_address_has_no_cid() {
+        let (_rt, server) = start_gateways_server();
+        let mut dataset = get_gateway_dataset(&server);
+        dataset.url = "/ip4/127.0.0.1/tcp/80".to_string();
+        let config = get_gateway_config(&server, &["/valid"], false);
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInvalidDatasetCid(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_trustless_gateway_does_not_return_car() {
+        let (_rt, server) = start_gateways_server();
+        let dataset = get_gateway_dataset(&server);
+        let mut config = get_gateway_config(&server, &["/valid"], false);
+        config.trustless = true;
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        let mut expected_attempts = DownloadAttempts::default();
+        expected_attempts.record(
+            &format!("{}/valid{IPFS_ENCRYPTED_DATASET_URL}", server.uri()),
+            "unexpected content type none",
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed(
+                PLAIN_DATA_FILE.to_string(),
+                expected_attempts,
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_uses_first_matching_gateway_when_racing() {
+        let (_rt, server) = start_gateways_server();
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/hung", "/corrupted", "/valid"], true);
+
+        let start = Instant::now();
+        let mut plain_data = Vec::new();
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut plain_data,
+        );
+
+        assert_eq!(result, Ok(PLAIN_DATA.len() as u64));
+        assert_eq!(plain_data, PLAIN_DATA.as_bytes());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_no_gateway_matches_cid_when_racing() {
+        let (_rt, server) = start_gateways_server();
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/hung", "/corrupted"], true);
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInvalidDatasetCid(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_no_gateway_matches_checksum_when_racing() {
+        let (_rt, server) = start_gateways_server();
+        let mut dataset = get_gateway_dataset(&server);
+        dataset.checksum = "0xinvalid".to_string();
+        let config = get_gateway_config(&server, &["/corrupted", "/valid"], true);
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_all_gateways_unreachable_when_racing() {
+        let (_rt, server) = start_gateways_server();
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/hung", "/missing"], true);
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed(filename, attempts))
+                if filename == PLAIN_DATA_FILE && attempts.len() == 2
         ));
-        assert_eq!(actual_content, expected_content);
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_retries_gateway_after_transient_error() {
+        let (rt, server) = start_gateways_server();
+        rt.block_on(async {
+            Mock::given(method("GET"))
+                .and(path(format!("/flaky{IPFS_ENCRYPTED_DATASET_URL}")))
+                .respond_with(ResponseTemplate::new(503))
+                .up_to_n_times(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path(format!("/flaky{IPFS_ENCRYPTED_DATASET_URL}")))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_bytes(fs::read(ENCRYPTED_DATASET_PATH).unwrap()),
+                )
+                .mount(&server)
+                .await;
+        });
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/flaky"], false);
+
+        let mut plain_data = Vec::new();
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &TWO_ATTEMPTS,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut plain_data,
+        );
+
+        assert_eq!(result, Ok(PLAIN_DATA.len() as u64));
+        assert_eq!(plain_data, PLAIN_DATA.as_bytes());
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_reports_attempts_on_every_gateway() {
+        let (rt, server) = start_gateways_server();
+        rt.block_on(async {
+            Mock::given(method("GET"))
+                .and(path(format!("/unavailable{IPFS_ENCRYPTED_DATASET_URL}")))
+                .respond_with(ResponseTemplate::new(503))
+                .mount(&server)
+                .await;
+        });
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/unavailable", "/missing"], false);
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &TWO_ATTEMPTS,
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        let unavailable_url = format!("{}/unavailable{IPFS_ENCRYPTED_DATASET_URL}", server.uri());
+        let missing_url = format!("{}/missing{IPFS_ENCRYPTED_DATASET_URL}", server.uri());
+        let mut expected_attempts = DownloadAttempts::default();
+        expected_attempts.record(&unavailable_url, "HTTP 503 Service Unavailable");
+        expected_attempts.record(&unavailable_url, "HTTP 503 Service Unavailable");
+        expected_attempts.record(&missing_url, "HTTP 404 Not Found");
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetDownloadFailed(
+                PLAIN_DATA_FILE.to_string(),
+                expected_attempts,
+            ))
+        );
     }
     // endregion
 
-    // region decrypt_dataset
+    // region download_and_decrypt_dataset
+    #[test]
+    fn download_and_decrypt_dataset_success_with_valid_dataset() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let dataset = get_test_dataset(&server);
+
+        let mut plain_data = Vec::new();
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut plain_data,
+        );
+
+        assert_eq!(result, Ok(PLAIN_DATA.len() as u64));
+        assert_eq!(plain_data, PLAIN_DATA.as_bytes());
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_accepts_checksum_in_any_case() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let mut dataset = get_test_dataset(&server);
+        dataset.checksum = format!("sha256:0x{}", DATASET_CHECKSUM[2..].to_uppercase());
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(result, Ok(PLAIN_DATA.len() as u64));
+    }
+
     #[test]
-    fn decrypt_dataset_success_with_valid_dataset() {
-        let dataset = get_test_dataset();
+    fn download_and_decrypt_dataset_failure_with_invalid_dataset_checksum() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let mut dataset = get_test_dataset(&server);
+        dataset.checksum = "invalid_dataset_checksum".to_string();
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
 
-        let encrypted_data = dataset.download_encrypted_dataset(CHAIN_TASK_ID).unwrap();
-        let expected_plain_data = Ok("Some very useful data.".as_bytes().to_vec());
-        let actual_plain_data = dataset.decrypt_dataset(&encrypted_data);
+    #[test]
+    fn download_and_decrypt_dataset_failure_with_bad_key() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let mut dataset = get_test_dataset(&server);
+        dataset.key = SecretString::from("bad_key");
 
-        assert_eq!(actual_plain_data, expected_plain_data);
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
     }
 
     #[test]
-    fn decrypt_dataset_failure_with_bad_key() {
-        let mut dataset = get_test_dataset();
-        dataset.key = "bad_key".to_string();
-        let encrypted_data = dataset.download_encrypted_dataset(CHAIN_TASK_ID).unwrap();
-        let actual_plain_data = dataset.decrypt_dataset(&encrypted_data);
+    fn download_and_decrypt_dataset_failure_with_truncated_content() {
+        let encrypted_data = fs::read(ENCRYPTED_DATASET_PATH).unwrap();
+        let truncated_data = encrypted_data[..encrypted_data.len() - 1].to_vec();
+        let (_rt, server) = start_dataset_server(truncated_data.clone());
+        let mut dataset = get_test_dataset(&server);
+        dataset.checksum = format!("0x{:x}", Sha256::digest(&truncated_data));
 
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
         assert_eq!(
-            actual_plain_data,
+            result,
             Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed(
                 PLAIN_DATA_FILE.to_string()
             ))
         );
     }
+
+    #[test]
+    fn download_and_decrypt_dataset_failure_with_unsupported_envelope() {
+        let mut envelope = b"IXDS".to_vec();
+        envelope.extend_from_slice(&[1, 0xFF, 0, 0, 0, 64, 1, 2, 3, 4, 5, 6, 7]);
+        envelope.extend_from_slice(&[0u8; 100]);
+        let (_rt, server) = start_dataset_server(envelope.clone());
+        let mut dataset = get_test_dataset(&server);
+        dataset.checksum = format!("0x{:x}", Sha256::digest(&envelope));
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetUnsupportedFormat(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_reports_invalid_checksum_before_unsupported_envelope() {
+        let mut envelope = b"IXDS".to_vec();
+        envelope.extend_from_slice(&[1, 0xFF, 0, 0, 0, 64, 1, 2, 3, 4, 5, 6, 7]);
+        let (_rt, server) = start_dataset_server(envelope);
+        let dataset = get_test_dataset(&server);
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &RetryPolicy::default(),
+            &DownloadQuota::default(),
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_dataset_too_large() {
+        let encrypted_dataset = fs::read(ENCRYPTED_DATASET_PATH).unwrap();
+        let max_file_size = encrypted_dataset.len() as u64 - 1;
+        let (_rt, server) = start_dataset_server(encrypted_dataset);
+        let dataset = get_test_dataset(&server);
+
+        let mut plain_data = Vec::new();
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::default(),
+            &NO_RETRY,
+            &DownloadQuota::new(Some(max_file_size), None),
+            &ProgressReporter::disabled(),
+            &mut plain_data,
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeFileTooLarge(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+        assert!(plain_data.is_empty());
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_download_quota_exceeded() {
+        let encrypted_dataset = fs::read(ENCRYPTED_DATASET_PATH).unwrap();
+        let quota = DownloadQuota::new(None, Some(encrypted_dataset.len() as u64 + 1));
+        let (_rt, server) = start_dataset_server(encrypted_dataset);
+        let dataset = get_test_dataset(&server);
+        let download = || {
+            dataset.download_and_decrypt_dataset(
+                CHAIN_TASK_ID,
+                &SourceResolvers::default(),
+                &NO_RETRY,
+                &quota,
+                &ProgressReporter::disabled(),
+                &mut Vec::new(),
+            )
+        };
+
+        assert_eq!(download(), Ok(PLAIN_DATA.len() as u64));
+        assert_eq!(
+            download(),
+            Err(ReplicateStatusCause::PreComputeDownloadQuotaExceeded(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn download_and_decrypt_dataset_fails_when_every_raced_gateway_content_too_large() {
+        let (_rt, server) = start_gateways_server();
+        let dataset = get_gateway_dataset(&server);
+        let config = get_gateway_config(&server, &["/corrupted", "/valid"], true);
+        let quota = DownloadQuota::new(Some(4), Some(1024));
+
+        let result = dataset.download_and_decrypt_dataset(
+            CHAIN_TASK_ID,
+            &SourceResolvers::new(config, None),
+            &NO_RETRY,
+            &quota,
+            &ProgressReporter::disabled(),
+            &mut Vec::new(),
+        );
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeFileTooLarge(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+        assert_eq!(quota.used(), 0);
+    }
     // endregion
 }
@@ -0,0 +1,648 @@
+//! Streaming decryption of encrypted datasets.
+//!
+//! Two dataset formats are supported:
+//!
+//! - **Legacy (header-less)**: AES-256-CBC with PKCS7 padding, the first 16 bytes
+//!   being the IV. Integrity only relies on the dataset checksum.
+//! - **Envelope v1**: authenticated encryption split into segments with the
+//!   STREAM construction (big-endian 32-bit counter + last-segment flag), so that
+//!   tampering, reordering or truncation is detected while streaming.
+//!
+//! The envelope layout is:
+//!
+//! ```text
+//! +-------+---------+-----------+--------------+--------------+--------------------------+
+//! | magic | version | algorithm | segment size | nonce prefix | segments (ciphertext+tag) |
+//! | IXDS  | 0x01    | 1 byte    | u32 BE       | 7 bytes      | ...                      |
+//! +-------+---------+-----------+--------------+--------------+--------------------------+
+//! ```
+//!
+//! Each segment holds `segment size` bytes of plaintext (the last one may be shorter)
+//! followed by a 16-byte authentication tag. The header is used as associated data
+//! for every segment, so it cannot be altered either.
+
+use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::utils::secret_utils::SecretBytes;
+use aead::{
+    AeadInPlace, KeyInit, Payload,
+    consts::U12,
+    stream::{DecryptorBE32, NewStream, StreamBE32, StreamPrimitive},
+};
+use aes::Aes256;
+use aes_gcm::{Aes256Gcm, aead};
+use cbc::{
+    Decryptor,
+    cipher::{BlockDecryptMut, KeyIvInit, block_padding::Pkcs7},
+};
+use chacha20poly1305::ChaCha20Poly1305;
+use log::error;
+
+type Aes256CbcDec = Decryptor<Aes256>;
+
+const AES_IV_LENGTH: usize = 16;
+const AES_BLOCK_SIZE: usize = 16;
+/// Magic bytes identifying a versioned dataset envelope.
+pub const ENVELOPE_MAGIC: &[u8; 4] = b"IXDS";
+/// Current version of the dataset envelope.
+pub const ENVELOPE_VERSION: u8 = 1;
+/// Length of the nonce prefix for the STREAM construction with 96-bit nonces.
+pub const ENVELOPE_NONCE_PREFIX_LENGTH: usize = 7;
+/// Total length of the envelope header.
+pub const ENVELOPE_HEADER_LENGTH: usize =
+    ENVELOPE_MAGIC.len() + 2 + 4 + ENVELOPE_NONCE_PREFIX_LENGTH;
+/// Length of the authentication tag appended to each segment.
+pub const ENVELOPE_TAG_LENGTH: usize = 16;
+/// Largest segment size accepted, which bounds the memory used to decrypt a segment.
+pub const ENVELOPE_MAX_SEGMENT_SIZE: usize = 16 * 1024 * 1024;
+
+/// Authenticated encryption algorithms supported by the dataset envelope.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum EnvelopeAlgorithm {
+    Aes256Gcm = 1,
+    ChaCha20Poly1305 = 2,
+}
+
+impl TryFrom<u8> for EnvelopeAlgorithm {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::Aes256Gcm),
+            2 => Ok(Self::ChaCha20Poly1305),
+            unknown => Err(unknown),
+        }
+    }
+}
+
+/// Incremental decryptor for one specific dataset format.
+trait StreamDecryptor {
+    /// Decrypts as much of the content received so far as possible and appends the plaintext to `out`.
+    fn update(
+        &mut self,
+        encrypted_chunk: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), ReplicateStatusCause>;
+
+    /// Decrypts the remaining content once the whole dataset has been received.
+    fn finalize(self: Box<Self>, out: &mut Vec<u8>) -> Result<(), ReplicateStatusCause>;
+}
+
+/// Incremental decryptor detecting the dataset format from its first bytes.
+///
+/// Content starting with [`ENVELOPE_MAGIC`] is parsed as a versioned envelope, any other
+/// content is decrypted with the legacy AES-256-CBC format. Bytes are fed with
+/// [`DatasetDecryptor::update`] in chunks of any size and memory usage stays bounded by
+/// the chunk size plus, for envelopes, one segment.
+///
+/// # Example
+///
+/// ```rust
+/// use tee_worker_pre_compute::compute::decryption::DatasetDecryptor;
+///
+/// let key = [0u8; 32];
+/// let mut decryptor = DatasetDecryptor::new(&key, "dataset.txt");
+/// let mut plaintext = Vec::new();
+/// for chunk in [[0u8; 16], [0u8; 16]] {
+///     if decryptor.update(&chunk, &mut plaintext).is_err() {
+///         break;
+///     }
+/// }
+/// let result = decryptor.finalize(&mut plaintext);
+/// ```
+pub struct DatasetDecryptor {
+    key: SecretBytes,
+    dataset_filename: String,
+    header: Vec<u8>,
+    format: Option<Box<dyn StreamDecryptor>>,
+}
+
+impl DatasetDecryptor {
+    pub fn new(key: &[u8], dataset_filename: &str) -> Self {
+        DatasetDecryptor {
+            key: SecretBytes::from(key),
+            dataset_filename: dataset_filename.to_string(),
+            header: Vec::with_capacity(ENVELOPE_HEADER_LENGTH),
+            format: None,
+        }
+    }
+
+    /// Decrypts as much of the content received so far as possible and appends the plaintext to `out`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the chunk was accepted.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetUnsupportedFormat)` if the envelope header is not supported.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetAuthenticationFailed)` if a segment fails authentication.
+    pub fn update(
+        &mut self,
+        encrypted_chunk: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), ReplicateStatusCause> {
+        if let Some(format) = self.format.as_mut() {
+            return format.update(encrypted_chunk, out);
+        }
+
+        self.header.extend_from_slice(encrypted_chunk);
+        let format: Box<dyn StreamDecryptor> = if self.header.len() < ENVELOPE_MAGIC.len() {
+            return Ok(());
+        } else if !self.header.starts_with(ENVELOPE_MAGIC) {
+            Box::new(AesCbcStreamDecryptor::new(
+                self.key.expose_secret(),
+                &self.dataset_filename,
+            ))
+        } else if self.header.len() < ENVELOPE_HEADER_LENGTH {
+            return Ok(());
+        } else {
+            self.parse_envelope_header()?
+        };
+
+        let buffered = std::mem::take(&mut self.header);
+        self.format.insert(format).update(&buffered, out)
+    }
+
+    /// Decrypts the remaining content once the whole dataset has been received.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the whole dataset was decrypted and, for envelopes, authenticated.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed)` if the content is malformed.
+    /// * `Err(ReplicateStatusCause::PreComputeDatasetAuthenticationFailed)` if the last segment fails authentication.
+    pub fn finalize(self, out: &mut Vec<u8>) -> Result<(), ReplicateStatusCause> {
+        match self.format {
+            Some(format) => format.finalize(out),
+            None => {
+                error!(
+                    "Encrypted dataset is too short to be decrypted [dataset:{}, size:{}]",
+                    self.dataset_filename,
+                    self.header.len()
+                );
+                Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed(
+                    self.dataset_filename,
+                ))
+            }
+        }
+    }
+
+    fn parse_envelope_header(&mut self) -> Result<Box<dyn StreamDecryptor>, ReplicateStatusCause> {
+        let header = &self.header[..ENVELOPE_HEADER_LENGTH];
+        let version = header[4];
+        let algorithm = header[5];
+        let segment_size =
+            u32::from_be_bytes([header[6], header[7], header[8], header[9]]) as usize;
+        let nonce_prefix = &header[10..ENVELOPE_HEADER_LENGTH];
+
+        let unsupported = |reason: String| {
+            error!(
+                "Unsupported dataset envelope [dataset:{}, reason:{reason}]",
+                self.dataset_filename
+            );
+            ReplicateStatusCause::PreComputeDatasetUnsupportedFormat(self.dataset_filename.clone())
+        };
+        if version != ENVELOPE_VERSION {
+            return Err(unsupported(format!("unknown version {version}")));
+        }
+        if segment_size == 0 || segment_size > ENVELOPE_MAX_SEGMENT_SIZE {
+            return Err(unsupported(format!("invalid segment size {segment_size}")));
+        }
+        let algorithm = EnvelopeAlgorithm::try_from(algorithm)
+            .map_err(|unknown| unsupported(format!("unknown algorithm {unknown}")))?;
+
+        let decryptor: Box<dyn StreamDecryptor> = match algorithm {
+            EnvelopeAlgorithm::Aes256Gcm => Box::new(AeadStreamDecryptor::<Aes256Gcm>::new(
+                self.key.expose_secret(),
+                header,
+                nonce_prefix,
+                segment_size,
+                &self.dataset_filename,
+            )?),
+            EnvelopeAlgorithm::ChaCha20Poly1305 => {
+                Box::new(AeadStreamDecryptor::<ChaCha20Poly1305>::new(
+                    self.key.expose_secret(),
+                    header,
+                    nonce_prefix,
+                    segment_size,
+                    &self.dataset_filename,
+                )?)
+            }
+        };
+        // The header has been consumed, only the segments remain to be decrypted.
+        self.header.drain(..ENVELOPE_HEADER_LENGTH);
+        Ok(decryptor)
+    }
+}
+
+/// Incremental AES-256-CBC decryptor for content prefixed by its IV and padded with PKCS7.
+///
+/// Only complete blocks are decrypted, and the last block is always held back until
+/// [`StreamDecryptor::finalize`] since it carries the padding.
+struct AesCbcStreamDecryptor {
+    key: SecretBytes,
+    dataset_filename: String,
+    cipher: Option<Aes256CbcDec>,
+    pending: Vec<u8>,
+}
+
+impl AesCbcStreamDecryptor {
+    fn new(key: &[u8], dataset_filename: &str) -> Self {
+        AesCbcStreamDecryptor {
+            key: SecretBytes::from(key),
+            dataset_filename: dataset_filename.to_string(),
+            cipher: None,
+            pending: Vec::new(),
+        }
+    }
+
+    fn decryption_failed(&self) -> ReplicateStatusCause {
+        error!(
+            "Failed to decrypt legacy AES-CBC dataset [dataset:{}]",
+            self.dataset_filename
+        );
+        ReplicateStatusCause::PreComputeDatasetDecryptionFailed(self.dataset_filename.clone())
+    }
+}
+
+impl StreamDecryptor for AesCbcStreamDecryptor {
+    fn update(
+        &mut self,
+        encrypted_chunk: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), ReplicateStatusCause> {
+        self.pending.extend_from_slice(encrypted_chunk);
+        let cipher = match self.cipher.as_mut() {
+            Some(cipher) => cipher,
+            None if self.pending.len() < AES_IV_LENGTH => return Ok(()),
+            None => {
+                let iv: Vec<u8> = self.pending.drain(..AES_IV_LENGTH).collect();
+                let cipher = Aes256CbcDec::new_from_slices(self.key.expose_secret(), &iv)
+                    .map_err(|_| self.decryption_failed())?;
+                self.cipher.insert(cipher)
+            }
+        };
+
+        let ready = self.pending.len().saturating_sub(1) / AES_BLOCK_SIZE * AES_BLOCK_SIZE;
+        for block in self.pending[..ready].chunks_exact_mut(AES_BLOCK_SIZE) {
+            cipher.decrypt_block_mut(block.into());
+        }
+        out.extend_from_slice(&self.pending[..ready]);
+        self.pending.drain(..ready);
+        Ok(())
+    }
+
+    fn finalize(mut self: Box<Self>, out: &mut Vec<u8>) -> Result<(), ReplicateStatusCause> {
+        let cipher = match self.cipher.take() {
+            Some(cipher) if self.pending.len() == AES_BLOCK_SIZE => cipher,
+            _ => return Err(self.decryption_failed()),
+        };
+        let mut last_block = std::mem::take(&mut self.pending);
+        match cipher.decrypt_padded_mut::<Pkcs7>(&mut last_block) {
+            Ok(plain) => {
+                out.extend_from_slice(plain);
+                Ok(())
+            }
+            Err(_) => Err(self.decryption_failed()),
+        }
+    }
+}
+
+/// Incremental decryptor for envelope segments encrypted with the STREAM construction.
+///
+/// A segment is only decrypted once the following bytes are known, since the last
+/// segment must be decrypted with the last-segment flag set.
+struct AeadStreamDecryptor<A>
+where
+    A: AeadInPlace<NonceSize = U12>,
+{
+    decryptor: DecryptorBE32<A>,
+    header: Vec<u8>,
+    encrypted_segment_size: usize,
+    dataset_filename: String,
+    pending: Vec<u8>,
+}
+
+impl<A> AeadStreamDecryptor<A>
+where
+    A: AeadInPlace<NonceSize = U12> + KeyInit,
+{
+    fn new(
+        key: &[u8],
+        header: &[u8],
+        nonce_prefix: &[u8],
+        segment_size: usize,
+        dataset_filename: &str,
+    ) -> Result<Self, ReplicateStatusCause> {
+        let aead = A::new_from_slice(key).map_err(|_| {
+            error!("Invalid dataset key for envelope [dataset:{dataset_filename}]");
+            ReplicateStatusCause::PreComputeDatasetDecryptionFailed(dataset_filename.to_string())
+        })?;
+        let stream = StreamBE32::from_aead(aead, nonce_prefix.into());
+        Ok(AeadStreamDecryptor {
+            decryptor: stream.decryptor(),
+            header: header.to_vec(),
+            encrypted_segment_size: segment_size + ENVELOPE_TAG_LENGTH,
+            dataset_filename: dataset_filename.to_string(),
+            pending: Vec::new(),
+        })
+    }
+
+    fn authentication_failed(dataset_filename: &str) -> ReplicateStatusCause {
+        error!("Dataset envelope segment failed authentication [dataset:{dataset_filename}]");
+        ReplicateStatusCause::PreComputeDatasetAuthenticationFailed(dataset_filename.to_string())
+    }
+}
+
+impl<A> StreamDecryptor for AeadStreamDecryptor<A>
+where
+    A: AeadInPlace<NonceSize = U12> + KeyInit,
+{
+    fn update(
+        &mut self,
+        encrypted_chunk: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), ReplicateStatusCause> {
+        self.pending.extend_from_slice(encrypted_chunk);
+        let mut offset = 0;
+        while self.pending.len() - offset > self.encrypted_segment_size {
+            let segment = &self.pending[offset..offset + self.encrypted_segment_size];
+            let plain = self
+                .decryptor
+                .decrypt_next(Payload {
+                    msg: segment,
+                    aad: &self.header,
+                })
+                .map_err(|_| Self::authentication_failed(&self.dataset_filename))?;
+            out.extend_from_slice(&plain);
+            offset += self.encrypted_segment_size;
+        }
+        self.pending.drain(..offset);
+        Ok(())
+    }
+
+    fn finalize(self: Box<Self>, out: &mut Vec<u8>) -> Result<(), ReplicateStatusCause> {
+        let AeadStreamDecryptor {
+            decryptor,
+            header,
+            dataset_filename,
+            pending,
+            ..
+        } = *self;
+        let plain = decryptor
+            .decrypt_last(Payload {
+                msg: &pending,
+                aad: &header,
+            })
+            .map_err(|_| Self::authentication_failed(&dataset_filename))?;
+        out.extend_from_slice(&plain);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aead::stream::EncryptorBE32;
+    use base64::{Engine as _, engine::general_purpose};
+    use std::fs;
+
+    const DATASET_FILENAME: &str = "0xDatasetAddress";
+    const LEGACY_DATASET_KEY: &str = "ubA6H9emVPJT91/flYAmnKHC0phSV3cfuqsLxQfgow0=";
+    const LEGACY_DATASET_PATH: &str = "src/tests_resources/encrypted-data.bin";
+    const LEGACY_PLAIN_DATA: &str = "Some very useful data.";
+    const KEY: [u8; 32] = [7u8; 32];
+    const NONCE_PREFIX: [u8; ENVELOPE_NONCE_PREFIX_LENGTH] = [1, 2, 3, 4, 5, 6, 7];
+
+    fn envelope_header(algorithm: u8, segment_size: u32) -> Vec<u8> {
+        let mut header = ENVELOPE_MAGIC.to_vec();
+        header.push(ENVELOPE_VERSION);
+        header.push(algorithm);
+        header.extend_from_slice(&segment_size.to_be_bytes());
+        header.extend_from_slice(&NONCE_PREFIX);
+        header
+    }
+
+    fn seal<A>(header: &[u8], plaintext: &[u8], segment_size: usize) -> Vec<u8>
+    where
+        A: AeadInPlace<NonceSize = U12> + KeyInit,
+    {
+        let aead = A::new_from_slice(&KEY).unwrap();
+        let mut encryptor = EncryptorBE32::from_aead(aead, NONCE_PREFIX.as_slice().into());
+        let mut sealed = header.to_vec();
+        let mut segments = plaintext.chunks(segment_size).peekable();
+        loop {
+            let segment = segments.next().unwrap_or_default();
+            if segments.peek().is_none() {
+                let last = encryptor
+                    .encrypt_last(Payload {
+                        msg: segment,
+                        aad: header,
+                    })
+                    .unwrap();
+                sealed.extend_from_slice(&last);
+                return sealed;
+            }
+            let next = encryptor
+                .encrypt_next(Payload {
+                    msg: segment,
+                    aad: header,
+                })
+                .unwrap();
+            sealed.extend_from_slice(&next);
+        }
+    }
+
+    fn seal_envelope(algorithm: EnvelopeAlgorithm, plaintext: &[u8], segment_size: u32) -> Vec<u8> {
+        let header = envelope_header(algorithm as u8, segment_size);
+        match algorithm {
+            EnvelopeAlgorithm::Aes256Gcm => {
+                seal::<Aes256Gcm>(&header, plaintext, segment_size as usize)
+            }
+            EnvelopeAlgorithm::ChaCha20Poly1305 => {
+                seal::<ChaCha20Poly1305>(&header, plaintext, segment_size as usize)
+            }
+        }
+    }
+
+    fn decrypt_in_chunks(
+        key: &[u8],
+        encrypted: &[u8],
+        chunk_size: usize,
+    ) -> Result<Vec<u8>, ReplicateStatusCause> {
+        let mut decryptor = DatasetDecryptor::new(key, DATASET_FILENAME);
+        let mut plaintext = Vec::new();
+        for chunk in encrypted.chunks(chunk_size) {
+            decryptor.update(chunk, &mut plaintext)?;
+        }
+        decryptor.finalize(&mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    // region legacy AES-CBC
+    #[test]
+    fn decrypt_legacy_dataset_produces_same_plaintext_whatever_the_chunk_size() {
+        let encrypted_data = fs::read(LEGACY_DATASET_PATH).unwrap();
+        let key = general_purpose::STANDARD
+            .decode(LEGACY_DATASET_KEY)
+            .unwrap();
+
+        for chunk_size in [
+            1,
+            7,
+            AES_BLOCK_SIZE,
+            AES_BLOCK_SIZE + 1,
+            encrypted_data.len(),
+        ] {
+            let plaintext = decrypt_in_chunks(&key, &encrypted_data, chunk_size);
+            assert_eq!(
+                plaintext,
+                Ok(LEGACY_PLAIN_DATA.as_bytes().to_vec()),
+                "chunk size {chunk_size}"
+            );
+        }
+    }
+
+    #[test]
+    fn decrypt_legacy_dataset_holds_back_at_most_one_chunk_and_one_block() {
+        let encrypted_data = fs::read(LEGACY_DATASET_PATH).unwrap();
+        let key = general_purpose::STANDARD
+            .decode(LEGACY_DATASET_KEY)
+            .unwrap();
+        let mut decryptor = AesCbcStreamDecryptor::new(&key, DATASET_FILENAME);
+        let mut plaintext = Vec::new();
+        for chunk in encrypted_data.chunks(5) {
+            decryptor.update(chunk, &mut plaintext).unwrap();
+            assert!(decryptor.pending.len() <= 5 + AES_BLOCK_SIZE);
+        }
+    }
+
+    #[test]
+    fn decrypt_legacy_dataset_fails_when_content_shorter_than_iv() {
+        let result = decrypt_in_chunks(&KEY, &[0u8; AES_IV_LENGTH - 1], 4);
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn decrypt_legacy_dataset_fails_when_key_is_wrong() {
+        let encrypted_data = fs::read(LEGACY_DATASET_PATH).unwrap();
+        let result = decrypt_in_chunks(&KEY, &encrypted_data, 16);
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+    // endregion
+
+    // region envelope
+    #[test]
+    fn decrypt_envelope_succeeds_for_each_algorithm_whatever_the_chunk_size() {
+        let plaintext: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        for algorithm in [
+            EnvelopeAlgorithm::Aes256Gcm,
+            EnvelopeAlgorithm::ChaCha20Poly1305,
+        ] {
+            let sealed = seal_envelope(algorithm, &plaintext, 64);
+            for chunk_size in [1, 13, 64, 80, 81, sealed.len()] {
+                assert_eq!(
+                    decrypt_in_chunks(&KEY, &sealed, chunk_size),
+                    Ok(plaintext.clone()),
+                    "algorithm {algorithm:?}, chunk size {chunk_size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decrypt_envelope_succeeds_when_plaintext_is_empty_or_segment_aligned() {
+        for plaintext in [vec![], vec![42u8; 128]] {
+            let sealed = seal_envelope(EnvelopeAlgorithm::Aes256Gcm, &plaintext, 64);
+            assert_eq!(decrypt_in_chunks(&KEY, &sealed, 10), Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn decrypt_envelope_fails_authentication_when_ciphertext_tampered() {
+        let mut sealed = seal_envelope(EnvelopeAlgorithm::ChaCha20Poly1305, &[1u8; 200], 64);
+        sealed[ENVELOPE_HEADER_LENGTH + 3] ^= 0x01;
+        assert_eq!(
+            decrypt_in_chunks(&KEY, &sealed, 32),
+            Err(ReplicateStatusCause::PreComputeDatasetAuthenticationFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn decrypt_envelope_fails_authentication_when_truncated_on_segment_boundary() {
+        let sealed = seal_envelope(EnvelopeAlgorithm::Aes256Gcm, &[1u8; 200], 64);
+        let truncated = &sealed[..ENVELOPE_HEADER_LENGTH + 2 * (64 + ENVELOPE_TAG_LENGTH)];
+        assert_eq!(
+            decrypt_in_chunks(&KEY, truncated, 32),
+            Err(ReplicateStatusCause::PreComputeDatasetAuthenticationFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn decrypt_envelope_fails_authentication_when_header_tampered() {
+        let mut sealed = seal_envelope(EnvelopeAlgorithm::Aes256Gcm, &[1u8; 200], 64);
+        sealed[ENVELOPE_HEADER_LENGTH - 1] ^= 0x01;
+        assert_eq!(
+            decrypt_in_chunks(&KEY, &sealed, 32),
+            Err(ReplicateStatusCause::PreComputeDatasetAuthenticationFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn decrypt_envelope_fails_authentication_when_key_is_wrong() {
+        let sealed = seal_envelope(EnvelopeAlgorithm::Aes256Gcm, &[1u8; 10], 64);
+        assert_eq!(
+            decrypt_in_chunks(&[8u8; 32], &sealed, 32),
+            Err(ReplicateStatusCause::PreComputeDatasetAuthenticationFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn decrypt_envelope_fails_when_format_is_unsupported() {
+        let unsupported_headers = vec![
+            {
+                let mut header = envelope_header(1, 64);
+                header[4] = 2;
+                header
+            },
+            envelope_header(3, 64),
+            envelope_header(1, 0),
+            envelope_header(1, ENVELOPE_MAX_SEGMENT_SIZE as u32 + 1),
+        ];
+        for header in unsupported_headers {
+            assert_eq!(
+                decrypt_in_chunks(&KEY, &header, 5),
+                Err(ReplicateStatusCause::PreComputeDatasetUnsupportedFormat(
+                    DATASET_FILENAME.to_string()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn decrypt_envelope_fails_when_header_is_incomplete() {
+        let header = envelope_header(1, 64);
+        assert_eq!(
+            decrypt_in_chunks(&KEY, &header[..ENVELOPE_HEADER_LENGTH - 1], 5),
+            Err(ReplicateStatusCause::PreComputeDatasetDecryptionFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+    // endregion
+}
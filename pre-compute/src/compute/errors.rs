@@ -1,3 +1,4 @@
+use crate::compute::utils::retry_utils::DownloadAttempts;
 use serde::{Serializer, ser::SerializeStruct};
 use strum_macros::EnumDiscriminants;
 use thiserror::Error;
@@ -9,36 +10,82 @@ use thiserror::Error;
 pub enum ReplicateStatusCause {
     #[error("input file URL {0} is missing")]
     PreComputeAtLeastOneInputFileUrlMissing(usize),
+    #[error("Dataset authentication failed for dataset {0}")]
+    PreComputeDatasetAuthenticationFailed(String),
     #[error("Dataset checksum related environment variable is missing for dataset {0}")]
     PreComputeDatasetChecksumMissing(String),
     #[error("Failed to decrypt dataset {0}")]
     PreComputeDatasetDecryptionFailed(String),
-    #[error("Failed to download encrypted dataset file for dataset {0}")]
-    PreComputeDatasetDownloadFailed(String),
+    #[error("Failed to download encrypted dataset file for dataset {0}{1}")]
+    PreComputeDatasetDownloadFailed(String, DownloadAttempts),
+    #[error("Failed to extract archive of dataset {0}")]
+    PreComputeDatasetExtractionFailed(String),
+    #[error("Archive of dataset {0} exceeds the extraction limits")]
+    PreComputeDatasetExtractionLimitExceeded(String),
     #[error("Dataset filename related environment variable is missing for dataset {0}")]
     PreComputeDatasetFilenameMissing(String),
     #[error("Dataset key related environment variable is missing for dataset {0}")]
     PreComputeDatasetKeyMissing(String),
+    #[error("Failed to retrieve key from the secret management service for dataset {0}")]
+    PreComputeDatasetKeyRetrievalFailed(String),
+    #[error("Archive of dataset {0} contains an unsafe entry")]
+    PreComputeDatasetUnsafeArchiveEntry(String),
+    #[error("Unsupported encrypted dataset format for dataset {0}")]
+    PreComputeDatasetUnsupportedFormat(String),
     #[error("Dataset URL related environment variable is missing for dataset {0}")]
     PreComputeDatasetUrlMissing(String),
+    #[error("Download quota exceeded while downloading {0}")]
+    PreComputeDownloadQuotaExceeded(String),
+    #[error("Dataset filename {0} is used by several datasets")]
+    PreComputeDuplicateDatasetFilename(String),
     #[error("Unexpected error occurred")]
     PreComputeFailedUnknownIssue,
+    #[error("File {0} exceeds the maximum file size")]
+    PreComputeFileTooLarge(String),
     #[error("Invalid TEE signature")]
     PreComputeInvalidTeeSignature,
     #[error("IS_DATASET_REQUIRED environment variable is missing")]
     PreComputeIsDatasetRequiredMissing,
-    #[error("Input file download failed for input {0}")]
-    PreComputeInputFileDownloadFailed(String),
+    #[error("Input file download failed for input {0}{1}")]
+    PreComputeInputFileDownloadFailed(String, DownloadAttempts),
+    #[error("Invalid checksum for input file {0}")]
+    PreComputeInputFileInvalidChecksum(String),
     #[error("Input files number related environment variable is missing")]
     PreComputeInputFilesNumberMissing,
+    #[error("Invalid archive extraction configuration")]
+    PreComputeInvalidArchiveExtractionConfiguration,
+    #[error("Invalid cleanup policy configuration")]
+    PreComputeInvalidCleanupPolicy,
     #[error("Invalid dataset checksum for dataset {0}")]
     PreComputeInvalidDatasetChecksum(String),
+    #[error("Invalid IPFS CID for dataset {0}")]
+    PreComputeInvalidDatasetCid(String),
+    #[error("Invalid filename for dataset {0}")]
+    PreComputeInvalidDatasetFilename(String),
+    #[error("Invalid download quota configuration")]
+    PreComputeInvalidDownloadQuotaConfiguration,
+    #[error("Invalid input file naming configuration")]
+    PreComputeInvalidInputFileNamingConfiguration,
+    #[error("Invalid IPFS gateway configuration")]
+    PreComputeInvalidIpfsGatewayConfiguration,
+    #[error("Invalid local source root configuration")]
+    PreComputeInvalidLocalSourceRoot,
+    #[error("Invalid download retry policy configuration")]
+    PreComputeInvalidRetryPolicy,
+    #[error("Invalid pre-compute session file")]
+    PreComputeInvalidSessionFile,
+    #[error("Invalid number of pre-compute workers")]
+    PreComputeInvalidWorkersNumber,
     #[error("Output folder related environment variable is missing")]
     PreComputeOutputFolderNotFound,
     #[error("Output path related environment variable is missing")]
     PreComputeOutputPathMissing,
+    #[error("Failed to write input files manifest")]
+    PreComputeSavingInputFilesManifestFailed,
     #[error("Failed to write plain dataset file")]
     PreComputeSavingPlainDatasetFailed,
+    #[error("Failed to read pre-compute session file")]
+    PreComputeSessionFileReadFailed,
     #[error("Task ID related environment variable is missing")]
     PreComputeTaskIdMissing,
     #[error("TEE challenge private key related environment variable is missing")]
@@ -86,6 +133,120 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serialize_produces_correct_json_when_configuration_errors() {
+        let test_cases = vec![
+            (
+                ReplicateStatusCause::PreComputeInvalidCleanupPolicy,
+                r#"{"cause":"PRE_COMPUTE_INVALID_CLEANUP_POLICY","message":"Invalid cleanup policy configuration"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidDownloadQuotaConfiguration,
+                r#"{"cause":"PRE_COMPUTE_INVALID_DOWNLOAD_QUOTA_CONFIGURATION","message":"Invalid download quota configuration"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidInputFileNamingConfiguration,
+                r#"{"cause":"PRE_COMPUTE_INVALID_INPUT_FILE_NAMING_CONFIGURATION","message":"Invalid input file naming configuration"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidIpfsGatewayConfiguration,
+                r#"{"cause":"PRE_COMPUTE_INVALID_IPFS_GATEWAY_CONFIGURATION","message":"Invalid IPFS gateway configuration"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidLocalSourceRoot,
+                r#"{"cause":"PRE_COMPUTE_INVALID_LOCAL_SOURCE_ROOT","message":"Invalid local source root configuration"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidRetryPolicy,
+                r#"{"cause":"PRE_COMPUTE_INVALID_RETRY_POLICY","message":"Invalid download retry policy configuration"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidWorkersNumber,
+                r#"{"cause":"PRE_COMPUTE_INVALID_WORKERS_NUMBER","message":"Invalid number of pre-compute workers"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeSavingInputFilesManifestFailed,
+                r#"{"cause":"PRE_COMPUTE_SAVING_INPUT_FILES_MANIFEST_FAILED","message":"Failed to write input files manifest"}"#,
+            ),
+        ];
+
+        for (cause, expected) in test_cases {
+            let serialized = to_string(&cause).unwrap();
+            assert_eq!(serialized, expected);
+        }
+    }
+
+    #[test]
+    fn serialize_produces_correct_json_when_download_size_limits_exceeded() {
+        let test_cases = vec![
+            (
+                ReplicateStatusCause::PreComputeFileTooLarge(DATASET_FILENAME.to_string()),
+                r#"{"cause":"PRE_COMPUTE_FILE_TOO_LARGE","message":"File 0xDatasetAddress exceeds the maximum file size"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeDownloadQuotaExceeded(DATASET_FILENAME.to_string()),
+                r#"{"cause":"PRE_COMPUTE_DOWNLOAD_QUOTA_EXCEEDED","message":"Download quota exceeded while downloading 0xDatasetAddress"}"#,
+            ),
+        ];
+
+        for (cause, expected) in test_cases {
+            let serialized = to_string(&cause).unwrap();
+            assert_eq!(serialized, expected);
+        }
+    }
+
+    #[test]
+    fn serialize_produces_correct_json_when_archive_extraction_fails() {
+        let test_cases = vec![
+            (
+                ReplicateStatusCause::PreComputeDatasetExtractionFailed(
+                    DATASET_FILENAME.to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_DATASET_EXTRACTION_FAILED","message":"Failed to extract archive of dataset 0xDatasetAddress"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeDatasetExtractionLimitExceeded(
+                    DATASET_FILENAME.to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_DATASET_EXTRACTION_LIMIT_EXCEEDED","message":"Archive of dataset 0xDatasetAddress exceeds the extraction limits"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeDatasetUnsafeArchiveEntry(
+                    DATASET_FILENAME.to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_DATASET_UNSAFE_ARCHIVE_ENTRY","message":"Archive of dataset 0xDatasetAddress contains an unsafe entry"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration,
+                r#"{"cause":"PRE_COMPUTE_INVALID_ARCHIVE_EXTRACTION_CONFIGURATION","message":"Invalid archive extraction configuration"}"#,
+            ),
+        ];
+
+        for (cause, expected) in test_cases {
+            let serialized = to_string(&cause).unwrap();
+            assert_eq!(serialized, expected);
+        }
+    }
+
+    #[test]
+    fn serialize_produces_correct_json_when_session_file_fails() {
+        let test_cases = vec![
+            (
+                ReplicateStatusCause::PreComputeSessionFileReadFailed,
+                r#"{"cause":"PRE_COMPUTE_SESSION_FILE_READ_FAILED","message":"Failed to read pre-compute session file"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidSessionFile,
+                r#"{"cause":"PRE_COMPUTE_INVALID_SESSION_FILE","message":"Invalid pre-compute session file"}"#,
+            ),
+        ];
+
+        for (cause, expected) in test_cases {
+            let serialized = to_string(&cause).unwrap();
+            assert_eq!(serialized, expected);
+        }
+    }
+
     #[test]
     fn serialize_produces_correct_json_when_multiple_dataset_errors_with_filenames() {
         let test_cases = vec![
@@ -93,6 +254,12 @@ mod tests {
                 ReplicateStatusCause::PreComputeAtLeastOneInputFileUrlMissing(1),
                 r#"{"cause":"PRE_COMPUTE_AT_LEAST_ONE_INPUT_FILE_URL_MISSING","message":"input file URL 1 is missing"}"#,
             ),
+            (
+                ReplicateStatusCause::PreComputeDatasetAuthenticationFailed(
+                    DATASET_FILENAME.to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_DATASET_AUTHENTICATION_FAILED","message":"Dataset authentication failed for dataset 0xDatasetAddress"}"#,
+            ),
             (
                 ReplicateStatusCause::PreComputeDatasetChecksumMissing(
                     DATASET_FILENAME.to_string(),
@@ -106,15 +273,50 @@ mod tests {
                 r#"{"cause":"PRE_COMPUTE_DATASET_DECRYPTION_FAILED","message":"Failed to decrypt dataset 0xDatasetAddress"}"#,
             ),
             (
-                ReplicateStatusCause::PreComputeDatasetDownloadFailed(DATASET_FILENAME.to_string()),
+                ReplicateStatusCause::PreComputeDatasetDownloadFailed(
+                    DATASET_FILENAME.to_string(),
+                    DownloadAttempts::default(),
+                ),
                 r#"{"cause":"PRE_COMPUTE_DATASET_DOWNLOAD_FAILED","message":"Failed to download encrypted dataset file for dataset 0xDatasetAddress"}"#,
             ),
+            (
+                ReplicateStatusCause::PreComputeDatasetKeyRetrievalFailed(
+                    DATASET_FILENAME.to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_DATASET_KEY_RETRIEVAL_FAILED","message":"Failed to retrieve key from the secret management service for dataset 0xDatasetAddress"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeDatasetUnsupportedFormat(
+                    DATASET_FILENAME.to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_DATASET_UNSUPPORTED_FORMAT","message":"Unsupported encrypted dataset format for dataset 0xDatasetAddress"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInputFileInvalidChecksum(
+                    "https://iex.ec/input".to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_INPUT_FILE_INVALID_CHECKSUM","message":"Invalid checksum for input file https://iex.ec/input"}"#,
+            ),
             (
                 ReplicateStatusCause::PreComputeInvalidDatasetChecksum(
                     DATASET_FILENAME.to_string(),
                 ),
                 r#"{"cause":"PRE_COMPUTE_INVALID_DATASET_CHECKSUM","message":"Invalid dataset checksum for dataset 0xDatasetAddress"}"#,
             ),
+            (
+                ReplicateStatusCause::PreComputeInvalidDatasetCid(DATASET_FILENAME.to_string()),
+                r#"{"cause":"PRE_COMPUTE_INVALID_DATASET_CID","message":"Invalid IPFS CID for dataset 0xDatasetAddress"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeInvalidDatasetFilename("dataset_1".to_string()),
+                r#"{"cause":"PRE_COMPUTE_INVALID_DATASET_FILENAME","message":"Invalid filename for dataset dataset_1"}"#,
+            ),
+            (
+                ReplicateStatusCause::PreComputeDuplicateDatasetFilename(
+                    DATASET_FILENAME.to_string(),
+                ),
+                r#"{"cause":"PRE_COMPUTE_DUPLICATE_DATASET_FILENAME","message":"Dataset filename 0xDatasetAddress is used by several datasets"}"#,
+            ),
         ];
 
         for (cause, expected) in test_cases {
@@ -123,6 +325,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn serialize_produces_correct_json_when_download_failed_after_attempts() {
+        let mut attempts = DownloadAttempts::default();
+        attempts.record("https://iex.ec/input", "HTTP 503 Service Unavailable");
+        attempts.record("https://iex.ec/input", "timed out");
+        let cause = ReplicateStatusCause::PreComputeInputFileDownloadFailed(
+            "https://iex.ec/input".to_string(),
+            attempts,
+        );
+        let serialized = to_string(&cause).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"cause":"PRE_COMPUTE_INPUT_FILE_DOWNLOAD_FAILED","message":"Input file download failed for input https://iex.ec/input after 2 attempts: [1] https://iex.ec/input: HTTP 503 Service Unavailable; [2] https://iex.ec/input: timed out"}"#
+        );
+    }
+
     #[test]
     fn serialize_produces_correct_json_when_vector_of_multiple_errors() {
         let causes = vec![
@@ -0,0 +1,587 @@
+use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::source::{SourceError, SourceRequest, SourceResolvers};
+use crate::compute::utils::file_utils::{
+    delete_file, sanitize_filename, save_stream, url_filename, write_file,
+};
+use crate::compute::utils::hash_utils::{
+    Checksum, ChecksumVerifyingReader, HashAlgorithm, HashingReader, is_checksum_mismatch, sha256,
+};
+use crate::compute::utils::quota_utils::{DownloadQuota, SizeLimitError, size_limit_error};
+use crate::compute::utils::retry_utils::{DownloadAttempts, RetryPolicy};
+use log::{error, info};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest describing the downloaded input files, written to the output folder.
+pub const INPUTS_MANIFEST_FILENAME: &str = "inputs-manifest.json";
+
+/// Represents an input file of a task, downloaded as is to the output folder.
+///
+/// When a checksum is given, the downloaded content is verified against it before the
+/// file is written, so the application only ever consumes the files ordered by the requester.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputFile {
+    pub url: String,
+    pub checksum: Option<Checksum>,
+}
+
+/// An input file written to the output folder.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DownloadedInputFile {
+    pub url: String,
+    pub path: PathBuf,
+    /// Sanitized filename suggested by the server or the URL, see [`assign_original_filenames`].
+    pub original_filename: Option<String>,
+    /// SHA-256 checksum of the content written to `path`.
+    pub checksum: Checksum,
+    pub size: u64,
+}
+
+impl InputFile {
+    pub fn new(url: String, checksum: Option<Checksum>) -> Self {
+        InputFile { url, checksum }
+    }
+
+    /// Downloads the input file to `output_dir`, naming it after the SHA-256 hash of its URL.
+    ///
+    /// The location of the input file is opened by the first of `sources` supporting it, see
+    /// [`SourceResolvers`]. The content is streamed to a partial file which is only renamed to
+    /// its final name once the whole content has been downloaded and, if a checksum is set,
+    /// verified. Transient failures are retried according to `retry_policy`, and the download
+    /// fails as soon as the content exceeds the limits of `quota`.
+    ///
+    /// The original filename of the input file is taken from the source, e.g. the
+    /// `Content-Disposition` header of an HTTP response, or, failing that, from the path of the
+    /// URL, and returned sanitized in [`DownloadedInputFile::original_filename`] without being
+    /// used.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DownloadedInputFile)` describing the downloaded file.
+    /// * `Err(ReplicateStatusCause::PreComputeInputFileInvalidChecksum)` if the content does not
+    ///   match the checksum of the input file.
+    /// * `Err(ReplicateStatusCause::PreComputeFileTooLarge)` if the content exceeds the file
+    ///   size limit.
+    /// * `Err(ReplicateStatusCause::PreComputeDownloadQuotaExceeded)` if the content exceeds the
+    ///   total size limit.
+    /// * `Err(ReplicateStatusCause::PreComputeInputFileDownloadFailed)` if the download or the
+    ///   file writing fails.
+    pub fn download(
+        &self,
+        chain_task_id: &str,
+        output_dir: &str,
+        sources: &SourceResolvers,
+        retry_policy: &RetryPolicy,
+        quota: &DownloadQuota,
+    ) -> Result<DownloadedInputFile, ReplicateStatusCause> {
+        let url = &self.url;
+        info!("Downloading input file [chainTaskId:{chain_task_id}, url:{url}]");
+
+        let request = SourceRequest {
+            chain_task_id,
+            url,
+            checksum: None,
+            retry_policy,
+            quota,
+        };
+        let stream = sources.open(&request).map_err(|e| {
+            error!("Failed to download input file [chainTaskId:{chain_task_id}, url:{url}]");
+            match e {
+                SourceError::Unavailable(attempts) => {
+                    ReplicateStatusCause::PreComputeInputFileDownloadFailed(url.clone(), attempts)
+                }
+                SourceError::ChecksumMismatch => {
+                    ReplicateStatusCause::PreComputeInputFileInvalidChecksum(url.clone())
+                }
+                SourceError::TooLarge => ReplicateStatusCause::PreComputeFileTooLarge(url.clone()),
+                SourceError::InvalidCid => {
+                    let mut attempts = DownloadAttempts::default();
+                    attempts.record(url, "content does not match its CID");
+                    ReplicateStatusCause::PreComputeInputFileDownloadFailed(url.clone(), attempts)
+                }
+            }
+        })?;
+        let original_filename = stream
+            .filename
+            .as_deref()
+            .and_then(sanitize_filename)
+            .or_else(|| url_filename(url).as_deref().and_then(sanitize_filename));
+
+        let filename = sha256(url.clone());
+        let context = format!("chainTaskId:{chain_task_id}, url:{url}");
+        let stream: Box<dyn Read> = match &self.checksum {
+            Some(checksum) => Box::new(ChecksumVerifyingReader::new(
+                stream.reader,
+                checksum.clone(),
+            )),
+            None => stream.reader,
+        };
+        let mut reader = HashingReader::new(stream, HashAlgorithm::Sha256);
+        let path = save_stream(&mut reader, output_dir, &filename, &context).map_err(|e| {
+            if is_checksum_mismatch(&e) {
+                error!("Input file checksum mismatch [{context}, error:{e}]");
+                return ReplicateStatusCause::PreComputeInputFileInvalidChecksum(url.clone());
+            }
+            match size_limit_error(&e) {
+                Some(SizeLimitError::FileTooLarge(_)) => {
+                    return ReplicateStatusCause::PreComputeFileTooLarge(url.clone());
+                }
+                Some(SizeLimitError::QuotaExceeded(_)) => {
+                    return ReplicateStatusCause::PreComputeDownloadQuotaExceeded(url.clone());
+                }
+                None => {}
+            }
+            let mut attempts = DownloadAttempts::default();
+            attempts.record(url, &format!("failed to write file: {e}"));
+            ReplicateStatusCause::PreComputeInputFileDownloadFailed(url.clone(), attempts)
+        })?;
+        let (checksum, size) = reader.finalize();
+        Ok(DownloadedInputFile {
+            url: url.clone(),
+            path,
+            original_filename,
+            checksum,
+            size,
+        })
+    }
+}
+
+/// Renames downloaded input files to their original filenames, in order.
+///
+/// A filename already used by a file of `output_dir`, by a previous input file or by the
+/// [`INPUTS_MANIFEST_FILENAME`] is made unique by appending `-1`, `-2`, etc. to its stem.
+/// Input files without original filename keep their hashed filename. Renaming files in the
+/// order of `files` once all of them are downloaded makes the chosen names deterministic.
+///
+/// Files which cannot be renamed are deleted and removed from `files`.
+///
+/// # Returns
+///
+/// A `PreComputeInputFileDownloadFailed` error for each file which could not be renamed.
+pub fn assign_original_filenames(
+    files: &mut Vec<DownloadedInputFile>,
+    output_dir: &str,
+) -> Vec<ReplicateStatusCause> {
+    let mut used_filenames = HashSet::from([INPUTS_MANIFEST_FILENAME.to_string()]);
+    let mut exit_causes = Vec::new();
+    files.retain_mut(|file| {
+        let Some(original_filename) = &file.original_filename else {
+            return true;
+        };
+        let filename = unique_filename(output_dir, original_filename, &used_filenames);
+        let path = Path::new(output_dir).join(&filename);
+        match fs::rename(&file.path, &path) {
+            Ok(()) => {
+                info!(
+                    "Input file renamed to its original filename [url:{}, filename:{filename}]",
+                    file.url
+                );
+                used_filenames.insert(filename);
+                file.path = path;
+                true
+            }
+            Err(e) => {
+                error!(
+                    "Failed to rename input file [url:{}, filename:{filename}, error:{e}]",
+                    file.url
+                );
+                delete_file(&file.path);
+                let mut attempts = DownloadAttempts::default();
+                attempts.record(&file.url, &format!("failed to rename file: {e}"));
+                exit_causes.push(ReplicateStatusCause::PreComputeInputFileDownloadFailed(
+                    file.url.clone(),
+                    attempts,
+                ));
+                false
+            }
+        }
+    });
+    exit_causes
+}
+
+/// Returns `filename`, or the first `<stem>-<n><extension>` variant of it, which is neither
+/// in `used_filenames` nor an existing file of `output_dir`.
+fn unique_filename(output_dir: &str, filename: &str, used_filenames: &HashSet<String>) -> String {
+    let is_free =
+        |name: &str| !used_filenames.contains(name) && !Path::new(output_dir).join(name).exists();
+    if is_free(filename) {
+        return filename.to_string();
+    }
+    let (stem, extension) = match filename.rfind('.') {
+        Some(index) if index > 0 => filename.split_at(index),
+        _ => (filename, ""),
+    };
+    (1..)
+        .map(|n| format!("{stem}-{n}{extension}"))
+        .find(|name| is_free(name))
+        .expect("an unused filename exists")
+}
+
+#[derive(Serialize)]
+struct InputsManifestEntry<'a> {
+    url: &'a str,
+    filename: String,
+    checksum: String,
+    size: u64,
+}
+
+/// Writes the [`INPUTS_MANIFEST_FILENAME`] manifest to `output_dir`.
+///
+/// The manifest is a JSON array listing, for each downloaded input file, its URL, its local
+/// filename, the SHA-256 checksum of its content and its size in bytes.
+///
+/// # Returns
+///
+/// * `Ok(())` if the manifest is written.
+/// * `Err(ReplicateStatusCause::PreComputeSavingInputFilesManifestFailed)` otherwise.
+pub fn write_inputs_manifest(
+    chain_task_id: &str,
+    output_dir: &str,
+    files: &[DownloadedInputFile],
+) -> Result<(), ReplicateStatusCause> {
+    let entries: Vec<InputsManifestEntry> = files
+        .iter()
+        .map(|file| InputsManifestEntry {
+            url: &file.url,
+            filename: file
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            checksum: file.checksum.to_string(),
+            size: file.size,
+        })
+        .collect();
+    let path = Path::new(output_dir).join(INPUTS_MANIFEST_FILENAME);
+    serde_json::to_vec_pretty(&entries)
+        .map_err(std::io::Error::from)
+        .and_then(|content: Vec<u8>| {
+            write_file(&content, &path, &format!("chainTaskId:{chain_task_id}"))
+        })
+        .map_err(|e| {
+            error!(
+                "Failed to write inputs manifest [chainTaskId:{chain_task_id}, path:{}, error:{e}]",
+                path.display()
+            );
+            ReplicateStatusCause::PreComputeSavingInputFilesManifestFailed
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::utils::file_utils::PARTIAL_FILE_SUFFIX;
+    use sha3::{Digest, Keccak256};
+    use std::fs;
+    use std::str::FromStr;
+    use tempfile::TempDir;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const CHAIN_TASK_ID: &str = "0x123456789abcdef";
+    const CONTENT: &[u8] = b"utf8String";
+    const CONTENT_SHA256: &str =
+        "0xb33845db05fb0822f1f1e3677cc6787b8a1a7a21f3c12f9e97c70cb596222218";
+    const NO_RETRY: RetryPolicy = RetryPolicy {
+        max_attempts: 1,
+        initial_backoff: std::time::Duration::ZERO,
+        max_backoff: std::time::Duration::ZERO,
+    };
+
+    fn start_server(rt: &Runtime) -> MockServer {
+        rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/input"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(CONTENT))
+                .mount(&server)
+                .await;
+            server
+        })
+    }
+
+    fn download(
+        input_file: &InputFile,
+        temp_dir: &TempDir,
+    ) -> Result<DownloadedInputFile, ReplicateStatusCause> {
+        download_with_quota(input_file, temp_dir, &DownloadQuota::default())
+    }
+
+    fn download_with_quota(
+        input_file: &InputFile,
+        temp_dir: &TempDir,
+        quota: &DownloadQuota,
+    ) -> Result<DownloadedInputFile, ReplicateStatusCause> {
+        input_file.download(
+            CHAIN_TASK_ID,
+            temp_dir.path().to_str().unwrap(),
+            &SourceResolvers::default(),
+            &NO_RETRY,
+            quota,
+        )
+    }
+
+    #[test]
+    fn download_succeeds_without_checksum() {
+        let rt = Runtime::new().unwrap();
+        let server = start_server(&rt);
+        let url = format!("{}/input", server.uri());
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = download(&InputFile::new(url.clone(), None), &temp_dir);
+
+        assert_eq!(
+            result.unwrap(),
+            DownloadedInputFile {
+                url: url.clone(),
+                path: temp_dir.path().join(sha256(url)),
+                original_filename: Some("input".to_string()),
+                checksum: Checksum::from_str(CONTENT_SHA256).unwrap(),
+                size: CONTENT.len() as u64,
+            }
+        );
+    }
+
+    #[test]
+    fn download_prefers_content_disposition_filename() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/input"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header(
+                            "Content-Disposition",
+                            r#"attachment; filename="../secret/report.csv""#,
+                        )
+                        .set_body_bytes(CONTENT),
+                )
+                .mount(&server)
+                .await;
+            server
+        });
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = download(
+            &InputFile::new(format!("{}/input", server.uri()), None),
+            &temp_dir,
+        );
+
+        assert_eq!(
+            result.unwrap().original_filename.as_deref(),
+            Some("report.csv")
+        );
+    }
+
+    #[test]
+    fn download_succeeds_when_checksum_matches() {
+        let rt = Runtime::new().unwrap();
+        let server = start_server(&rt);
+        let url = format!("{}/input", server.uri());
+        let keccak256 = format!("keccak256:0x{:x}", Keccak256::digest(CONTENT));
+
+        for checksum in [CONTENT_SHA256.to_string(), keccak256] {
+            let temp_dir = TempDir::new().unwrap();
+            let input_file =
+                InputFile::new(url.clone(), Some(Checksum::from_str(&checksum).unwrap()));
+
+            let result = download(&input_file, &temp_dir);
+
+            assert_eq!(fs::read(result.unwrap().path).unwrap(), CONTENT);
+        }
+    }
+
+    #[test]
+    fn download_fails_and_writes_nothing_when_checksum_mismatches() {
+        let rt = Runtime::new().unwrap();
+        let server = start_server(&rt);
+        let url = format!("{}/input", server.uri());
+        let temp_dir = TempDir::new().unwrap();
+        let checksum = Checksum::from_str(&format!("0x{}", "0".repeat(64))).unwrap();
+
+        let result = download(&InputFile::new(url.clone(), Some(checksum)), &temp_dir);
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInputFileInvalidChecksum(
+                url.clone()
+            ))
+        );
+        let filename = sha256(url);
+        assert!(!temp_dir.path().join(&filename).exists());
+        assert!(
+            !temp_dir
+                .path()
+                .join(format!("{filename}{PARTIAL_FILE_SUFFIX}"))
+                .exists()
+        );
+    }
+
+    #[test]
+    fn download_fails_when_input_file_is_not_found() {
+        let rt = Runtime::new().unwrap();
+        let server = start_server(&rt);
+        let url = format!("{}/missing", server.uri());
+        let temp_dir = TempDir::new().unwrap();
+        let mut attempts = DownloadAttempts::default();
+        attempts.record(&url, "HTTP 404 Not Found");
+
+        let result = download(&InputFile::new(url.clone(), None), &temp_dir);
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeInputFileDownloadFailed(
+                url, attempts
+            ))
+        );
+    }
+
+    #[test]
+    fn download_fails_and_writes_nothing_when_file_too_large() {
+        let rt = Runtime::new().unwrap();
+        let server = start_server(&rt);
+        let url = format!("{}/input", server.uri());
+        let temp_dir = TempDir::new().unwrap();
+        let quota = DownloadQuota::new(Some(CONTENT.len() as u64 - 1), None);
+
+        let result = download_with_quota(&InputFile::new(url.clone(), None), &temp_dir, &quota);
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeFileTooLarge(url.clone()))
+        );
+        assert!(!temp_dir.path().join(sha256(url)).exists());
+    }
+
+    #[test]
+    fn download_fails_when_download_quota_exceeded() {
+        let rt = Runtime::new().unwrap();
+        let server = start_server(&rt);
+        let url = format!("{}/input", server.uri());
+        let quota = DownloadQuota::new(None, Some(CONTENT.len() as u64 + 1));
+
+        let first = download_with_quota(
+            &InputFile::new(url.clone(), None),
+            &TempDir::new().unwrap(),
+            &quota,
+        );
+        let second = download_with_quota(
+            &InputFile::new(url.clone(), None),
+            &TempDir::new().unwrap(),
+            &quota,
+        );
+
+        assert!(first.is_ok());
+        assert_eq!(
+            second,
+            Err(ReplicateStatusCause::PreComputeDownloadQuotaExceeded(url))
+        );
+    }
+
+    // region original filenames
+    fn downloaded_file(
+        temp_dir: &TempDir,
+        url: &str,
+        original_filename: Option<&str>,
+    ) -> DownloadedInputFile {
+        let path = temp_dir.path().join(sha256(url.to_string()));
+        fs::write(&path, url).unwrap();
+        DownloadedInputFile {
+            url: url.to_string(),
+            path,
+            original_filename: original_filename.map(String::from),
+            checksum: Checksum::from_str(CONTENT_SHA256).unwrap(),
+            size: url.len() as u64,
+        }
+    }
+
+    #[test]
+    fn assign_original_filenames_renames_files_in_order_without_collision() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().to_str().unwrap();
+        fs::write(temp_dir.path().join("dataset.txt"), "dataset").unwrap();
+        let mut files = vec![
+            downloaded_file(&temp_dir, "https://a/data.csv", Some("data.csv")),
+            downloaded_file(&temp_dir, "https://b/data.csv", Some("data.csv")),
+            downloaded_file(&temp_dir, "https://c/dataset.txt", Some("dataset.txt")),
+            downloaded_file(
+                &temp_dir,
+                "https://d/manifest",
+                Some(INPUTS_MANIFEST_FILENAME),
+            ),
+            downloaded_file(&temp_dir, "https://e/", None),
+        ];
+
+        let exit_causes = assign_original_filenames(&mut files, output_dir);
+
+        assert!(exit_causes.is_empty());
+        let filenames: Vec<String> = files
+            .iter()
+            .map(|file| {
+                file.path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(
+            filenames,
+            vec![
+                "data.csv".to_string(),
+                "data-1.csv".to_string(),
+                "dataset-1.txt".to_string(),
+                "inputs-manifest-1.json".to_string(),
+                sha256("https://e/".to_string()),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(&files[1].path).unwrap(),
+            "https://b/data.csv"
+        );
+    }
+
+    #[test]
+    fn write_inputs_manifest_lists_downloaded_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().to_str().unwrap();
+        let mut files = vec![downloaded_file(
+            &temp_dir,
+            "https://a/data.csv",
+            Some("data.csv"),
+        )];
+        assign_original_filenames(&mut files, output_dir);
+
+        write_inputs_manifest(CHAIN_TASK_ID, output_dir, &files).unwrap();
+
+        let manifest: serde_json::Value = serde_json::from_slice(
+            &fs::read(temp_dir.path().join(INPUTS_MANIFEST_FILENAME)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            manifest,
+            serde_json::json!([{
+                "url": "https://a/data.csv",
+                "filename": "data.csv",
+                "checksum": format!("sha256:{CONTENT_SHA256}"),
+                "size": 18,
+            }])
+        );
+    }
+
+    #[test]
+    fn write_inputs_manifest_fails_when_output_dir_missing() {
+        let result = write_inputs_manifest(CHAIN_TASK_ID, "/non/existing/output/dir", &[]);
+
+        assert_eq!(
+            result,
+            Err(ReplicateStatusCause::PreComputeSavingInputFilesManifestFailed)
+        );
+    }
+    // endregion
+}
@@ -0,0 +1,331 @@
+use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::session_file::SessionFile;
+use crate::compute::signer::get_challenge;
+use crate::compute::utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error};
+use crate::compute::utils::secret_utils::SecretString;
+use log::error;
+use reqwest::blocking::Client;
+use reqwest::header::AUTHORIZATION;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Maximum duration of a request to the Secret Management Service.
+const SMS_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Source of the Base64-encoded AES-256 keys of the datasets, see [`Dataset::key`].
+///
+/// [`Dataset::key`]: crate::compute::dataset::Dataset::key
+pub trait KeyProvider {
+    /// Returns the key of the dataset with the given `index`, 0 being the main dataset and the
+    /// following ones the bulk datasets. `filename` identifies the dataset in exit causes.
+    fn dataset_key(
+        &self,
+        index: usize,
+        filename: &str,
+    ) -> Result<SecretString, ReplicateStatusCause>;
+}
+
+impl<P: KeyProvider + ?Sized> KeyProvider for &P {
+    fn dataset_key(
+        &self,
+        index: usize,
+        filename: &str,
+    ) -> Result<SecretString, ReplicateStatusCause> {
+        (**self).dataset_key(index, filename)
+    }
+}
+
+/// Reads the keys from the `IEXEC_DATASET_KEY` and `IEXEC_DATASET_#_KEY` environment variables.
+pub struct EnvKeyProvider;
+
+impl KeyProvider for EnvKeyProvider {
+    fn dataset_key(
+        &self,
+        index: usize,
+        filename: &str,
+    ) -> Result<SecretString, ReplicateStatusCause> {
+        get_env_var_or_error(
+            TeeSessionEnvironmentVariable::IexecDatasetKey(index),
+            ReplicateStatusCause::PreComputeDatasetKeyMissing(filename.to_string()),
+        )
+        .map(SecretString::from)
+    }
+}
+
+/// Reads the keys from the `key` fields of the datasets of the session file.
+impl KeyProvider for SessionFile {
+    fn dataset_key(
+        &self,
+        index: usize,
+        filename: &str,
+    ) -> Result<SecretString, ReplicateStatusCause> {
+        self.dataset(index)
+            .and_then(|dataset| dataset.key.clone())
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| ReplicateStatusCause::PreComputeDatasetKeyMissing(filename.to_string()))
+    }
+}
+
+/// Returns empty keys, standing for keys of an SMS which are not retrieved when the
+/// configuration is only validated, see [`SmsKeyProvider`].
+pub struct SkippedKeyProvider;
+
+impl KeyProvider for SkippedKeyProvider {
+    fn dataset_key(
+        &self,
+        _index: usize,
+        _filename: &str,
+    ) -> Result<SecretString, ReplicateStatusCause> {
+        Ok(SecretString::from(""))
+    }
+}
+
+/// Retrieves the keys from a Secret Management Service (SMS), so that they never appear in the
+/// environment of the pre-compute.
+///
+/// The key of the dataset with index `i` is fetched with a `GET` request to
+/// `{base_url}/tee/tasks/{chainTaskId}/datasets/{i}/key`, authenticated by the enclave challenge
+/// signature of the task, see [`get_challenge`]. The SMS answers with a JSON document
+/// `{"key": "<Base64-encoded key>"}`.
+pub struct SmsKeyProvider {
+    base_url: String,
+    chain_task_id: String,
+    authorization: String,
+    client: Client,
+}
+
+#[derive(Deserialize)]
+struct SmsDatasetKey {
+    key: SecretString,
+}
+
+impl SmsKeyProvider {
+    pub fn new(base_url: &str, chain_task_id: &str, authorization: String) -> Self {
+        SmsKeyProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            chain_task_id: chain_task_id.to_string(),
+            authorization,
+            client: Client::new(),
+        }
+    }
+
+    /// Creates a provider querying the SMS at `base_url` for the task of the `IEXEC_TASK_ID`
+    /// environment variable, authenticated by its challenge.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SmsKeyProvider)` if the challenge is computed.
+    /// * `Err(ReplicateStatusCause::PreComputeTaskIdMissing)` if the task ID is missing.
+    /// * `Err(ReplicateStatusCause)` forwarded from [`get_challenge`] otherwise.
+    pub fn from_env(base_url: &str) -> Result<Self, ReplicateStatusCause> {
+        let chain_task_id = get_env_var_or_error(
+            TeeSessionEnvironmentVariable::IexecTaskId,
+            ReplicateStatusCause::PreComputeTaskIdMissing,
+        )?;
+        let authorization = get_challenge(&chain_task_id)?;
+        Ok(Self::new(base_url, &chain_task_id, authorization))
+    }
+}
+
+impl KeyProvider for SmsKeyProvider {
+    fn dataset_key(
+        &self,
+        index: usize,
+        filename: &str,
+    ) -> Result<SecretString, ReplicateStatusCause> {
+        let chain_task_id = &self.chain_task_id;
+        let url = format!(
+            "{}/tee/tasks/{chain_task_id}/datasets/{index}/key",
+            self.base_url
+        );
+        let retrieval_failed =
+            || ReplicateStatusCause::PreComputeDatasetKeyRetrievalFailed(filename.to_string());
+        let response = self
+            .client
+            .get(&url)
+            .header(AUTHORIZATION, &self.authorization)
+            .timeout(SMS_REQUEST_TIMEOUT)
+            .send()
+            .map_err(|e| {
+                error!(
+                    "Failed to request dataset key [chainTaskId:{chain_task_id}, url:{url}, error:{e}]"
+                );
+                retrieval_failed()
+            })?;
+        let status = response.status();
+        if !status.is_success() {
+            error!(
+                "SMS rejected dataset key request [chainTaskId:{chain_task_id}, url:{url}, status:{status}]"
+            );
+            return Err(retrieval_failed());
+        }
+        // The body is not logged as it contains the key.
+        match response.json::<SmsDatasetKey>() {
+            Ok(dataset_key) if !dataset_key.key.is_empty() => Ok(dataset_key.key),
+            _ => {
+                error!(
+                    "Invalid dataset key response from SMS [chainTaskId:{chain_task_id}, url:{url}]"
+                );
+                Err(retrieval_failed())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use temp_env::with_vars;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const CHAIN_TASK_ID: &str = "0x123456789abcdef";
+    const CHALLENGE: &str = "challenge";
+    const DATASET_FILENAME: &str = "dataset.txt";
+    const DATASET_KEY: &str = "ubA6H9emVPJT91/flYAmnKHC0phSV3cfuqsLxQfgow0=";
+
+    fn start_sms_server(index: usize, response: ResponseTemplate) -> (Runtime, MockServer) {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path(format!(
+                    "/tee/tasks/{CHAIN_TASK_ID}/datasets/{index}/key"
+                )))
+                .and(header("Authorization", CHALLENGE))
+                .respond_with(response)
+                .mount(&server)
+                .await;
+            server
+        });
+        (rt, server)
+    }
+
+    // region EnvKeyProvider
+    #[test]
+    fn env_key_provider_reads_indexed_key() {
+        with_vars(
+            vec![
+                ("IEXEC_DATASET_KEY", Some("main-key")),
+                ("IEXEC_DATASET_1_KEY", None),
+                ("IEXEC_DATASET_2_KEY", Some("bulk-key")),
+            ],
+            || {
+                assert_eq!(
+                    EnvKeyProvider.dataset_key(0, DATASET_FILENAME),
+                    Ok(SecretString::from("main-key"))
+                );
+                assert_eq!(
+                    EnvKeyProvider.dataset_key(2, DATASET_FILENAME),
+                    Ok(SecretString::from("bulk-key"))
+                );
+                assert_eq!(
+                    EnvKeyProvider.dataset_key(1, DATASET_FILENAME),
+                    Err(ReplicateStatusCause::PreComputeDatasetKeyMissing(
+                        DATASET_FILENAME.to_string()
+                    ))
+                );
+            },
+        );
+    }
+    // endregion
+
+    // region SessionFile
+    #[test]
+    fn session_file_key_provider_reads_dataset_key() {
+        let session: SessionFile = serde_json::from_value(json!({
+            "dataset": { "key": "main-key" },
+            "bulk_datasets": [{ "key": "" }]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            session.dataset_key(0, DATASET_FILENAME),
+            Ok(SecretString::from("main-key"))
+        );
+        assert_eq!(
+            session.dataset_key(1, DATASET_FILENAME),
+            Err(ReplicateStatusCause::PreComputeDatasetKeyMissing(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+        assert!(!format!("{session:?}").contains("main-key"));
+    }
+    // endregion
+
+    // region SmsKeyProvider
+    #[test]
+    fn sms_key_provider_retrieves_key() {
+        let (_rt, server) = start_sms_server(
+            1,
+            ResponseTemplate::new(200).set_body_json(json!({ "key": DATASET_KEY })),
+        );
+        let provider = SmsKeyProvider::new(
+            &format!("{}/", server.uri()),
+            CHAIN_TASK_ID,
+            CHALLENGE.to_string(),
+        );
+
+        assert_eq!(
+            provider.dataset_key(1, DATASET_FILENAME),
+            Ok(SecretString::from(DATASET_KEY))
+        );
+    }
+
+    #[test]
+    fn sms_key_provider_fails_when_sms_rejects_request() {
+        let (_rt, server) = start_sms_server(0, ResponseTemplate::new(403));
+        let provider = SmsKeyProvider::new(&server.uri(), CHAIN_TASK_ID, CHALLENGE.to_string());
+
+        assert_eq!(
+            provider.dataset_key(0, DATASET_FILENAME),
+            Err(ReplicateStatusCause::PreComputeDatasetKeyRetrievalFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn sms_key_provider_fails_when_response_invalid() {
+        for response in [
+            ResponseTemplate::new(200).set_body_string("not json"),
+            ResponseTemplate::new(200).set_body_json(json!({ "key": "" })),
+        ] {
+            let (_rt, server) = start_sms_server(0, response);
+            let provider = SmsKeyProvider::new(&server.uri(), CHAIN_TASK_ID, CHALLENGE.to_string());
+
+            assert_eq!(
+                provider.dataset_key(0, DATASET_FILENAME),
+                Err(ReplicateStatusCause::PreComputeDatasetKeyRetrievalFailed(
+                    DATASET_FILENAME.to_string()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn sms_key_provider_fails_when_sms_unreachable() {
+        let provider =
+            SmsKeyProvider::new("http://127.0.0.1:1", CHAIN_TASK_ID, CHALLENGE.to_string());
+
+        assert_eq!(
+            provider.dataset_key(0, DATASET_FILENAME),
+            Err(ReplicateStatusCause::PreComputeDatasetKeyRetrievalFailed(
+                DATASET_FILENAME.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn sms_key_provider_from_env_fails_when_task_id_missing() {
+        with_vars(vec![("IEXEC_TASK_ID", None::<&str>)], || {
+            assert!(matches!(
+                SmsKeyProvider::from_env("http://sms"),
+                Err(ReplicateStatusCause::PreComputeTaskIdMissing)
+            ));
+        });
+    }
+    // endregion
+}
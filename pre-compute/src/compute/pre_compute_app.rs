@@ -1,28 +1,42 @@
+use crate::api::worker_api::ProgressStage;
+use crate::compute::archive::extract_archive;
+use crate::compute::cleanup::WrittenPaths;
+use crate::compute::completion::{ProducedFile, ProducedFileKind};
+use crate::compute::dataset::Dataset;
 use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::input_file::{
+    DownloadedInputFile, INPUTS_MANIFEST_FILENAME, InputFile, assign_original_filenames,
+    write_inputs_manifest,
+};
 use crate::compute::pre_compute_args::PreComputeArgs;
+use crate::compute::progress::ProgressReporter;
 use crate::compute::utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error};
-use crate::compute::utils::file_utils::{download_file, write_file};
-use crate::compute::utils::hash_utils::sha256;
+use crate::compute::utils::file_utils::secure_delete;
+use crate::compute::utils::hash_utils::{HashAlgorithm, HashingWriter};
 use log::{error, info};
 #[cfg(test)]
 use mockall::automock;
-use std::path::{Path, PathBuf};
+use std::fs::File;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
 #[cfg_attr(test, automock)]
 pub trait PreComputeAppTrait {
-    fn run(&mut self) -> Result<(), Vec<ReplicateStatusCause>>;
+    fn run(&mut self) -> Result<Vec<ProducedFile>, Vec<ReplicateStatusCause>>;
     fn check_output_folder(&self) -> Result<(), ReplicateStatusCause>;
-    fn download_input_files(&self) -> Result<(), Vec<ReplicateStatusCause>>;
+    fn download_input_files(&self) -> Result<Vec<ProducedFile>, Vec<ReplicateStatusCause>>;
     fn save_plain_dataset_file(
         &self,
-        plain_content: &[u8],
-        plain_dataset_filename: &str,
-    ) -> Result<(), ReplicateStatusCause>;
+        dataset: &Dataset,
+    ) -> Result<ProducedFile, ReplicateStatusCause>;
 }
 
 pub struct PreComputeApp {
     chain_task_id: String,
     pre_compute_args: PreComputeArgs,
+    progress: ProgressReporter,
+    written_paths: WrittenPaths,
 }
 
 impl PreComputeApp {
@@ -30,6 +44,8 @@ impl PreComputeApp {
         PreComputeApp {
             chain_task_id,
             pre_compute_args: PreComputeArgs::default(),
+            progress: ProgressReporter::disabled(),
+            written_paths: WrittenPaths::default(),
         }
     }
 }
@@ -41,15 +57,26 @@ impl PreComputeAppTrait for PreComputeApp {
     /// 1. Reads the output directory from environment variable `IEXEC_PRE_COMPUTE_OUT`
     /// 2. Reads and validates configuration arguments from environment variables
     /// 3. Validates the output folder exists
-    /// 4. Downloads and decrypts all datasets (if required)
+    /// 4. Downloads, decrypts and saves all datasets (if required), extracting archives if asked to
+    ///    and reporting their progress to the worker, see [`ProgressReporter`]
     /// 5. Downloads all input files
     ///
-    /// The method collects all errors encountered during execution and returns them together,
-    /// allowing partial completion when possible (e.g., if one dataset fails, others are still processed).
+    /// When any exit cause is reported, everything written to the output folder by steps 4 and 5
+    /// is removed according to the configured [`CleanupPolicy`], so that no plaintext is left
+    /// behind after a failed run.
+    ///
+    /// [`CleanupPolicy`]: crate::compute::cleanup::CleanupPolicy
+    ///
+    /// Datasets, then input files, are processed concurrently by at most `workers` threads, see
+    /// [`PreComputeArgs`]. The method collects all errors encountered during execution and returns
+    /// them together, allowing partial completion when possible (e.g., if one dataset fails, others
+    /// are still processed). Errors are returned in the order of the datasets and input files,
+    /// whatever the order in which they were processed.
     ///
     /// # Returns
     ///
-    /// - `Ok(())` if all operations completed successfully
+    /// - `Ok(Vec<ProducedFile>)` listing the saved datasets, then the downloaded input files, if
+    ///   all operations completed successfully
     /// - `Err(Vec<ReplicateStatusCause>)` containing all errors encountered during execution
     ///
     /// # Example
@@ -60,7 +87,7 @@ impl PreComputeAppTrait for PreComputeApp {
     /// let mut app = PreComputeApp::new("task_id".to_string());
     /// app.run();
     /// ```
-    fn run(&mut self) -> Result<(), Vec<ReplicateStatusCause>> {
+    fn run(&mut self) -> Result<Vec<ProducedFile>, Vec<ReplicateStatusCause>> {
         let (mut args, mut exit_causes): (PreComputeArgs, Vec<ReplicateStatusCause>);
         match get_env_var_or_error(
             TeeSessionEnvironmentVariable::IexecPreComputeOut,
@@ -80,25 +107,33 @@ impl PreComputeAppTrait for PreComputeApp {
         if let Err(exit_cause) = self.check_output_folder() {
             return Err(vec![exit_cause]);
         }
+        if !self.pre_compute_args.datasets.is_empty() {
+            self.progress = ProgressReporter::from_env(&self.chain_task_id);
+        }
 
-        for dataset in self.pre_compute_args.datasets.iter() {
-            if let Err(exit_cause) = dataset
-                .download_encrypted_dataset(&self.chain_task_id)
-                .and_then(|encrypted_content| dataset.decrypt_dataset(&encrypted_content))
-                .and_then(|plain_content| {
-                    self.save_plain_dataset_file(&plain_content, &dataset.filename)
-                })
-            {
-                exit_causes.push(exit_cause);
-            };
+        let mut produced_files: Vec<ProducedFile> = Vec::new();
+        for result in process_concurrently(
+            &self.pre_compute_args.datasets,
+            self.pre_compute_args.workers,
+            |dataset| self.save_plain_dataset_file(dataset),
+        ) {
+            match result {
+                Ok(produced_file) => produced_files.push(produced_file),
+                Err(exit_cause) => exit_causes.push(exit_cause),
+            }
         }
-        if let Err(exit_cause) = self.download_input_files() {
-            exit_causes.extend(exit_cause);
+        // Waits for the queued progress reports, so that they reach the worker before the outcome
+        self.progress = ProgressReporter::disabled();
+        match self.download_input_files() {
+            Ok(input_files) => produced_files.extend(input_files),
+            Err(exit_cause) => exit_causes.extend(exit_cause),
         };
         if !exit_causes.is_empty() {
+            self.written_paths
+                .cleanup(&self.chain_task_id, self.pre_compute_args.cleanup_policy);
             Err(exit_causes)
         } else {
-            Ok(())
+            Ok(produced_files)
         }
     }
 
@@ -131,91 +166,267 @@ impl PreComputeAppTrait for PreComputeApp {
     ///
     /// # Behavior
     ///
+    /// - Files are downloaded concurrently by at most `workers` threads, a URL listed several
+    ///   times being downloaded once
     /// - Downloads continue even when individual files fail
-    /// - Successfully downloaded files are saved with SHA-256 hashed filenames
+    /// - Transient failures of a download are retried according to the configured retry policy
+    /// - Files with a checksum are verified before being written, see [`InputFile::download`]
+    /// - Successfully downloaded files are saved with SHA-256 hashed filenames, unless
+    ///   `original_input_filenames` is set, in which case they are renamed to their original
+    ///   filenames and listed in an inputs manifest, see [`assign_original_filenames`] and
+    ///   [`write_inputs_manifest`]
     /// - All download failures are collected and returned together
     ///
     /// # Returns
     ///
-    /// - `Ok(())` if all files are downloaded successfully
-    /// - `Err(Vec<ReplicateStatusCause>)` containing a `PreComputeInputFileDownloadFailed` error
-    ///   for each file that failed to download
-    fn download_input_files(&self) -> Result<(), Vec<ReplicateStatusCause>> {
-        let mut exit_causes: Vec<ReplicateStatusCause> = Vec::new();
+    /// - `Ok(Vec<ProducedFile>)` listing the downloaded files, with their final name, if all files
+    ///   are downloaded successfully
+    /// - `Err(Vec<ReplicateStatusCause>)` containing a `PreComputeInputFileDownloadFailed` or
+    ///   `PreComputeInputFileInvalidChecksum` error for each file that failed to download, in the
+    ///   order of the input files, followed by renaming and manifest errors if any
+    fn download_input_files(&self) -> Result<Vec<ProducedFile>, Vec<ReplicateStatusCause>> {
         let args = &self.pre_compute_args;
         let chain_task_id: &str = &self.chain_task_id;
 
-        for url in args.input_files.iter() {
-            info!("Downloading input file [chainTaskId:{chain_task_id}, url:{url}]");
+        let mut input_files: Vec<&InputFile> = Vec::with_capacity(args.input_files.len());
+        for input_file in args.input_files.iter() {
+            if !input_files.contains(&input_file) {
+                input_files.push(input_file);
+            }
+        }
+        let mut exit_causes: Vec<ReplicateStatusCause> = Vec::new();
+        let mut downloaded_files: Vec<DownloadedInputFile> = Vec::new();
+        let sources = args.source_resolvers();
+        for result in process_concurrently(&input_files, args.workers, |input_file| {
+            input_file.download(
+                chain_task_id,
+                &args.output_dir,
+                &sources,
+                &args.retry_policy,
+                &args.download_quota,
+            )
+        }) {
+            match result {
+                Ok(downloaded_file) => {
+                    self.written_paths.record(&downloaded_file.path);
+                    downloaded_files.push(downloaded_file);
+                }
+                Err(exit_cause) => exit_causes.push(exit_cause),
+            }
+        }
 
-            let filename = sha256(url.to_string());
-            if download_file(url, &args.output_dir, &filename).is_none() {
-                exit_causes.push(ReplicateStatusCause::PreComputeInputFileDownloadFailed(
-                    url.to_string(),
-                ));
+        if args.original_input_filenames {
+            exit_causes.extend(assign_original_filenames(
+                &mut downloaded_files,
+                &args.output_dir,
+            ));
+            for downloaded_file in downloaded_files.iter() {
+                self.written_paths.record(&downloaded_file.path);
+            }
+            self.written_paths
+                .record(&Path::new(&args.output_dir).join(INPUTS_MANIFEST_FILENAME));
+            if let Err(exit_cause) =
+                write_inputs_manifest(chain_task_id, &args.output_dir, &downloaded_files)
+            {
+                exit_causes.push(exit_cause);
             }
         }
 
         if !exit_causes.is_empty() {
             Err(exit_causes)
         } else {
-            Ok(())
+            Ok(downloaded_files
+                .into_iter()
+                .map(|file| ProducedFile {
+                    kind: ProducedFileKind::InputFile,
+                    filename: file
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    size: file.size,
+                    sha256: file.checksum.digest,
+                })
+                .collect())
         }
     }
 
-    /// Saves the decrypted (plain) dataset to disk in the configured output directory.
+    /// Streams the decrypted (plain) dataset to disk in the configured output directory.
+    ///
+    /// The output filename is taken from `dataset.filename`, a [`SafeFilename`] so that the file
+    /// cannot be written outside of the output folder. The dataset is downloaded,
+    /// verified and decrypted chunk by chunk by [`Dataset::download_and_decrypt_dataset`],
+    /// and each chunk is written to the output file as soon as it is decrypted, so the
+    /// dataset is never fully held in memory. If any step fails, the partially written
+    /// file is overwritten then deleted, see [`secure_delete`].
+    ///
+    /// When `dataset.extract` is set, the saved file is then extracted to a subdirectory of the
+    /// output folder named after it, see [`extract_archive`] and
+    /// [`ArchiveFormat::extraction_dir_name`]. The archive itself is kept.
     ///
-    /// The output filename is taken from `pre_compute_args.plain_dataset_filename`.
+    /// The [`ProgressStage::Started`] and [`ProgressStage::Saved`] stages of the dataset are
+    /// reported to the worker, along with the intermediate stages reported while downloading.
+    ///
+    /// [`SafeFilename`]: crate::compute::utils::file_utils::SafeFilename
+    /// [`ArchiveFormat::extraction_dir_name`]: crate::compute::archive::ArchiveFormat::extraction_dir_name
     ///
     /// # Arguments
     ///
-    /// * `plain_dataset` - The dataset content to write to a file.
+    /// * `dataset` - The dataset to download, decrypt and save.
     ///
     /// # Returns
     ///
-    /// * `Ok(())` if the file is successfully saved.
+    /// * `Ok(ProducedFile)` with the size and the SHA-256 digest of the plaintext if the file is
+    ///   successfully saved.
     /// * `Err(ReplicateStatusCause::PreComputeSavingPlainDatasetFailed)` if the path is invalid or write fails.
+    /// * `Err(ReplicateStatusCause)` forwarded from [`Dataset::download_and_decrypt_dataset`] or
+    ///   [`extract_archive`] otherwise.
     fn save_plain_dataset_file(
         &self,
-        plain_dataset: &[u8],
-        plain_dataset_filename: &str,
-    ) -> Result<(), ReplicateStatusCause> {
+        dataset: &Dataset,
+    ) -> Result<ProducedFile, ReplicateStatusCause> {
         let chain_task_id: &str = &self.chain_task_id;
         let args = &self.pre_compute_args;
         let output_dir: &str = &args.output_dir;
 
-        let mut path = PathBuf::from(output_dir);
-        path.push(plain_dataset_filename);
+        let filename = &dataset.filename;
+        let path = Path::new(output_dir).join(filename.as_str());
 
         info!(
             "Saving plain dataset file [chain_task_id:{chain_task_id}, path:{}]",
             path.display()
         );
 
-        write_file(
-            plain_dataset,
-            &path,
-            &format!("chainTaskId:{chain_task_id}"),
-        )
-        .map_err(|_| ReplicateStatusCause::PreComputeSavingPlainDatasetFailed)
+        self.progress
+            .report(filename.as_str(), ProgressStage::Started, 0);
+        self.written_paths.record(&path);
+        let file = File::create(&path).map_err(|e| {
+            error!(
+                "Failed to create plain dataset file [chainTaskId:{chain_task_id}, path:{}, error:{e}]",
+                path.display()
+            );
+            ReplicateStatusCause::PreComputeSavingPlainDatasetFailed
+        })?;
+        let mut file = HashingWriter::new(file, HashAlgorithm::Sha256);
+
+        match dataset.download_and_decrypt_dataset(
+            chain_task_id,
+            &args.source_resolvers(),
+            &args.retry_policy,
+            &args.download_quota,
+            &self.progress,
+            &mut file,
+        ) {
+            Ok(size) => {
+                info!(
+                    "File written successfully [chainTaskId:{chain_task_id}, path:{}, size:{size}]",
+                    path.display()
+                );
+                let (checksum, _) = file.finalize();
+                if let Some(format) = dataset.extract {
+                    let destination =
+                        Path::new(output_dir).join(format.extraction_dir_name(filename.as_str()));
+                    extract_archive(
+                        chain_task_id,
+                        &path,
+                        format,
+                        &destination,
+                        &args.extraction_limits,
+                    )?;
+                    self.written_paths.record(&destination);
+                }
+                self.progress
+                    .report(filename.as_str(), ProgressStage::Saved, size);
+                Ok(ProducedFile {
+                    kind: ProducedFileKind::Dataset,
+                    filename: filename.as_str().to_string(),
+                    size,
+                    sha256: checksum.digest,
+                })
+            }
+            Err(exit_cause) => {
+                drop(file);
+                if let Err(e) = secure_delete(&path) {
+                    error!(
+                        "Failed to delete partial plain dataset file [chainTaskId:{chain_task_id}, path:{}, error:{e}]",
+                        path.display()
+                    );
+                }
+                Err(exit_cause)
+            }
+        }
     }
 }
 
+/// Applies `task` to every item using at most `workers` threads.
+///
+/// Items are handed out in order to the first available thread. Results are returned in the
+/// order of `items`, whatever the order in which the tasks complete.
+fn process_concurrently<T, R, F>(items: &[T], workers: usize, task: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = workers.min(items.len());
+    if workers <= 1 {
+        return items.iter().map(task).collect();
+    }
+
+    let next_item = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next_item.fetch_add(1, Ordering::SeqCst);
+                        let Some(item) = items.get(index) else {
+                            return results;
+                        };
+                        results.push((index, task(item)));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::compute::dataset::Dataset;
+    use crate::api::worker_api::WorkerApiClient;
+    use crate::compute::archive::{ArchiveFormat, ExtractionLimits};
+    use crate::compute::cleanup::CleanupPolicy;
+    use crate::compute::dataset::IpfsGatewayConfig;
     use crate::compute::pre_compute_args::PreComputeArgs;
+    use crate::compute::utils::file_utils::SafeFilename;
+    use crate::compute::utils::hash_utils::{sha256, sha256_from_bytes};
+    use crate::compute::utils::quota_utils::DownloadQuota;
+    use crate::compute::utils::retry_utils::{DownloadAttempts, RetryPolicy};
+    use crate::compute::utils::secret_utils::SecretString;
     use std::fs;
+    use std::str::FromStr;
+    use std::time::{Duration, Instant};
     use tempfile::TempDir;
     use testcontainers::core::WaitFor;
     use testcontainers::runners::SyncRunner;
     use testcontainers::{Container, GenericImage};
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     const CHAIN_TASK_ID: &str = "0x123456789abcdef";
     const DATASET_CHECKSUM: &str =
         "0x02a12ef127dcfbdb294a090c8f0b69a0ca30b7940fc36cabf971f488efd374d7";
     const ENCRYPTED_DATASET_KEY: &str = "ubA6H9emVPJT91/flYAmnKHC0phSV3cfuqsLxQfgow0=";
+    const ENCRYPTED_DATASET_PATH: &str = "src/tests_resources/encrypted-data.bin";
     const HTTP_DATASET_URL: &str = "https://raw.githubusercontent.com/iExecBlockchainComputing/tee-worker-pre-compute-rust/main/src/tests_resources/encrypted-data.bin";
     const PLAIN_DATA_FILE: &str = "plain-data.txt";
 
@@ -226,21 +437,44 @@ mod tests {
     ) -> PreComputeApp {
         PreComputeApp {
             chain_task_id: chain_task_id.to_string(),
+            progress: ProgressReporter::disabled(),
             pre_compute_args: PreComputeArgs {
-                input_files: urls.into_iter().map(String::from).collect(),
+                input_files: urls
+                    .into_iter()
+                    .map(|url| InputFile::new(url.to_string(), None))
+                    .collect(),
+                original_input_filenames: false,
                 output_dir: output_dir.to_string(),
                 is_dataset_required: true,
                 iexec_bulk_slice_size: 0,
                 datasets: vec![Dataset {
                     url: HTTP_DATASET_URL.to_string(),
                     checksum: DATASET_CHECKSUM.to_string(),
-                    filename: PLAIN_DATA_FILE.to_string(),
-                    key: ENCRYPTED_DATASET_KEY.to_string(),
+                    filename: SafeFilename::from_str(PLAIN_DATA_FILE).unwrap(),
+                    key: SecretString::from(ENCRYPTED_DATASET_KEY),
+                    extract: None,
                 }],
+                ipfs_gateways: IpfsGatewayConfig::default(),
+                retry_policy: RetryPolicy {
+                    max_attempts: 1,
+                    ..RetryPolicy::default()
+                },
+                workers: 4,
+                download_quota: DownloadQuota::default(),
+                extraction_limits: ExtractionLimits::default(),
+                cleanup_policy: CleanupPolicy::Secure,
+                local_source_root: None,
             },
+            written_paths: WrittenPaths::default(),
         }
     }
 
+    fn get_failed_attempts(url: &str) -> DownloadAttempts {
+        let mut attempts = DownloadAttempts::default();
+        attempts.record(url, "connection failed");
+        attempts
+    }
+
     fn start_container() -> (Container<GenericImage>, String, String) {
         let container = GenericImage::new("kennethreitz/httpbin", "latest")
             .with_wait_for(WaitFor::message_on_stderr("Listening at"))
@@ -337,7 +571,8 @@ mod tests {
         assert_eq!(
             result.unwrap_err(),
             vec![ReplicateStatusCause::PreComputeInputFileDownloadFailed(
-                "https://invalid-url-that-should-fail.com/file.txt".to_string()
+                "https://invalid-url-that-should-fail.com/file.txt".to_string(),
+                get_failed_attempts("https://invalid-url-that-should-fail.com/file.txt"),
             )]
         );
     }
@@ -361,7 +596,8 @@ mod tests {
         assert_eq!(
             result.unwrap_err(),
             vec![ReplicateStatusCause::PreComputeInputFileDownloadFailed(
-                "https://invalid-url-that-should-fail.com/file.txt".to_string()
+                "https://invalid-url-that-should-fail.com/file.txt".to_string(),
+                get_failed_attempts("https://invalid-url-that-should-fail.com/file.txt"),
             )]
         );
 
@@ -373,20 +609,264 @@ mod tests {
         let xml_hash = sha256(xml_url);
         assert!(temp_dir.path().join(xml_hash).exists());
     }
+
+    #[test]
+    fn download_input_files_returns_errors_in_input_order() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/slow-missing"))
+                .respond_with(ResponseTemplate::new(404).set_delay(Duration::from_millis(300)))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/fast-missing"))
+                .respond_with(ResponseTemplate::new(404))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/file"))
+                .respond_with(ResponseTemplate::new(200).set_body_string("content"))
+                .mount(&server)
+                .await;
+            server
+        });
+        let slow_url = format!("{}/slow-missing", server.uri());
+        let file_url = format!("{}/file", server.uri());
+        let fast_url = format!("{}/fast-missing", server.uri());
+        let temp_dir = TempDir::new().unwrap();
+        let app = get_pre_compute_app(
+            CHAIN_TASK_ID,
+            vec![&slow_url, &file_url, &fast_url],
+            temp_dir.path().to_str().unwrap(),
+        );
+
+        let result = app.download_input_files();
+
+        let causes: Vec<String> = result
+            .unwrap_err()
+            .into_iter()
+            .map(|cause| match cause {
+                ReplicateStatusCause::PreComputeInputFileDownloadFailed(url, _) => url,
+                cause => panic!("Unexpected exit cause {cause:?}"),
+            })
+            .collect();
+        assert_eq!(causes, vec![slow_url, fast_url]);
+        assert!(temp_dir.path().join(sha256(file_url)).exists());
+    }
+
+    #[test]
+    fn download_input_files_downloads_duplicate_url_once() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/file"))
+                .respond_with(ResponseTemplate::new(200).set_body_string("content"))
+                .expect(1)
+                .mount(&server)
+                .await;
+            server
+        });
+        let file_url = format!("{}/file", server.uri());
+        let temp_dir = TempDir::new().unwrap();
+        let app = get_pre_compute_app(
+            CHAIN_TASK_ID,
+            vec![&file_url, &file_url],
+            temp_dir.path().to_str().unwrap(),
+        );
+
+        let result = app.download_input_files();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            fs::read(temp_dir.path().join(sha256(file_url))).unwrap(),
+            b"content"
+        );
+    }
+
+    #[test]
+    fn download_input_files_saves_original_filenames_and_manifest() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            for (file_path, content) in [("/a/data.csv", "a"), ("/b/data.csv", "b")] {
+                Mock::given(method("GET"))
+                    .and(path(file_path))
+                    .respond_with(ResponseTemplate::new(200).set_body_string(content))
+                    .mount(&server)
+                    .await;
+            }
+            server
+        });
+        let first_url = format!("{}/a/data.csv", server.uri());
+        let second_url = format!("{}/b/data.csv", server.uri());
+        let temp_dir = TempDir::new().unwrap();
+        let mut app = get_pre_compute_app(
+            CHAIN_TASK_ID,
+            vec![&first_url, &second_url],
+            temp_dir.path().to_str().unwrap(),
+        );
+        app.pre_compute_args.original_input_filenames = true;
+
+        let result = app.download_input_files();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("data.csv")).unwrap(),
+            "a"
+        );
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("data-1.csv")).unwrap(),
+            "b"
+        );
+        let manifest: serde_json::Value = serde_json::from_slice(
+            &fs::read(temp_dir.path().join(INPUTS_MANIFEST_FILENAME)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest[0]["url"], first_url);
+        assert_eq!(manifest[0]["filename"], "data.csv");
+        assert_eq!(manifest[1]["filename"], "data-1.csv");
+        assert_eq!(manifest[1]["size"], 1);
+    }
+    // endregion
+
+    // region process_concurrently
+    #[test]
+    fn process_concurrently_returns_results_in_item_order() {
+        let items: Vec<u64> = vec![50, 10, 30, 0, 20];
+
+        let results = process_concurrently(&items, 3, |delay| {
+            thread::sleep(Duration::from_millis(*delay));
+            delay * 2
+        });
+
+        assert_eq!(results, vec![100, 20, 60, 0, 40]);
+    }
+
+    #[test]
+    fn process_concurrently_runs_at_most_workers_tasks_at_once() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let items: Vec<usize> = (0..12).collect();
+
+        let results = process_concurrently(&items, 3, |item| {
+            let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now_running, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            running.fetch_sub(1, Ordering::SeqCst);
+            *item
+        });
+
+        assert_eq!(results, items);
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn process_concurrently_handles_no_items() {
+        let results = process_concurrently(&Vec::<usize>::new(), 4, |item| *item);
+        assert!(results.is_empty());
+    }
+    // endregion
+
+    // region run cleanup
+    fn run_with_failing_input_file(cleanup_policy: Option<&str>) -> TempDir {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let temp_dir = TempDir::new().unwrap();
+        let dataset = get_served_dataset(&server, PLAIN_DATA_FILE);
+        let missing_url = format!("{}/missing.txt", server.uri());
+        let env_vars = vec![
+            (
+                "IEXEC_PRE_COMPUTE_OUT",
+                Some(temp_dir.path().to_str().unwrap()),
+            ),
+            ("IS_DATASET_REQUIRED", Some("true")),
+            ("IEXEC_BULK_SLICE_SIZE", Some("0")),
+            ("IEXEC_DATASET_URL", Some(dataset.url.as_str())),
+            ("IEXEC_DATASET_CHECKSUM", Some(DATASET_CHECKSUM)),
+            ("IEXEC_DATASET_FILENAME", Some(PLAIN_DATA_FILE)),
+            ("IEXEC_DATASET_KEY", Some(ENCRYPTED_DATASET_KEY)),
+            ("IEXEC_INPUT_FILES_NUMBER", Some("1")),
+            ("IEXEC_INPUT_FILE_URL_1", Some(missing_url.as_str())),
+            ("IEXEC_PRE_COMPUTE_CLEANUP_ON_FAILURE", cleanup_policy),
+        ];
+
+        let result = temp_env::with_vars(env_vars, || {
+            PreComputeApp::new(CHAIN_TASK_ID.to_string()).run()
+        });
+
+        assert!(matches!(
+            result.unwrap_err().as_slice(),
+            [ReplicateStatusCause::PreComputeInputFileDownloadFailed(url, _)] if *url == missing_url
+        ));
+        temp_dir
+    }
+
+    #[test]
+    fn run_removes_written_files_when_failing() {
+        let temp_dir = run_with_failing_input_file(None);
+
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn run_keeps_written_files_when_failing_with_keep_policy() {
+        let temp_dir = run_with_failing_input_file(Some("keep"));
+
+        assert_eq!(
+            fs::read(temp_dir.path().join(PLAIN_DATA_FILE)).unwrap(),
+            b"Some very useful data."
+        );
+    }
     // endregion
 
     // region save_plain_dataset_file
+    fn start_dataset_server(content: Vec<u8>) -> (Runtime, MockServer) {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/encrypted-data.bin"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(content))
+                .mount(&server)
+                .await;
+            server
+        });
+        (rt, server)
+    }
+
+    fn get_served_dataset(server: &MockServer, filename: &str) -> Dataset {
+        Dataset {
+            url: format!("{}/encrypted-data.bin", server.uri()),
+            checksum: DATASET_CHECKSUM.to_string(),
+            filename: SafeFilename::from_str(filename).unwrap(),
+            key: SecretString::from(ENCRYPTED_DATASET_KEY),
+            extract: None,
+        }
+    }
+
     #[test]
     fn save_plain_dataset_file_success_with_valid_output_dir() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
         let temp_dir = TempDir::new().unwrap();
         let output_path = temp_dir.path().to_str().unwrap();
 
         let app = get_pre_compute_app(CHAIN_TASK_ID, vec![], output_path);
 
         let plain_dataset = "Some very useful data.".as_bytes().to_vec();
-        let saved_dataset = app.save_plain_dataset_file(&plain_dataset, PLAIN_DATA_FILE);
+        let saved_dataset =
+            app.save_plain_dataset_file(&get_served_dataset(&server, PLAIN_DATA_FILE));
 
-        assert!(saved_dataset.is_ok());
+        assert_eq!(
+            saved_dataset,
+            Ok(ProducedFile {
+                kind: ProducedFileKind::Dataset,
+                filename: PLAIN_DATA_FILE.to_string(),
+                size: plain_dataset.len() as u64,
+                sha256: sha256_from_bytes(&plain_dataset),
+            })
+        );
 
         let expected_file_path = temp_dir.path().join(PLAIN_DATA_FILE);
         assert!(
@@ -402,20 +882,151 @@ mod tests {
         );
     }
 
+    #[test]
+    fn save_plain_dataset_file_reads_dataset_from_local_source_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_root = temp_dir.path().join("datasets");
+        let output_dir = temp_dir.path().join("out");
+        fs::create_dir(&source_root).unwrap();
+        fs::create_dir(&output_dir).unwrap();
+        fs::copy(
+            ENCRYPTED_DATASET_PATH,
+            source_root.join("encrypted-data.bin"),
+        )
+        .unwrap();
+        let mut app = get_pre_compute_app(CHAIN_TASK_ID, vec![], output_dir.to_str().unwrap());
+        app.pre_compute_args.local_source_root = Some(fs::canonicalize(&source_root).unwrap());
+        let mut dataset = app.pre_compute_args.datasets[0].clone();
+        dataset.url = format!("file://{}/encrypted-data.bin", source_root.display());
+
+        let saved_dataset = app.save_plain_dataset_file(&dataset);
+
+        assert!(saved_dataset.is_ok());
+        assert_eq!(
+            fs::read_to_string(output_dir.join(PLAIN_DATA_FILE)).unwrap(),
+            "Some very useful data."
+        );
+    }
+
     #[test]
     fn save_plain_dataset_file_failure_with_invalid_output_dir() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
         let temp_dir = TempDir::new().unwrap();
-        let output_path = temp_dir.path().to_str().unwrap();
+        let output_path = temp_dir.path().join("some-folder-123");
 
-        let app = get_pre_compute_app(CHAIN_TASK_ID, vec![], output_path);
-        let plain_dataset = "Some very useful data.".as_bytes().to_vec();
+        let app = get_pre_compute_app(CHAIN_TASK_ID, vec![], output_path.to_str().unwrap());
         let saved_dataset =
-            app.save_plain_dataset_file(&plain_dataset, "/some-folder-123/not-found");
+            app.save_plain_dataset_file(&get_served_dataset(&server, PLAIN_DATA_FILE));
 
         assert_eq!(
             saved_dataset,
             Err(ReplicateStatusCause::PreComputeSavingPlainDatasetFailed)
         );
     }
+
+    #[test]
+    fn save_plain_dataset_file_fails_when_extraction_of_plain_file_fails() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().to_str().unwrap();
+
+        let app = get_pre_compute_app(CHAIN_TASK_ID, vec![], output_path);
+        let mut dataset = get_served_dataset(&server, "plain-data.zip");
+        dataset.extract = Some(ArchiveFormat::Zip);
+        let saved_dataset = app.save_plain_dataset_file(&dataset);
+
+        assert_eq!(
+            saved_dataset,
+            Err(ReplicateStatusCause::PreComputeDatasetExtractionFailed(
+                "plain-data.zip".to_string()
+            ))
+        );
+        assert!(temp_dir.path().join("plain-data.zip").exists());
+        assert!(!temp_dir.path().join("plain-data").exists());
+    }
+
+    #[test]
+    fn save_plain_dataset_file_deletes_partial_file_when_checksum_invalid() {
+        let (_rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        let temp_dir = TempDir::new().unwrap();
+        let output_path = temp_dir.path().to_str().unwrap();
+
+        let app = get_pre_compute_app(CHAIN_TASK_ID, vec![], output_path);
+        let mut dataset = get_served_dataset(&server, PLAIN_DATA_FILE);
+        dataset.checksum = "0xbadchecksum".to_string();
+        let saved_dataset = app.save_plain_dataset_file(&dataset);
+
+        assert_eq!(
+            saved_dataset,
+            Err(ReplicateStatusCause::PreComputeInvalidDatasetChecksum(
+                PLAIN_DATA_FILE.to_string()
+            ))
+        );
+        assert!(!temp_dir.path().join(PLAIN_DATA_FILE).exists());
+    }
+
+    #[test]
+    fn save_plain_dataset_file_reports_progress_to_worker() {
+        let (rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        rt.block_on(async {
+            Mock::given(method("POST"))
+                .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/progress")))
+                .respond_with(ResponseTemplate::new(200))
+                .mount(&server)
+                .await;
+        });
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut app = get_pre_compute_app(CHAIN_TASK_ID, vec![], temp_dir.path().to_str().unwrap());
+        app.progress = ProgressReporter::new(
+            CHAIN_TASK_ID,
+            WorkerApiClient::new(&server.uri()),
+            "challenge".to_string(),
+            Duration::from_secs(60),
+        );
+        let saved_dataset =
+            app.save_plain_dataset_file(&get_served_dataset(&server, PLAIN_DATA_FILE));
+
+        assert!(saved_dataset.is_ok());
+        // Dropping the reporter waits for the queued reports to be sent
+        app.progress = ProgressReporter::disabled();
+        let stages: Vec<serde_json::Value> = rt
+            .block_on(server.received_requests())
+            .unwrap()
+            .iter()
+            .filter(|request| request.method.as_str() == "POST")
+            .map(|request| {
+                serde_json::from_slice::<serde_json::Value>(&request.body).unwrap()["stage"].clone()
+            })
+            .collect();
+        assert_eq!(stages, vec!["STARTED", "VERIFIED", "DECRYPTED", "SAVED"]);
+    }
+
+    #[test]
+    fn save_plain_dataset_file_does_not_wait_for_slow_progress_reports() {
+        let (rt, server) = start_dataset_server(fs::read(ENCRYPTED_DATASET_PATH).unwrap());
+        rt.block_on(async {
+            Mock::given(method("POST"))
+                .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/progress")))
+                .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(1)))
+                .mount(&server)
+                .await;
+        });
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut app = get_pre_compute_app(CHAIN_TASK_ID, vec![], temp_dir.path().to_str().unwrap());
+        app.progress = ProgressReporter::new(
+            CHAIN_TASK_ID,
+            WorkerApiClient::new(&server.uri()),
+            "challenge".to_string(),
+            Duration::from_secs(60),
+        );
+        let start = Instant::now();
+        let saved_dataset =
+            app.save_plain_dataset_file(&get_served_dataset(&server, PLAIN_DATA_FILE));
+
+        assert!(saved_dataset.is_ok());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
     // endregion
 }
@@ -1,23 +1,60 @@
-use crate::compute::dataset::Dataset;
+use crate::compute::archive::{ArchiveFormat, ExtractionLimits};
+use crate::compute::cleanup::CleanupPolicy;
+use crate::compute::dataset::{Dataset, IpfsGatewayConfig, dataset_output_names};
 use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::input_file::InputFile;
+use crate::compute::key_provider::{
+    EnvKeyProvider, KeyProvider, SkippedKeyProvider, SmsKeyProvider,
+};
+use crate::compute::session_file::SessionFile;
+use crate::compute::source::SourceResolvers;
 use crate::compute::utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error};
+use crate::compute::utils::file_utils::SafeFilename;
+use crate::compute::utils::hash_utils::Checksum;
+use crate::compute::utils::quota_utils::DownloadQuota;
+use crate::compute::utils::retry_utils::RetryPolicy;
 use log::{error, info};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Number of datasets and input files processed concurrently when none is configured.
+pub const DEFAULT_PRE_COMPUTE_WORKERS: usize = 4;
 
 /// Represents parameters required for pre-compute tasks in a Trusted Execution Environment (TEE).
 ///
 /// This structure aggregates configuration parameters from environment variables and task context,
-/// providing a validated interface for subsequent computation phases.
-#[cfg_attr(test, derive(Debug))]
-#[derive(Clone, Default)]
+/// providing a validated interface for subsequent computation phases. Dataset keys are held as
+/// [`SecretString`], so that they are redacted when the arguments are logged.
+///
+/// [`SecretString`]: crate::compute::utils::secret_utils::SecretString
+#[derive(Clone, Debug, Default)]
 pub struct PreComputeArgs {
     pub output_dir: String,
     // Dataset related fields
     pub is_dataset_required: bool,
     // Input files
-    pub input_files: Vec<String>,
+    pub input_files: Vec<InputFile>,
+    // Whether input files keep their original filenames, listed in an inputs manifest
+    pub original_input_filenames: bool,
     // Bulk processing
     pub iexec_bulk_slice_size: usize,
     pub datasets: Vec<Dataset>,
+    // IPFS gateways used to resolve datasets stored on IPFS
+    pub ipfs_gateways: IpfsGatewayConfig,
+    // Retries of transient failures, shared by dataset and input file downloads
+    pub retry_policy: RetryPolicy,
+    // Maximum number of datasets or input files processed concurrently
+    pub workers: usize,
+    // Size limits of the downloads of datasets and input files
+    pub download_quota: DownloadQuota,
+    // Limits of the content extracted from dataset archives
+    pub extraction_limits: ExtractionLimits,
+    // Removal of the files written to the output folder when the pre-compute fails
+    pub cleanup_policy: CleanupPolicy,
+    // Canonical root of the local volume datasets and input files may be read from
+    pub local_source_root: Option<PathBuf>,
 }
 
 impl PreComputeArgs {
@@ -34,15 +71,54 @@ impl PreComputeArgs {
     ///   - `IEXEC_BULK_SLICE_SIZE`: Number of bulk datasets (0 means no bulk processing)
     /// - Required when `IEXEC_DATASET_REQUIRED` = "true":
     ///   - `IEXEC_DATASET_URL`: Encrypted dataset URL
-    ///   - `IEXEC_DATASET_KEY`: Base64-encoded dataset encryption key
+    ///   - `IEXEC_DATASET_KEY`: Base64-encoded dataset encryption key, unless retrieved from an SMS
     ///   - `IEXEC_DATASET_CHECKSUM`: Encrypted dataset checksum
     ///   - `IEXEC_DATASET_FILENAME`: Decrypted dataset filename
     /// - Required when `IEXEC_BULK_SLICE_SIZE` > 0 (for each dataset index from 1 to IEXEC_BULK_SLICE_SIZE):
     ///   - `IEXEC_DATASET_#_URL`: Dataset URL
     ///   - `IEXEC_DATASET_#_CHECKSUM`: Dataset checksum
     ///   - `IEXEC_DATASET_#_FILENAME`: Dataset filename
-    ///   - `IEXEC_DATASET_#_KEY`: Dataset decryption key
+    ///   - `IEXEC_DATASET_#_KEY`: Dataset decryption key, unless retrieved from an SMS
+    /// - Optional `IEXEC_PRE_COMPUTE_SMS_URL`: Base URL of a Secret Management Service the dataset
+    ///   keys are retrieved from instead of the variables above, see [`SmsKeyProvider`]
+    /// - Dataset filenames must be valid [`SafeFilename`]s, distinct from one another
+    /// - Optional `IEXEC_DATASET_EXTRACT` and `IEXEC_DATASET_#_EXTRACT`: Format of the decrypted
+    ///   dataset to extract, one of "zip", "tar", "tar.gz" or "tar.zst", or "auto" to detect it
+    ///   from the extension of the dataset filename, see [`ArchiveFormat`]
     /// - Input file URLs (`IEXEC_INPUT_FILE_URL_1`, `IEXEC_INPUT_FILE_URL_2`, etc.)
+    /// - Optional input file checksums (`IEXEC_INPUT_FILE_CHECKSUM_1`, etc.): SHA-256 or Keccak-256
+    ///   digest the content of the input file with the same index must match, see [`Checksum`].
+    ///   A URL listed several times must be given the same checksum each time.
+    /// - Optional `IEXEC_INPUT_FILES_ORIGINAL_NAMES`: Boolean ("true"/"false") to save input files
+    ///   under their original filenames instead of the SHA-256 hash of their URL
+    /// - Optional IPFS gateway configuration:
+    ///   - `IEXEC_IPFS_GATEWAYS`: Comma-separated list of gateway base URLs, tried in order
+    ///   - `IEXEC_IPFS_GATEWAY_TIMEOUT`: Maximum duration in seconds of a request to a single gateway
+    ///   - `IEXEC_IPFS_GATEWAYS_RACE`: Boolean ("true"/"false") to query all gateways concurrently
+    ///   - `IEXEC_IPFS_GATEWAYS_TRUSTLESS`: Boolean ("true"/"false") to fetch verifiable CAR responses
+    /// - Optional retry policy of the downloads, defaulting to the values of [`RetryPolicy::default`]:
+    ///   - `IEXEC_PRE_COMPUTE_MAX_ATTEMPTS`: Maximum number of attempts of each download
+    ///   - `IEXEC_PRE_COMPUTE_INITIAL_BACKOFF`: Delay in milliseconds before the first retry
+    ///   - `IEXEC_PRE_COMPUTE_MAX_BACKOFF`: Maximum delay in milliseconds between two attempts
+    /// - Optional `IEXEC_PRE_COMPUTE_WORKERS`: Maximum number of datasets or input files processed
+    ///   concurrently, defaults to [`DEFAULT_PRE_COMPUTE_WORKERS`]
+    /// - Optional download size limits, in bytes, not enforced when unset, see [`DownloadQuota`]:
+    ///   - `IEXEC_PRE_COMPUTE_MAX_FILE_SIZE`: Maximum size of a downloaded dataset or input file
+    ///   - `IEXEC_PRE_COMPUTE_MAX_TOTAL_SIZE`: Maximum size of all downloaded datasets and input files
+    /// - Optional limits of the content extracted from each dataset archive, see [`ExtractionLimits`]:
+    ///   - `IEXEC_PRE_COMPUTE_MAX_EXTRACTED_SIZE`: Maximum size in bytes of the extracted files
+    ///   - `IEXEC_PRE_COMPUTE_MAX_EXTRACTED_ENTRIES`: Maximum number of extracted files and directories
+    /// - Optional `IEXEC_PRE_COMPUTE_CLEANUP_ON_FAILURE`: Removal of the files written to the output
+    ///   folder when the pre-compute fails, one of "secure" (default), "delete" or "keep", see
+    ///   [`CleanupPolicy`]
+    ///
+    /// # Session file
+    ///
+    /// When `IEXEC_PRE_COMPUTE_SESSION_FILE` is set, the arguments are read from the JSON or TOML
+    /// session document at this path instead of the environment variables above, see
+    /// [`SessionFile`]. They are validated the same way and errors are collected the same way,
+    /// except that a session file which cannot be read or parsed is reported alone as
+    /// `PreComputeSessionFileReadFailed` or `PreComputeInvalidSessionFile`.
     ///
     /// # Returns
     ///
@@ -62,10 +138,46 @@ impl PreComputeArgs {
     /// args.output_dir = "/path/to/output".to_string(); // Set output_dir separately
     /// ```
     pub fn read_args() -> (PreComputeArgs, Vec<ReplicateStatusCause>) {
-        info!("Starting to read pre-compute arguments from environment variables");
+        Self::read(KeyRetrieval::Fetch)
+    }
+
+    /// Reads the arguments like [`PreComputeArgs::read_args`], without sending any request to
+    /// the SMS when `IEXEC_PRE_COMPUTE_SMS_URL` is set.
+    ///
+    /// The SMS key provider is still created, so that a missing task ID or challenge is
+    /// reported, but the keys of the datasets are left empty. This is used by the validate-only
+    /// mode, see [`validate_configuration`].
+    ///
+    /// [`validate_configuration`]: crate::compute::validation::validate_configuration
+    pub fn read_args_without_sms_keys() -> (PreComputeArgs, Vec<ReplicateStatusCause>) {
+        Self::read(KeyRetrieval::Skip)
+    }
+
+    fn read(key_retrieval: KeyRetrieval) -> (PreComputeArgs, Vec<ReplicateStatusCause>) {
+        match get_env_var_or_error(
+            TeeSessionEnvironmentVariable::IexecPreComputeSessionFile,
+            ReplicateStatusCause::PreComputeSessionFileReadFailed,
+        ) {
+            Ok(path) => match SessionFile::load(&path) {
+                Ok(session_file) => {
+                    Self::read_args_from(&ArgsSource::SessionFile(&session_file), key_retrieval)
+                }
+                Err(e) => (PreComputeArgs::default(), vec![e]),
+            },
+            Err(_) => {
+                info!("Starting to read pre-compute arguments from environment variables");
+                Self::read_args_from(&ArgsSource::Environment, key_retrieval)
+            }
+        }
+    }
+
+    fn read_args_from(
+        source: &ArgsSource,
+        key_retrieval: KeyRetrieval,
+    ) -> (PreComputeArgs, Vec<ReplicateStatusCause>) {
         let mut exit_causes: Vec<ReplicateStatusCause> = Vec::new();
 
-        let is_dataset_required = match get_env_var_or_error(
+        let is_dataset_required = match source.get(
             TeeSessionEnvironmentVariable::IsDatasetRequired,
             ReplicateStatusCause::PreComputeIsDatasetRequiredMissing,
         ) {
@@ -84,7 +196,7 @@ impl PreComputeArgs {
             }
         };
 
-        let iexec_bulk_slice_size = match get_env_var_or_error(
+        let iexec_bulk_slice_size = match source.get(
             TeeSessionEnvironmentVariable::IexecBulkSliceSize,
             ReplicateStatusCause::PreComputeFailedUnknownIssue,
         ) {
@@ -96,12 +208,13 @@ impl PreComputeArgs {
             Err(_) => 0,
         }; // TODO: replace with a more specific error
 
+        let key_provider = read_key_provider(source, key_retrieval, &mut exit_causes);
         let mut datasets = Vec::with_capacity(iexec_bulk_slice_size + 1);
 
         // Read datasets
         let start_index = if is_dataset_required { 0 } else { 1 };
         for i in start_index..=iexec_bulk_slice_size {
-            let filename = match get_env_var_or_error(
+            let filename = match source.get(
                 TeeSessionEnvironmentVariable::IexecDatasetFilename(i),
                 ReplicateStatusCause::PreComputeDatasetFilenameMissing(format!("dataset_{i}")),
             ) {
@@ -112,10 +225,61 @@ impl PreComputeArgs {
                     continue;
                 }
             };
+            let filename = match SafeFilename::from_str(&filename) {
+                Ok(filename) => filename,
+                Err(e) => {
+                    error!("Invalid dataset {i} filename [filename:{filename:?}, error:{e}]");
+                    exit_causes.push(ReplicateStatusCause::PreComputeInvalidDatasetFilename(
+                        format!("dataset_{i}"),
+                    ));
+                    continue;
+                }
+            };
+            let extract = match source.get(
+                TeeSessionEnvironmentVariable::IexecDatasetExtract(i),
+                ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration,
+            ) {
+                Ok(s) => {
+                    let format = if s.eq_ignore_ascii_case("auto") {
+                        ArchiveFormat::from_filename(filename.as_str())
+                    } else {
+                        ArchiveFormat::from_str(&s).ok()
+                    };
+                    match format {
+                        Some(format) => Some(format),
+                        None => {
+                            error!(
+                                "Invalid dataset {i} archive format [filename:{filename}, format:{s}]"
+                            );
+                            exit_causes.push(
+                                ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration,
+                            );
+                            continue;
+                        }
+                    }
+                }
+                Err(_) => None,
+            };
+
+            // Datasets must not overwrite each other, including their partial and extracted files.
+            let output_names = dataset_output_names(filename.as_str(), extract);
+            if datasets
+                .iter()
+                .flat_map(|dataset: &Dataset| {
+                    dataset_output_names(dataset.filename.as_str(), dataset.extract)
+                })
+                .any(|name| output_names.contains(&name))
+            {
+                error!("Duplicate dataset {i} filename [filename:{filename}]");
+                exit_causes.push(ReplicateStatusCause::PreComputeDuplicateDatasetFilename(
+                    filename.to_string(),
+                ));
+                continue;
+            }
 
-            let url = match get_env_var_or_error(
+            let url = match source.get(
                 TeeSessionEnvironmentVariable::IexecDatasetUrl(i),
-                ReplicateStatusCause::PreComputeDatasetUrlMissing(filename.clone()),
+                ReplicateStatusCause::PreComputeDatasetUrlMissing(filename.to_string()),
             ) {
                 Ok(url) => url,
                 Err(e) => {
@@ -125,9 +289,9 @@ impl PreComputeArgs {
                 }
             };
 
-            let checksum = match get_env_var_or_error(
+            let checksum = match source.get(
                 TeeSessionEnvironmentVariable::IexecDatasetChecksum(i),
-                ReplicateStatusCause::PreComputeDatasetChecksumMissing(filename.clone()),
+                ReplicateStatusCause::PreComputeDatasetChecksumMissing(filename.to_string()),
             ) {
                 Ok(checksum) => checksum,
                 Err(e) => {
@@ -137,10 +301,11 @@ impl PreComputeArgs {
                 }
             };
 
-            let key = match get_env_var_or_error(
-                TeeSessionEnvironmentVariable::IexecDatasetKey(i),
-                ReplicateStatusCause::PreComputeDatasetKeyMissing(filename.clone()),
-            ) {
+            // A provider which cannot be created is already reported.
+            let Some(key_provider) = &key_provider else {
+                continue;
+            };
+            let key = match key_provider.dataset_key(i, filename.as_str()) {
                 Ok(key) => key,
                 Err(e) => {
                     error!("Failed to read dataset {i} key: {e:?}");
@@ -149,10 +314,16 @@ impl PreComputeArgs {
                 }
             };
 
-            datasets.push(Dataset::new(url, checksum, filename, key));
+            datasets.push(Dataset {
+                url,
+                checksum,
+                filename,
+                key,
+                extract,
+            });
         }
 
-        let input_files_nb = match get_env_var_or_error(
+        let input_files_nb = match source.get(
             TeeSessionEnvironmentVariable::IexecInputFilesNumber,
             ReplicateStatusCause::PreComputeInputFilesNumberMissing,
         ) {
@@ -171,20 +342,116 @@ impl PreComputeArgs {
             }
         };
 
-        let mut input_files: Vec<String> = Vec::new();
+        let mut input_files: Vec<InputFile> = Vec::new();
         for i in 1..=input_files_nb {
-            match get_env_var_or_error(
+            let url = match source.get(
                 TeeSessionEnvironmentVariable::IexecInputFileUrlPrefix(i),
                 ReplicateStatusCause::PreComputeAtLeastOneInputFileUrlMissing(i),
             ) {
-                Ok(url) => input_files.push(url),
+                Ok(url) => url,
                 Err(e) => {
                     error!("Failed to read input file {i} URL: {e:?}");
-                    exit_causes.push(e)
+                    exit_causes.push(e);
+                    continue;
                 }
+            };
+            let checksum = match source.get(
+                TeeSessionEnvironmentVariable::IexecInputFileChecksumPrefix(i),
+                ReplicateStatusCause::PreComputeInputFileInvalidChecksum(url.clone()),
+            ) {
+                Ok(s) => match Checksum::from_str(&s) {
+                    Ok(checksum) => Some(checksum),
+                    Err(e) => {
+                        error!("Invalid input file {i} checksum [url:{url}, error:{e}]");
+                        exit_causes.push(ReplicateStatusCause::PreComputeInputFileInvalidChecksum(
+                            url,
+                        ));
+                        continue;
+                    }
+                },
+                Err(_) => None,
+            };
+            let input_file = InputFile::new(url, checksum);
+            if input_files
+                .iter()
+                .any(|other| other.url == input_file.url && other != &input_file)
+            {
+                error!(
+                    "Conflicting checksums for input file {i} [url:{}]",
+                    input_file.url
+                );
+                exit_causes.push(ReplicateStatusCause::PreComputeInputFileInvalidChecksum(
+                    input_file.url,
+                ));
+                continue;
             }
+            input_files.push(input_file);
         }
 
+        let original_input_filenames = match source.get(
+            TeeSessionEnvironmentVariable::IexecInputFilesOriginalNames,
+            ReplicateStatusCause::PreComputeInvalidInputFileNamingConfiguration,
+        ) {
+            Ok(s) => match s.to_lowercase().parse::<bool>() {
+                Ok(value) => value,
+                Err(_) => {
+                    error!("Invalid boolean format for IEXEC_INPUT_FILES_ORIGINAL_NAMES: {s}");
+                    exit_causes
+                        .push(ReplicateStatusCause::PreComputeInvalidInputFileNamingConfiguration);
+                    false
+                }
+            },
+            Err(_) => false,
+        };
+
+        let ipfs_gateways = read_ipfs_gateway_config(source, &mut exit_causes);
+        let retry_policy = read_retry_policy(source, &mut exit_causes);
+
+        let workers = match source.get(
+            TeeSessionEnvironmentVariable::IexecPreComputeWorkers,
+            ReplicateStatusCause::PreComputeInvalidWorkersNumber,
+        ) {
+            Ok(s) => match s.parse::<usize>() {
+                Ok(workers) if workers > 0 => workers,
+                _ => {
+                    error!("Invalid numeric format for IEXEC_PRE_COMPUTE_WORKERS: {s}");
+                    exit_causes.push(ReplicateStatusCause::PreComputeInvalidWorkersNumber);
+                    DEFAULT_PRE_COMPUTE_WORKERS
+                }
+            },
+            Err(_) => DEFAULT_PRE_COMPUTE_WORKERS,
+        };
+
+        let download_quota = read_download_quota(source, &mut exit_causes);
+        let extraction_limits = read_extraction_limits(source, &mut exit_causes);
+
+        let cleanup_policy = match source.get(
+            TeeSessionEnvironmentVariable::IexecPreComputeCleanupOnFailure,
+            ReplicateStatusCause::PreComputeInvalidCleanupPolicy,
+        ) {
+            Ok(s) => CleanupPolicy::from_str(&s).unwrap_or_else(|e| {
+                error!("Invalid value for IEXEC_PRE_COMPUTE_CLEANUP_ON_FAILURE: {e}");
+                exit_causes.push(ReplicateStatusCause::PreComputeInvalidCleanupPolicy);
+                CleanupPolicy::default()
+            }),
+            Err(_) => CleanupPolicy::default(),
+        };
+
+        let local_source_root = match source.get(
+            TeeSessionEnvironmentVariable::IexecPreComputeLocalSourceRoot,
+            ReplicateStatusCause::PreComputeInvalidLocalSourceRoot,
+        ) {
+            Ok(s) => match fs::canonicalize(&s) {
+                Ok(root) if root.is_dir() => Some(root),
+                _ => {
+                    error!("Invalid directory for IEXEC_PRE_COMPUTE_LOCAL_SOURCE_ROOT: {s}");
+                    exit_causes.push(ReplicateStatusCause::PreComputeInvalidLocalSourceRoot);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
         if !exit_causes.is_empty() {
             error!(
                 "Encountered {} error(s) while reading pre-compute arguments",
@@ -199,20 +466,313 @@ impl PreComputeArgs {
                 output_dir: String::new(),
                 is_dataset_required,
                 input_files,
+                original_input_filenames,
                 iexec_bulk_slice_size,
                 datasets,
+                ipfs_gateways,
+                retry_policy,
+                workers,
+                download_quota,
+                extraction_limits,
+                cleanup_policy,
+                local_source_root,
             },
             exit_causes,
         )
     }
 }
 
+impl PreComputeArgs {
+    /// Returns the resolvers opening the locations of the datasets and input files: HTTP(S) URLs,
+    /// IPFS multi-addresses through the configured gateways and, when a local source root is
+    /// configured, local files below it.
+    pub fn source_resolvers(&self) -> SourceResolvers {
+        SourceResolvers::new(self.ipfs_gateways.clone(), self.local_source_root.clone())
+    }
+}
+
+/// Source of the values of the session variables read by [`PreComputeArgs::read_args`].
+enum ArgsSource<'a> {
+    Environment,
+    SessionFile(&'a SessionFile),
+}
+
+impl ArgsSource<'_> {
+    /// Returns the value of `env_var`, or `status_cause_if_missing` if it is missing or empty,
+    /// like [`get_env_var_or_error`].
+    fn get(
+        &self,
+        env_var: TeeSessionEnvironmentVariable,
+        status_cause_if_missing: ReplicateStatusCause,
+    ) -> Result<String, ReplicateStatusCause> {
+        match self {
+            ArgsSource::Environment => get_env_var_or_error(env_var, status_cause_if_missing),
+            ArgsSource::SessionFile(session_file) => session_file
+                .value(&env_var)
+                .filter(|value| !value.is_empty())
+                .ok_or(status_cause_if_missing),
+        }
+    }
+
+    /// Returns the entries of the comma-separated list `env_var`, or `status_cause_if_missing`
+    /// if it is missing or empty. A list of the session file is returned as is, so an empty list
+    /// is returned as such rather than as a missing value.
+    fn get_list(
+        &self,
+        env_var: TeeSessionEnvironmentVariable,
+        status_cause_if_missing: ReplicateStatusCause,
+    ) -> Result<Vec<String>, ReplicateStatusCause> {
+        match self {
+            ArgsSource::Environment => get_env_var_or_error(env_var, status_cause_if_missing)
+                .map(|s| s.split(',').map(String::from).collect()),
+            ArgsSource::SessionFile(session_file) => {
+                session_file.list(&env_var).ok_or(status_cause_if_missing)
+            }
+        }
+    }
+}
+
+/// Whether the dataset keys held by an SMS are retrieved while reading the arguments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum KeyRetrieval {
+    Fetch,
+    Skip,
+}
+
+/// Returns the [`KeyProvider`] of the dataset keys: an [`SmsKeyProvider`] when
+/// `IEXEC_PRE_COMPUTE_SMS_URL` is set, or the session variables otherwise.
+///
+/// With [`KeyRetrieval::Skip`], the [`SmsKeyProvider`] is created but replaced by a provider of
+/// empty keys, so that the SMS is never requested.
+///
+/// A failure to create the [`SmsKeyProvider`] is reported in `exit_causes` and `None` is returned.
+fn read_key_provider<'a>(
+    source: &ArgsSource<'a>,
+    key_retrieval: KeyRetrieval,
+    exit_causes: &mut Vec<ReplicateStatusCause>,
+) -> Option<Box<dyn KeyProvider + 'a>> {
+    match source.get(
+        TeeSessionEnvironmentVariable::IexecPreComputeSmsUrl,
+        ReplicateStatusCause::PreComputeFailedUnknownIssue,
+    ) {
+        Ok(sms_url) => match SmsKeyProvider::from_env(&sms_url) {
+            Ok(_) if key_retrieval == KeyRetrieval::Skip => {
+                info!("Skipping retrieval of dataset keys from SMS [url:{sms_url}]");
+                Some(Box::new(SkippedKeyProvider))
+            }
+            Ok(provider) => {
+                info!("Retrieving dataset keys from SMS [url:{sms_url}]");
+                Some(Box::new(provider))
+            }
+            Err(e) => {
+                error!("Failed to create SMS key provider [url:{sms_url}, error:{e:?}]");
+                exit_causes.push(e);
+                None
+            }
+        },
+        Err(_) => match source {
+            ArgsSource::Environment => Some(Box::new(EnvKeyProvider)),
+            ArgsSource::SessionFile(session_file) => Some(Box::new(*session_file)),
+        },
+    }
+}
+
+/// Reads the optional IPFS gateway configuration, falling back to defaults for unset variables.
+///
+/// Invalid values are reported as [`ReplicateStatusCause::PreComputeInvalidIpfsGatewayConfiguration`]
+/// in `exit_causes`, and the default value is used instead.
+fn read_ipfs_gateway_config(
+    source: &ArgsSource,
+    exit_causes: &mut Vec<ReplicateStatusCause>,
+) -> IpfsGatewayConfig {
+    let mut config = IpfsGatewayConfig::default();
+    let invalid = ReplicateStatusCause::PreComputeInvalidIpfsGatewayConfiguration;
+
+    if let Ok(entries) = source.get_list(
+        TeeSessionEnvironmentVariable::IexecIpfsGateways,
+        invalid.clone(),
+    ) {
+        let gateways: Vec<String> = entries
+            .iter()
+            .map(|gateway| gateway.trim().trim_end_matches('/'))
+            .filter(|gateway| !gateway.is_empty())
+            .map(String::from)
+            .collect();
+        if gateways.is_empty() {
+            error!("No gateway found in IEXEC_IPFS_GATEWAYS: {entries:?}");
+            exit_causes.push(invalid.clone());
+        } else {
+            config.gateways = gateways;
+        }
+    }
+
+    if let Ok(s) = source.get(
+        TeeSessionEnvironmentVariable::IexecIpfsGatewayTimeout,
+        invalid.clone(),
+    ) {
+        match s.parse::<u64>() {
+            Ok(seconds) if seconds > 0 => config.timeout = Duration::from_secs(seconds),
+            _ => {
+                error!("Invalid numeric format for IEXEC_IPFS_GATEWAY_TIMEOUT: {s}");
+                exit_causes.push(invalid.clone());
+            }
+        }
+    }
+
+    for (env_var, flag) in [
+        (
+            TeeSessionEnvironmentVariable::IexecIpfsGatewaysRace,
+            &mut config.race,
+        ),
+        (
+            TeeSessionEnvironmentVariable::IexecIpfsGatewaysTrustless,
+            &mut config.trustless,
+        ),
+    ] {
+        let name = env_var.name();
+        if let Ok(s) = source.get(env_var, invalid.clone()) {
+            match s.to_lowercase().parse::<bool>() {
+                Ok(value) => *flag = value,
+                Err(_) => {
+                    error!("Invalid boolean format for {name}: {s}");
+                    exit_causes.push(invalid.clone());
+                }
+            }
+        }
+    }
+
+    config
+}
+
+/// Reads the optional download size limits, leaving unset limits unenforced.
+///
+/// Values which are not a positive number of bytes are reported as
+/// [`ReplicateStatusCause::PreComputeInvalidDownloadQuotaConfiguration`] and ignored.
+fn read_download_quota(
+    source: &ArgsSource,
+    exit_causes: &mut Vec<ReplicateStatusCause>,
+) -> DownloadQuota {
+    let mut read_limit = |env_var: TeeSessionEnvironmentVariable| {
+        let name = env_var.name();
+        let s = source
+            .get(
+                env_var,
+                ReplicateStatusCause::PreComputeInvalidDownloadQuotaConfiguration,
+            )
+            .ok()?;
+        match s.parse::<u64>() {
+            Ok(limit) if limit > 0 => Some(limit),
+            _ => {
+                error!("Invalid numeric format for {name}: {s}");
+                exit_causes.push(ReplicateStatusCause::PreComputeInvalidDownloadQuotaConfiguration);
+                None
+            }
+        }
+    };
+    let max_file_size = read_limit(TeeSessionEnvironmentVariable::IexecPreComputeMaxFileSize);
+    let max_total_size = read_limit(TeeSessionEnvironmentVariable::IexecPreComputeMaxTotalSize);
+    DownloadQuota::new(max_file_size, max_total_size)
+}
+
+/// Reads the optional limits of the content extracted from dataset archives, falling back to
+/// the defaults of [`ExtractionLimits`] for unset variables.
+///
+/// Values which are not a positive number are reported as
+/// [`ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration`] and ignored.
+fn read_extraction_limits(
+    source: &ArgsSource,
+    exit_causes: &mut Vec<ReplicateStatusCause>,
+) -> ExtractionLimits {
+    let mut limits = ExtractionLimits::default();
+    for (env_var, limit) in [
+        (
+            TeeSessionEnvironmentVariable::IexecPreComputeMaxExtractedSize,
+            &mut limits.max_size,
+        ),
+        (
+            TeeSessionEnvironmentVariable::IexecPreComputeMaxExtractedEntries,
+            &mut limits.max_entries,
+        ),
+    ] {
+        let name = env_var.name();
+        if let Ok(s) = source.get(
+            env_var,
+            ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration,
+        ) {
+            match s.parse::<u64>() {
+                Ok(value) if value > 0 => *limit = value,
+                _ => {
+                    error!("Invalid numeric format for {name}: {s}");
+                    exit_causes.push(
+                        ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration,
+                    );
+                }
+            }
+        }
+    }
+    limits
+}
+
+/// Reads the optional retry policy of the downloads, falling back to the defaults of
+/// [`RetryPolicy`] for unset variables.
+///
+/// Values which are not a positive number are reported as
+/// [`ReplicateStatusCause::PreComputeInvalidRetryPolicy`] and ignored. So is an initial backoff
+/// longer than the maximum backoff, in which case the default policy is used.
+fn read_retry_policy(
+    source: &ArgsSource,
+    exit_causes: &mut Vec<ReplicateStatusCause>,
+) -> RetryPolicy {
+    let mut read_value = |env_var: TeeSessionEnvironmentVariable| {
+        let name = env_var.name();
+        let s = source
+            .get(env_var, ReplicateStatusCause::PreComputeInvalidRetryPolicy)
+            .ok()?;
+        match s.parse::<u64>() {
+            Ok(value) if value > 0 => Some(value),
+            _ => {
+                error!("Invalid numeric format for {name}: {s}");
+                exit_causes.push(ReplicateStatusCause::PreComputeInvalidRetryPolicy);
+                None
+            }
+        }
+    };
+    let mut policy = RetryPolicy::default();
+    if let Some(max_attempts) =
+        read_value(TeeSessionEnvironmentVariable::IexecPreComputeMaxAttempts)
+    {
+        policy.max_attempts = u32::try_from(max_attempts).unwrap_or(u32::MAX);
+    }
+    if let Some(millis) = read_value(TeeSessionEnvironmentVariable::IexecPreComputeInitialBackoff) {
+        policy.initial_backoff = Duration::from_millis(millis);
+    }
+    if let Some(millis) = read_value(TeeSessionEnvironmentVariable::IexecPreComputeMaxBackoff) {
+        policy.max_backoff = Duration::from_millis(millis);
+    }
+    if policy.initial_backoff > policy.max_backoff {
+        error!(
+            "Initial backoff is longer than maximum backoff [initialBackoff:{}ms, maxBackoff:{}ms]",
+            policy.initial_backoff.as_millis(),
+            policy.max_backoff.as_millis()
+        );
+        exit_causes.push(ReplicateStatusCause::PreComputeInvalidRetryPolicy);
+        return RetryPolicy::default();
+    }
+    policy
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::compute::errors::ReplicateStatusCause;
     use crate::compute::utils::env_utils::TeeSessionEnvironmentVariable::*;
+    use crate::compute::utils::hash_utils::HashAlgorithm;
     use std::collections::HashMap;
+    use std::fs;
+    use tempfile::TempDir;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     const DATASET_URL: &str = "https://dataset.url";
     const DATASET_KEY: &str = "datasetKey123";
@@ -288,7 +848,7 @@ mod tests {
             assert_eq!(args.output_dir, "");
             assert!(!args.is_dataset_required);
             assert_eq!(args.input_files.len(), 1);
-            assert_eq!(args.input_files[0], "https://input-1.txt");
+            assert_eq!(args.input_files[0].url, "https://input-1.txt");
             assert_eq!(args.iexec_bulk_slice_size, 0);
             assert_eq!(args.datasets.len(), 0);
         });
@@ -310,9 +870,12 @@ mod tests {
             assert_eq!(args.output_dir, "");
             assert!(args.is_dataset_required);
             assert_eq!(args.datasets[0].url, DATASET_URL.to_string());
-            assert_eq!(args.datasets[0].key, DATASET_KEY.to_string());
+            assert_eq!(args.datasets[0].key.expose_secret(), DATASET_KEY);
             assert_eq!(args.datasets[0].checksum, DATASET_CHECKSUM.to_string());
-            assert_eq!(args.datasets[0].filename, DATASET_FILENAME.to_string());
+            assert_eq!(
+                args.datasets[0].filename.as_str(),
+                DATASET_FILENAME.to_string()
+            );
             assert_eq!(args.input_files.len(), 0);
             assert_eq!(args.iexec_bulk_slice_size, 0);
             assert_eq!(args.datasets.len(), 1);
@@ -336,9 +899,9 @@ mod tests {
             assert_eq!(args.output_dir, "");
             assert!(!args.is_dataset_required);
             assert_eq!(args.input_files.len(), 3);
-            assert_eq!(args.input_files[0], "https://input-1.txt");
-            assert_eq!(args.input_files[1], "https://input-2.txt");
-            assert_eq!(args.input_files[2], "https://input-3.txt");
+            assert_eq!(args.input_files[0].url, "https://input-1.txt");
+            assert_eq!(args.input_files[1].url, "https://input-2.txt");
+            assert_eq!(args.input_files[2].url, "https://input-3.txt");
             assert_eq!(args.iexec_bulk_slice_size, 0);
             assert_eq!(args.datasets.len(), 0);
         });
@@ -420,20 +983,20 @@ mod tests {
             // Check first bulk dataset
             assert_eq!(args.datasets[0].url, "https://bulk-dataset-1.bin");
             assert_eq!(args.datasets[0].checksum, "0x123checksum");
-            assert_eq!(args.datasets[0].filename, "bulk-dataset-1.txt");
-            assert_eq!(args.datasets[0].key, "bulkKey123");
+            assert_eq!(args.datasets[0].filename.as_str(), "bulk-dataset-1.txt");
+            assert_eq!(args.datasets[0].key.expose_secret(), "bulkKey123");
 
             // Check second bulk dataset
             assert_eq!(args.datasets[1].url, "https://bulk-dataset-2.bin");
             assert_eq!(args.datasets[1].checksum, "0x223checksum");
-            assert_eq!(args.datasets[1].filename, "bulk-dataset-2.txt");
-            assert_eq!(args.datasets[1].key, "bulkKey223");
+            assert_eq!(args.datasets[1].filename.as_str(), "bulk-dataset-2.txt");
+            assert_eq!(args.datasets[1].key.expose_secret(), "bulkKey223");
 
             // Check third bulk dataset
             assert_eq!(args.datasets[2].url, "https://bulk-dataset-3.bin");
             assert_eq!(args.datasets[2].checksum, "0x323checksum");
-            assert_eq!(args.datasets[2].filename, "bulk-dataset-3.txt");
-            assert_eq!(args.datasets[2].key, "bulkKey323");
+            assert_eq!(args.datasets[2].filename.as_str(), "bulk-dataset-3.txt");
+            assert_eq!(args.datasets[2].key.expose_secret(), "bulkKey323");
         });
     }
 
@@ -459,8 +1022,8 @@ mod tests {
             // Check regular dataset (first in list)
             assert_eq!(args.datasets[0].url, DATASET_URL);
             assert_eq!(args.datasets[0].checksum, DATASET_CHECKSUM);
-            assert_eq!(args.datasets[0].filename, DATASET_FILENAME);
-            assert_eq!(args.datasets[0].key, DATASET_KEY);
+            assert_eq!(args.datasets[0].filename.as_str(), DATASET_FILENAME);
+            assert_eq!(args.datasets[0].key.expose_secret(), DATASET_KEY);
 
             // Check bulk datasets
             assert_eq!(args.datasets[1].url, "https://bulk-dataset-1.bin");
@@ -758,9 +1321,9 @@ mod tests {
 
             // Should successfully load the other three input files
             assert_eq!(result.0.input_files.len(), 3);
-            assert_eq!(result.0.input_files[0], "https://input-1.txt");
-            assert_eq!(result.0.input_files[1], "https://input-3.txt");
-            assert_eq!(result.0.input_files[2], "https://input-5.txt");
+            assert_eq!(result.0.input_files[0].url, "https://input-1.txt");
+            assert_eq!(result.0.input_files[1].url, "https://input-3.txt");
+            assert_eq!(result.0.input_files[2].url, "https://input-5.txt");
         });
     }
 
@@ -940,4 +1503,848 @@ mod tests {
         });
     }
     // endregion
+
+    // region IPFS gateway configuration
+    #[test]
+    fn read_args_uses_default_ipfs_gateways_when_not_configured() {
+        let env_vars = setup_basic_env_vars();
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(!errors.iter().any(|e| {
+                *e == ReplicateStatusCause::PreComputeInvalidIpfsGatewayConfiguration
+            }));
+            assert_eq!(args.ipfs_gateways, IpfsGatewayConfig::default());
+        });
+    }
+
+    #[test]
+    fn read_args_succeeds_when_ipfs_gateways_configured() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(
+            IexecIpfsGateways.name(),
+            " https://gateway-1.io/ ,,https://gateway-2.io".to_string(),
+        );
+        env_vars.insert(IexecIpfsGatewayTimeout.name(), "5".to_string());
+        env_vars.insert(IexecIpfsGatewaysRace.name(), "TRUE".to_string());
+        env_vars.insert(IexecIpfsGatewaysTrustless.name(), "true".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(errors.is_empty());
+            assert_eq!(
+                args.ipfs_gateways,
+                IpfsGatewayConfig {
+                    gateways: vec![
+                        "https://gateway-1.io".to_string(),
+                        "https://gateway-2.io".to_string()
+                    ],
+                    timeout: Duration::from_secs(5),
+                    race: true,
+                    trustless: true,
+                }
+            );
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_ipfs_gateway_configuration_invalid() {
+        let invalid_values = vec![
+            (IexecIpfsGateways.name(), " , ".to_string()),
+            (IexecIpfsGatewayTimeout.name(), "0".to_string()),
+            (IexecIpfsGatewayTimeout.name(), "ten".to_string()),
+            (IexecIpfsGatewaysRace.name(), "maybe".to_string()),
+            (IexecIpfsGatewaysTrustless.name(), "yes".to_string()),
+        ];
+
+        for (name, value) in invalid_values {
+            let mut env_vars = setup_basic_env_vars();
+            env_vars.extend(setup_dataset_env_vars());
+            env_vars.insert(name.clone(), value.clone());
+
+            temp_env::with_vars(to_temp_env_vars(env_vars), || {
+                let (args, errors) = PreComputeArgs::read_args();
+
+                assert_eq!(
+                    errors,
+                    vec![ReplicateStatusCause::PreComputeInvalidIpfsGatewayConfiguration],
+                    "{name}={value}"
+                );
+                assert_eq!(args.ipfs_gateways, IpfsGatewayConfig::default());
+            });
+        }
+    }
+    // endregion
+
+    // region input file checksums
+    const INPUT_FILE_SHA256: &str =
+        "0xb33845db05fb0822f1f1e3677cc6787b8a1a7a21f3c12f9e97c70cb596222218";
+
+    #[test]
+    fn read_args_reads_optional_input_file_checksums() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(2));
+        env_vars.insert(
+            IexecInputFileChecksumPrefix(2).name(),
+            format!("keccak256:{INPUT_FILE_SHA256}"),
+        );
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert!(exit_causes.is_empty());
+            assert_eq!(args.input_files[0].checksum, None);
+            assert_eq!(
+                args.input_files[1].checksum,
+                Some(Checksum {
+                    algorithm: HashAlgorithm::Keccak256,
+                    digest: INPUT_FILE_SHA256.to_string(),
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_input_file_checksum_invalid() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(2));
+        env_vars.insert(IexecInputFileChecksumPrefix(1).name(), "0x1234".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInputFileInvalidChecksum(
+                    "https://input-1.txt".to_string()
+                )]
+            );
+            assert_eq!(args.input_files.len(), 1);
+            assert_eq!(args.input_files[0].url, "https://input-2.txt");
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_same_input_file_has_conflicting_checksums() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(2));
+        env_vars.insert(
+            IexecInputFileUrlPrefix(2).name(),
+            "https://input-1.txt".to_string(),
+        );
+        env_vars.insert(
+            IexecInputFileChecksumPrefix(2).name(),
+            INPUT_FILE_SHA256.to_string(),
+        );
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInputFileInvalidChecksum(
+                    "https://input-1.txt".to_string()
+                )]
+            );
+            assert_eq!(args.input_files.len(), 1);
+        });
+    }
+
+    #[test]
+    fn read_args_reads_original_input_filenames_flag() {
+        for (value, expected, expected_causes) in [
+            (None, false, vec![]),
+            (Some("TRUE"), true, vec![]),
+            (Some("false"), false, vec![]),
+            (
+                Some("maybe"),
+                false,
+                vec![ReplicateStatusCause::PreComputeInvalidInputFileNamingConfiguration],
+            ),
+        ] {
+            let mut env_vars = setup_basic_env_vars();
+            env_vars.extend(setup_dataset_env_vars());
+            env_vars.extend(setup_input_files_env_vars(1));
+            if let Some(value) = value {
+                env_vars.insert(IexecInputFilesOriginalNames.name(), value.to_string());
+            }
+
+            temp_env::with_vars(to_temp_env_vars(env_vars), || {
+                let (args, exit_causes) = PreComputeArgs::read_args();
+
+                assert_eq!(args.original_input_filenames, expected, "{value:?}");
+                assert_eq!(exit_causes, expected_causes, "{value:?}");
+            });
+        }
+    }
+    // endregion
+
+    // region workers
+    #[test]
+    fn read_args_uses_default_workers_when_not_configured() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(errors.is_empty());
+            assert_eq!(args.workers, DEFAULT_PRE_COMPUTE_WORKERS);
+        });
+    }
+
+    #[test]
+    fn read_args_succeeds_when_workers_configured() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(IexecPreComputeWorkers.name(), "16".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(errors.is_empty());
+            assert_eq!(args.workers, 16);
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_workers_invalid() {
+        for value in ["0", "-1", "many"] {
+            let mut env_vars = setup_basic_env_vars();
+            env_vars.extend(setup_dataset_env_vars());
+            env_vars.insert(IexecPreComputeWorkers.name(), value.to_string());
+
+            temp_env::with_vars(to_temp_env_vars(env_vars), || {
+                let (args, errors) = PreComputeArgs::read_args();
+
+                assert_eq!(
+                    errors,
+                    vec![ReplicateStatusCause::PreComputeInvalidWorkersNumber]
+                );
+                assert_eq!(args.workers, DEFAULT_PRE_COMPUTE_WORKERS);
+            });
+        }
+    }
+    // endregion
+
+    // region download quota
+    #[test]
+    fn read_args_reads_download_size_limits() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.insert(IexecPreComputeMaxFileSize.name(), "1024".to_string());
+        env_vars.insert(IexecPreComputeMaxTotalSize.name(), "4096".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert!(exit_causes.is_empty());
+            assert_eq!(args.download_quota.max_file_size, Some(1024));
+            assert_eq!(args.download_quota.max_total_size, Some(4096));
+        });
+    }
+
+    #[test]
+    fn read_args_does_not_limit_downloads_by_default() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert!(exit_causes.is_empty());
+            assert_eq!(args.download_quota.max_file_size, None);
+            assert_eq!(args.download_quota.max_total_size, None);
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_download_size_limits_invalid() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.insert(IexecPreComputeMaxFileSize.name(), "0".to_string());
+        env_vars.insert(IexecPreComputeMaxTotalSize.name(), "1GB".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidDownloadQuotaConfiguration; 2]
+            );
+            assert_eq!(args.download_quota.max_file_size, None);
+            assert_eq!(args.download_quota.max_total_size, None);
+        });
+    }
+    // endregion
+
+    // region dataset filenames
+    #[test]
+    fn read_args_fails_when_bulk_dataset_filename_unsafe() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.insert(IsDatasetRequired.name(), "false".to_string());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.extend(setup_bulk_dataset_env_vars(2));
+        env_vars.insert(IexecDatasetFilename(1).name(), "../evil.txt".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidDatasetFilename(
+                    "dataset_1".to_string()
+                )]
+            );
+            assert_eq!(args.datasets.len(), 1);
+            assert_eq!(args.datasets[0].filename.as_str(), "bulk-dataset-2.txt");
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_dataset_filename_absolute() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.insert(IexecDatasetFilename(0).name(), "/etc/passwd".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (_, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidDatasetFilename(
+                    "dataset_0".to_string()
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_bulk_dataset_filenames_duplicated() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.extend(setup_bulk_dataset_env_vars(2));
+        env_vars.insert(IexecDatasetFilename(2).name(), DATASET_FILENAME.to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeDuplicateDatasetFilename(
+                    DATASET_FILENAME.to_string()
+                )]
+            );
+            assert_eq!(args.datasets.len(), 2);
+            assert_eq!(args.datasets[0].filename.as_str(), DATASET_FILENAME);
+            assert_eq!(args.datasets[1].filename.as_str(), "bulk-dataset-1.txt");
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_bulk_dataset_filename_is_partial_file_of_another() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.extend(setup_bulk_dataset_env_vars(1));
+        env_vars.insert(
+            IexecDatasetFilename(1).name(),
+            format!("{DATASET_FILENAME}.part"),
+        );
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeDuplicateDatasetFilename(
+                    format!("{DATASET_FILENAME}.part")
+                )]
+            );
+            assert_eq!(args.datasets.len(), 1);
+            assert_eq!(args.datasets[0].filename.as_str(), DATASET_FILENAME);
+        });
+    }
+    // endregion
+
+    // region archive extraction
+    #[test]
+    fn read_args_reads_dataset_archive_formats() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.extend(setup_bulk_dataset_env_vars(2));
+        env_vars.insert(IexecDatasetExtract(0).name(), "tar.gz".to_string());
+        env_vars.insert(
+            IexecDatasetFilename(1).name(),
+            "bulk-dataset-1.ZIP".to_string(),
+        );
+        env_vars.insert(IexecDatasetExtract(1).name(), "auto".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert!(exit_causes.is_empty());
+            assert_eq!(args.datasets[0].extract, Some(ArchiveFormat::TarGz));
+            assert_eq!(args.datasets[1].extract, Some(ArchiveFormat::Zip));
+            assert_eq!(args.datasets[2].extract, None);
+            assert_eq!(args.extraction_limits, ExtractionLimits::default());
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_dataset_archive_format_invalid() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.extend(setup_bulk_dataset_env_vars(2));
+        env_vars.insert(IexecDatasetExtract(1).name(), "rar".to_string());
+        // bulk-dataset-2.txt has no archive extension
+        env_vars.insert(IexecDatasetExtract(2).name(), "auto".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration; 2]
+            );
+            assert_eq!(args.datasets.len(), 1);
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_bulk_dataset_filename_is_extraction_dir_of_another() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.extend(setup_bulk_dataset_env_vars(3));
+        env_vars.insert(IexecDatasetFilename(1).name(), "data.tar.gz".to_string());
+        env_vars.insert(IexecDatasetExtract(1).name(), "auto".to_string());
+        env_vars.insert(IexecDatasetFilename(2).name(), "data".to_string());
+        env_vars.insert(IexecDatasetFilename(3).name(), "data.part".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![
+                    ReplicateStatusCause::PreComputeDuplicateDatasetFilename("data".to_string()),
+                    ReplicateStatusCause::PreComputeDuplicateDatasetFilename(
+                        "data.part".to_string()
+                    ),
+                ]
+            );
+            assert_eq!(args.datasets.len(), 2);
+            assert_eq!(args.datasets[1].filename.as_str(), "data.tar.gz");
+        });
+    }
+
+    #[test]
+    fn read_args_reads_extraction_limits() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(0));
+        env_vars.insert(IexecPreComputeMaxExtractedSize.name(), "2048".to_string());
+        env_vars.insert(IexecPreComputeMaxExtractedEntries.name(), "0".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidArchiveExtractionConfiguration]
+            );
+            assert_eq!(args.extraction_limits.max_size, 2048);
+            assert_eq!(
+                args.extraction_limits.max_entries,
+                ExtractionLimits::default().max_entries
+            );
+        });
+    }
+    // endregion
+
+    // region cleanup policy
+    #[test]
+    fn read_args_uses_secure_cleanup_when_not_configured() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(errors.is_empty());
+            assert_eq!(args.cleanup_policy, CleanupPolicy::Secure);
+        });
+    }
+
+    #[test]
+    fn read_args_reads_cleanup_policy() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(IexecPreComputeCleanupOnFailure.name(), "keep".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(errors.is_empty());
+            assert_eq!(args.cleanup_policy, CleanupPolicy::Keep);
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_cleanup_policy_invalid() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(IexecPreComputeCleanupOnFailure.name(), "shred".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                errors,
+                vec![ReplicateStatusCause::PreComputeInvalidCleanupPolicy]
+            );
+            assert_eq!(args.cleanup_policy, CleanupPolicy::Secure);
+        });
+    }
+    // endregion
+
+    // region local source root
+    #[test]
+    fn read_args_reads_canonical_local_source_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(
+            IexecPreComputeLocalSourceRoot.name(),
+            format!("{}/datasets/..", temp_dir.path().display()),
+        );
+        fs::create_dir(temp_dir.path().join("datasets")).unwrap();
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(errors.is_empty());
+            assert_eq!(
+                args.local_source_root,
+                Some(fs::canonicalize(temp_dir.path()).unwrap())
+            );
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_local_source_root_not_a_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file.txt");
+        fs::write(&file, "content").unwrap();
+
+        for root in [file, temp_dir.path().join("missing")] {
+            let mut env_vars = setup_basic_env_vars();
+            env_vars.extend(setup_dataset_env_vars());
+            env_vars.insert(
+                IexecPreComputeLocalSourceRoot.name(),
+                root.display().to_string(),
+            );
+
+            temp_env::with_vars(to_temp_env_vars(env_vars), || {
+                let (args, errors) = PreComputeArgs::read_args();
+
+                assert_eq!(
+                    errors,
+                    vec![ReplicateStatusCause::PreComputeInvalidLocalSourceRoot]
+                );
+                assert_eq!(args.local_source_root, None);
+            });
+        }
+    }
+    // endregion
+
+    // region dataset key provider
+    const ENCLAVE_CHALLENGE_PRIVATE_KEY: &str =
+        "0xdd3b993ec21c71c1f6d63a5240850e0d4d8dd83ff70d29e49247958548c1d479";
+
+    fn setup_sms_env_vars(sms_url: &str) -> HashMap<String, String> {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.remove(&IexecDatasetKey(0).name());
+        env_vars.insert(IexecPreComputeSmsUrl.name(), sms_url.to_string());
+        env_vars.insert(IexecTaskId.name(), "0x123456789abcdef".to_string());
+        env_vars.insert(SignWorkerAddress.name(), "0xabcdef123456789".to_string());
+        env_vars.insert(
+            SignTeeChallengePrivateKey.name(),
+            ENCLAVE_CHALLENGE_PRIVATE_KEY.to_string(),
+        );
+        env_vars
+    }
+
+    #[test]
+    fn read_args_retrieves_dataset_keys_from_sms_when_configured() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(path("/tee/tasks/0x123456789abcdef/datasets/0/key"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_json(serde_json::json!({ "key": "smsKey123" })),
+                )
+                .expect(1)
+                .mount(&server)
+                .await;
+            server
+        });
+
+        temp_env::with_vars(to_temp_env_vars(setup_sms_env_vars(&server.uri())), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert!(errors.is_empty());
+            assert_eq!(args.datasets[0].key.expose_secret(), "smsKey123");
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_sms_challenge_cannot_be_computed() {
+        let mut env_vars = setup_sms_env_vars("http://127.0.0.1:1");
+        env_vars.insert(SignTeeChallengePrivateKey.name(), String::new());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, errors) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                errors,
+                vec![ReplicateStatusCause::PreComputeTeeChallengePrivateKeyMissing]
+            );
+            assert!(args.datasets.is_empty());
+        });
+    }
+    // endregion
+
+    // region session file
+    fn write_session_file(temp_dir: &TempDir, name: &str, content: &str) -> String {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn read_args_reads_session_file_instead_of_env_vars() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_file = write_session_file(
+            &temp_dir,
+            "session.toml",
+            r#"
+            is_dataset_required = false
+            original_input_filenames = true
+            workers = 2
+
+            [[bulk_datasets]]
+            url = "https://bulk-dataset-1.bin"
+            checksum = "0x123checksum"
+            filename = "bulk-dataset-1.zip"
+            key = "bulkKey123"
+            extract = "auto"
+
+            [[bulk_datasets]]
+            url = "https://bulk-dataset-2.bin"
+            checksum = "0x223checksum"
+            filename = "bulk-dataset-2.txt"
+            key = "bulkKey223"
+
+            [[input_files]]
+            url = "https://input-1.txt"
+            checksum = "0x0000000000000000000000000000000000000000000000000000000000000001"
+            "#,
+        );
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.extend(setup_input_files_env_vars(3));
+        env_vars.insert(IexecPreComputeSessionFile.name(), session_file);
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert!(exit_causes.is_empty());
+            assert!(!args.is_dataset_required);
+            assert_eq!(args.iexec_bulk_slice_size, 2);
+            assert_eq!(args.datasets.len(), 2);
+            assert_eq!(args.datasets[0].url, "https://bulk-dataset-1.bin");
+            assert_eq!(args.datasets[0].key.expose_secret(), "bulkKey123");
+            assert_eq!(args.datasets[0].extract, Some(ArchiveFormat::Zip));
+            assert_eq!(args.datasets[1].filename.as_str(), "bulk-dataset-2.txt");
+            assert_eq!(args.datasets[1].extract, None);
+            assert_eq!(args.input_files.len(), 1);
+            assert_eq!(args.input_files[0].url, "https://input-1.txt");
+            assert!(args.input_files[0].checksum.is_some());
+            assert!(args.original_input_filenames);
+            assert_eq!(args.workers, 2);
+        });
+    }
+
+    #[test]
+    fn read_args_validates_session_file_like_env_vars() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_file = write_session_file(
+            &temp_dir,
+            "session.json",
+            r#"{
+                "is_dataset_required": true,
+                "dataset": { "url": "https://dataset.url", "checksum": "0x1", "filename": "../dataset.txt", "key": "key" },
+                "bulk_datasets": [
+                    { "url": "https://bulk-dataset-1.bin", "checksum": "0x2", "filename": "bulk-dataset-1.txt" },
+                    { "url": "https://bulk-dataset-2.bin", "checksum": "0x3", "filename": "bulk-dataset-2.txt", "key": "key" }
+                ],
+                "input_files": [{ "checksum": "0x4" }],
+                "workers": 0
+            }"#,
+        );
+        let env_vars = HashMap::from([(IexecPreComputeSessionFile.name(), session_file)]);
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![
+                    ReplicateStatusCause::PreComputeInvalidDatasetFilename("dataset_0".to_string()),
+                    ReplicateStatusCause::PreComputeDatasetKeyMissing(
+                        "bulk-dataset-1.txt".to_string()
+                    ),
+                    ReplicateStatusCause::PreComputeAtLeastOneInputFileUrlMissing(1),
+                    ReplicateStatusCause::PreComputeInvalidWorkersNumber,
+                ]
+            );
+            assert_eq!(args.datasets.len(), 1);
+            assert_eq!(args.datasets[0].filename.as_str(), "bulk-dataset-2.txt");
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_session_file_lists_no_ipfs_gateway() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_file = write_session_file(
+            &temp_dir,
+            "session.json",
+            r#"{ "is_dataset_required": false, "ipfs_gateways": [] }"#,
+        );
+        let env_vars = HashMap::from([(IexecPreComputeSessionFile.name(), session_file)]);
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidIpfsGatewayConfiguration]
+            );
+            assert_eq!(args.ipfs_gateways, IpfsGatewayConfig::default());
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_session_file_invalid() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_file = write_session_file(
+            &temp_dir,
+            "session.json",
+            r#"{ "is_dataset_required": "yes" }"#,
+        );
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.insert(IexecPreComputeSessionFile.name(), session_file);
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidSessionFile]
+            );
+            assert!(args.datasets.is_empty());
+        });
+    }
+    // endregion
+
+    // region retry policy
+    #[test]
+    fn read_args_reads_retry_policy() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(IexecPreComputeMaxAttempts.name(), "7".to_string());
+        env_vars.insert(IexecPreComputeInitialBackoff.name(), "250".to_string());
+        env_vars.insert(IexecPreComputeMaxBackoff.name(), "5000".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert!(exit_causes.is_empty());
+            assert_eq!(
+                args.retry_policy,
+                RetryPolicy {
+                    max_attempts: 7,
+                    initial_backoff: Duration::from_millis(250),
+                    max_backoff: Duration::from_secs(5),
+                }
+            );
+        });
+    }
+
+    #[test]
+    fn read_args_uses_default_retry_policy_when_not_set() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert!(exit_causes.is_empty());
+            assert_eq!(args.retry_policy, RetryPolicy::default());
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_retry_policy_invalid() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(IexecPreComputeMaxAttempts.name(), "0".to_string());
+        env_vars.insert(IexecPreComputeInitialBackoff.name(), "1s".to_string());
+        env_vars.insert(IexecPreComputeMaxBackoff.name(), "-1".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidRetryPolicy; 3]
+            );
+            assert_eq!(args.retry_policy, RetryPolicy::default());
+        });
+    }
+
+    #[test]
+    fn read_args_fails_when_initial_backoff_exceeds_max_backoff() {
+        let mut env_vars = setup_basic_env_vars();
+        env_vars.extend(setup_dataset_env_vars());
+        env_vars.insert(IexecPreComputeInitialBackoff.name(), "5000".to_string());
+        env_vars.insert(IexecPreComputeMaxBackoff.name(), "1000".to_string());
+
+        temp_env::with_vars(to_temp_env_vars(env_vars), || {
+            let (args, exit_causes) = PreComputeArgs::read_args();
+
+            assert_eq!(
+                exit_causes,
+                vec![ReplicateStatusCause::PreComputeInvalidRetryPolicy]
+            );
+            assert_eq!(args.retry_policy, RetryPolicy::default());
+        });
+    }
+    // endregion
 }
@@ -0,0 +1,304 @@
+use crate::api::worker_api::{ProgressEvent, ProgressStage, WorkerApiClient};
+use crate::compute::signer::get_challenge;
+use log::warn;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Minimal delay between two [`ProgressStage::Downloading`] reports.
+pub const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+/// Maximum number of reports waiting to be sent to the worker, further reports being dropped.
+pub const PROGRESS_QUEUE_CAPACITY: usize = 16;
+
+/// Reports the progress of the datasets to the worker while the pre-compute runs.
+///
+/// Milestone stages are always reported, while [`ProgressStage::Downloading`] reports are
+/// throttled to one every `interval` for the whole pre-compute, so that concurrent downloads
+/// do not flood the worker. Reporting never fails the task: a rejected report is only logged.
+///
+/// Reports are sent by a background thread, so that a slow worker never holds the downloads
+/// back. At most [`PROGRESS_QUEUE_CAPACITY`] reports wait to be sent, further ones being
+/// dropped, and the pending ones are sent before the reporter is dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use tee_worker_pre_compute::api::worker_api::ProgressStage;
+/// use tee_worker_pre_compute::compute::progress::ProgressReporter;
+///
+/// let progress = ProgressReporter::disabled();
+/// progress.report("dataset.txt", ProgressStage::Started, 0);
+/// ```
+pub struct ProgressReporter {
+    chain_task_id: String,
+    /// Queue of the reports sent by the background thread, `None` when reporting is disabled.
+    queue: Option<SyncSender<ProgressEvent>>,
+    sender_thread: Option<JoinHandle<()>>,
+    interval: Duration,
+    last_report: Mutex<Option<Instant>>,
+}
+
+impl ProgressReporter {
+    pub fn new(
+        chain_task_id: &str,
+        client: WorkerApiClient,
+        authorization: String,
+        interval: Duration,
+    ) -> Self {
+        let (queue, reports) = mpsc::sync_channel::<ProgressEvent>(PROGRESS_QUEUE_CAPACITY);
+        let thread_chain_task_id = chain_task_id.to_string();
+        let sender_thread = thread::spawn(move || {
+            for event in reports {
+                if let Err(e) = client.send_progress_for_pre_compute_stage(
+                    &authorization,
+                    &thread_chain_task_id,
+                    &event,
+                ) {
+                    warn!(
+                        "Failed to report progress, ignoring [chainTaskId:{thread_chain_task_id}, dataset:{}, stage:{:?}, error:{e}]",
+                        event.dataset, event.stage
+                    );
+                }
+            }
+        });
+        ProgressReporter {
+            chain_task_id: chain_task_id.to_string(),
+            queue: Some(queue),
+            sender_thread: Some(sender_thread),
+            interval,
+            last_report: Mutex::new(None),
+        }
+    }
+
+    /// Creates a reporter which does not report anything.
+    pub fn disabled() -> Self {
+        ProgressReporter {
+            chain_task_id: String::new(),
+            queue: None,
+            sender_thread: None,
+            interval: PROGRESS_REPORT_INTERVAL,
+            last_report: Mutex::new(None),
+        }
+    }
+
+    /// Creates a reporter sending progress to the worker configured by environment variables,
+    /// see [`WorkerApiClient::from_env`], authenticated by [`get_challenge`].
+    ///
+    /// Reporting is disabled if the challenge cannot be computed.
+    pub fn from_env(chain_task_id: &str) -> Self {
+        match get_challenge(chain_task_id) {
+            Ok(authorization) => Self::new(
+                chain_task_id,
+                WorkerApiClient::from_env(),
+                authorization,
+                PROGRESS_REPORT_INTERVAL,
+            ),
+            Err(e) => {
+                warn!(
+                    "Progress reporting disabled, failed to compute challenge [chainTaskId:{chain_task_id}, error:{e:?}]"
+                );
+                Self::disabled()
+            }
+        }
+    }
+
+    /// Reports that `dataset` reached `stage` after downloading `bytes_transferred` encrypted
+    /// bytes.
+    ///
+    /// [`ProgressStage::Downloading`] reports sent less than `interval` after the previous report
+    /// are skipped. The report is only queued, it is sent to the worker in the background.
+    pub fn report(&self, dataset: &str, stage: ProgressStage, bytes_transferred: u64) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        {
+            let mut last_report = self.last_report.lock().unwrap();
+            let now = Instant::now();
+            if stage == ProgressStage::Downloading
+                && last_report.is_some_and(|last| now.duration_since(last) < self.interval)
+            {
+                return;
+            }
+            *last_report = Some(now);
+        }
+
+        let event = ProgressEvent {
+            dataset: dataset.to_string(),
+            stage,
+            bytes_transferred,
+        };
+        if queue.try_send(event).is_err() {
+            warn!(
+                "Failed to queue progress report, ignoring [chainTaskId:{}, dataset:{dataset}, stage:{stage:?}]",
+                self.chain_task_id
+            );
+        }
+    }
+}
+
+impl Drop for ProgressReporter {
+    /// Waits for the queued reports to be sent.
+    fn drop(&mut self) {
+        self.queue.take();
+        if let Some(sender_thread) = self.sender_thread.take()
+            && sender_thread.join().is_err()
+        {
+            warn!(
+                "Progress reporting thread panicked [chainTaskId:{}]",
+                self.chain_task_id
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const CHAIN_TASK_ID: &str = "0x123456789abcdef";
+    const DATASET: &str = "dataset.txt";
+
+    fn start_worker_server(status: u16) -> (Runtime, MockServer) {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/progress")))
+                .respond_with(ResponseTemplate::new(status))
+                .mount(&server)
+                .await;
+            server
+        });
+        (rt, server)
+    }
+
+    fn count_reports(rt: &Runtime, server: &MockServer, stage: &str) -> usize {
+        rt.block_on(server.received_requests())
+            .unwrap()
+            .iter()
+            .filter(|request| {
+                serde_json::from_slice::<serde_json::Value>(&request.body).unwrap()["stage"]
+                    == stage
+            })
+            .count()
+    }
+
+    #[test]
+    fn report_throttles_downloading_but_not_milestones() {
+        let (rt, server) = start_worker_server(200);
+        let progress = ProgressReporter::new(
+            CHAIN_TASK_ID,
+            WorkerApiClient::new(&server.uri()),
+            "challenge".to_string(),
+            Duration::from_secs(60),
+        );
+
+        progress.report(DATASET, ProgressStage::Started, 0);
+        progress.report(DATASET, ProgressStage::Downloading, 10);
+        progress.report(DATASET, ProgressStage::Downloading, 20);
+        progress.report(DATASET, ProgressStage::Verified, 30);
+        progress.report(DATASET, ProgressStage::Saved, 30);
+        drop(progress);
+
+        assert_eq!(count_reports(&rt, &server, "STARTED"), 1);
+        assert_eq!(count_reports(&rt, &server, "DOWNLOADING"), 0);
+        assert_eq!(count_reports(&rt, &server, "VERIFIED"), 1);
+        assert_eq!(count_reports(&rt, &server, "SAVED"), 1);
+    }
+
+    #[test]
+    fn report_sends_downloading_once_interval_elapsed() {
+        let (rt, server) = start_worker_server(200);
+        let progress = ProgressReporter::new(
+            CHAIN_TASK_ID,
+            WorkerApiClient::new(&server.uri()),
+            "challenge".to_string(),
+            Duration::ZERO,
+        );
+
+        progress.report(DATASET, ProgressStage::Downloading, 10);
+        progress.report(DATASET, ProgressStage::Downloading, 20);
+        drop(progress);
+
+        assert_eq!(count_reports(&rt, &server, "DOWNLOADING"), 2);
+    }
+
+    #[test]
+    fn report_ignores_rejected_report() {
+        let (rt, server) = start_worker_server(500);
+        let progress = ProgressReporter::new(
+            CHAIN_TASK_ID,
+            WorkerApiClient::new(&server.uri()),
+            "challenge".to_string(),
+            Duration::ZERO,
+        );
+
+        progress.report(DATASET, ProgressStage::Started, 0);
+        drop(progress);
+
+        assert_eq!(count_reports(&rt, &server, "STARTED"), 1);
+    }
+
+    #[test]
+    fn report_does_not_wait_for_slow_worker() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/progress")))
+                .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(1)))
+                .mount(&server)
+                .await;
+            server
+        });
+        let progress = ProgressReporter::new(
+            CHAIN_TASK_ID,
+            WorkerApiClient::new(&server.uri()),
+            "challenge".to_string(),
+            Duration::ZERO,
+        );
+
+        let start = Instant::now();
+        progress.report(DATASET, ProgressStage::Started, 0);
+        progress.report(DATASET, ProgressStage::Downloading, 10);
+        progress.report(DATASET, ProgressStage::Verified, 20);
+        assert!(start.elapsed() < Duration::from_millis(500));
+
+        drop(progress);
+        assert_eq!(count_reports(&rt, &server, "STARTED"), 1);
+        assert_eq!(count_reports(&rt, &server, "DOWNLOADING"), 1);
+        assert_eq!(count_reports(&rt, &server, "VERIFIED"), 1);
+    }
+
+    #[test]
+    fn report_drops_reports_when_queue_full() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path(format!("/compute/pre/{CHAIN_TASK_ID}/progress")))
+                .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(50)))
+                .mount(&server)
+                .await;
+            server
+        });
+        let progress = ProgressReporter::new(
+            CHAIN_TASK_ID,
+            WorkerApiClient::new(&server.uri()),
+            "challenge".to_string(),
+            Duration::ZERO,
+        );
+
+        for bytes_transferred in 0..2 * PROGRESS_QUEUE_CAPACITY as u64 {
+            progress.report(DATASET, ProgressStage::Downloading, bytes_transferred);
+        }
+        drop(progress);
+
+        let reports = count_reports(&rt, &server, "DOWNLOADING");
+        assert!(reports <= PROGRESS_QUEUE_CAPACITY + 1, "{reports} reports");
+    }
+}
@@ -0,0 +1,327 @@
+use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::utils::env_utils::TeeSessionEnvironmentVariable;
+use crate::compute::utils::secret_utils::SecretString;
+use log::{error, info};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Session document provisioning the pre-compute arguments, as an alternative to environment
+/// variables, see [`PreComputeArgs::read_args`].
+///
+/// The document is a JSON file, or a TOML file when its name ends with `.toml`. Each field
+/// replaces the environment variable noted next to it, and is validated exactly like it: typed
+/// fields are re-encoded as the string the environment variable would hold, e.g. `true` as
+/// `"true"` or `8` as `"8"`, see [`SessionFile::value`]. Lists are the exception, they are read
+/// as is by [`SessionFile::list`], so that an empty list is reported as invalid instead of being
+/// taken for an unset variable.
+///
+/// For instance, in JSON:
+///
+/// ```json
+/// {
+///   "is_dataset_required": true,
+///   "dataset": { "url": "https://…", "checksum": "0x…", "filename": "data.zip", "key": "…" },
+///   "bulk_datasets": [{ "url": "https://…", "checksum": "0x…", "filename": "a.txt", "key": "…" }],
+///   "input_files": [{ "url": "https://…", "checksum": "sha256:0x…" }],
+///   "ipfs_gateways": ["https://ipfs.io"],
+///   "workers": 8
+/// }
+/// ```
+///
+/// [`PreComputeArgs::read_args`]: crate::compute::pre_compute_args::PreComputeArgs::read_args
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionFile {
+    /// `IS_DATASET_REQUIRED`
+    pub is_dataset_required: Option<bool>,
+    /// `IEXEC_DATASET_*`
+    pub dataset: Option<SessionDataset>,
+    /// `IEXEC_DATASET_#_*`, `IEXEC_BULK_SLICE_SIZE` being the number of bulk datasets
+    pub bulk_datasets: Vec<SessionDataset>,
+    /// `IEXEC_INPUT_FILE_*_#`, `IEXEC_INPUT_FILES_NUMBER` being the number of input files
+    pub input_files: Vec<SessionInputFile>,
+    /// `IEXEC_INPUT_FILES_ORIGINAL_NAMES`
+    pub original_input_filenames: Option<bool>,
+    /// `IEXEC_IPFS_GATEWAYS`
+    pub ipfs_gateways: Option<Vec<String>>,
+    /// `IEXEC_IPFS_GATEWAY_TIMEOUT`
+    pub ipfs_gateway_timeout: Option<u64>,
+    /// `IEXEC_IPFS_GATEWAYS_RACE`
+    pub ipfs_gateways_race: Option<bool>,
+    /// `IEXEC_IPFS_GATEWAYS_TRUSTLESS`
+    pub ipfs_gateways_trustless: Option<bool>,
+    /// `IEXEC_PRE_COMPUTE_WORKERS`
+    pub workers: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_MAX_FILE_SIZE`
+    pub max_file_size: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_MAX_TOTAL_SIZE`
+    pub max_total_size: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_MAX_EXTRACTED_SIZE`
+    pub max_extracted_size: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_MAX_EXTRACTED_ENTRIES`
+    pub max_extracted_entries: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_MAX_ATTEMPTS`
+    pub max_attempts: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_INITIAL_BACKOFF`
+    pub initial_backoff: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_MAX_BACKOFF`
+    pub max_backoff: Option<u64>,
+    /// `IEXEC_PRE_COMPUTE_CLEANUP_ON_FAILURE`
+    pub cleanup_on_failure: Option<String>,
+    /// `IEXEC_PRE_COMPUTE_SMS_URL`
+    pub sms_url: Option<String>,
+    /// `IEXEC_PRE_COMPUTE_LOCAL_SOURCE_ROOT`
+    pub local_source_root: Option<String>,
+}
+
+/// Dataset of a [`SessionFile`], whose fields replace the `IEXEC_DATASET_*` variables.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionDataset {
+    pub url: Option<String>,
+    pub checksum: Option<String>,
+    pub filename: Option<String>,
+    /// Read by the [`KeyProvider`] of the session file only, so that it is never exposed as a
+    /// plain string.
+    ///
+    /// [`KeyProvider`]: crate::compute::key_provider::KeyProvider
+    pub key: Option<SecretString>,
+    pub extract: Option<String>,
+}
+
+/// Input file of a [`SessionFile`], whose fields replace the `IEXEC_INPUT_FILE_*` variables.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionInputFile {
+    pub url: Option<String>,
+    pub checksum: Option<String>,
+}
+
+impl SessionFile {
+    /// Reads and parses the session file at `path`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SessionFile)` if the file is read and parsed.
+    /// * `Err(ReplicateStatusCause::PreComputeSessionFileReadFailed)` if the file cannot be read.
+    /// * `Err(ReplicateStatusCause::PreComputeInvalidSessionFile)` if the file is not a valid
+    ///   session document.
+    pub fn load(path: &str) -> Result<Self, ReplicateStatusCause> {
+        info!("Loading pre-compute session file [path:{path}]");
+        let content = fs::read_to_string(path).map_err(|e| {
+            error!("Failed to read session file [path:{path}, error:{e}]");
+            ReplicateStatusCause::PreComputeSessionFileReadFailed
+        })?;
+        let is_toml = Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        let session_file = if is_toml {
+            toml::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        };
+        session_file.map_err(|e| {
+            error!("Invalid session file [path:{path}, error:{e}]");
+            ReplicateStatusCause::PreComputeInvalidSessionFile
+        })
+    }
+
+    /// Returns the value of the field replacing `env_var`, formatted like the environment
+    /// variable, or `None` if the field is not set. Dataset keys are never returned, see
+    /// [`SessionDataset::key`].
+    pub fn value(&self, env_var: &TeeSessionEnvironmentVariable) -> Option<String> {
+        use TeeSessionEnvironmentVariable::*;
+
+        match env_var {
+            IsDatasetRequired => self.is_dataset_required.map(|value| value.to_string()),
+            IexecBulkSliceSize => Some(self.bulk_datasets.len().to_string()),
+            IexecDatasetChecksum(i) => self.dataset(*i)?.checksum.clone(),
+            IexecDatasetExtract(i) => self.dataset(*i)?.extract.clone(),
+            IexecDatasetFilename(i) => self.dataset(*i)?.filename.clone(),
+            IexecDatasetUrl(i) => self.dataset(*i)?.url.clone(),
+            IexecInputFilesNumber => Some(self.input_files.len().to_string()),
+            IexecInputFileChecksumPrefix(i) => self.input_file(*i)?.checksum.clone(),
+            IexecInputFileUrlPrefix(i) => self.input_file(*i)?.url.clone(),
+            IexecInputFilesOriginalNames => {
+                self.original_input_filenames.map(|value| value.to_string())
+            }
+            IexecIpfsGatewayTimeout => self.ipfs_gateway_timeout.map(|value| value.to_string()),
+            IexecIpfsGatewaysRace => self.ipfs_gateways_race.map(|value| value.to_string()),
+            IexecIpfsGatewaysTrustless => {
+                self.ipfs_gateways_trustless.map(|value| value.to_string())
+            }
+            IexecPreComputeWorkers => self.workers.map(|value| value.to_string()),
+            IexecPreComputeMaxFileSize => self.max_file_size.map(|value| value.to_string()),
+            IexecPreComputeMaxTotalSize => self.max_total_size.map(|value| value.to_string()),
+            IexecPreComputeMaxExtractedSize => {
+                self.max_extracted_size.map(|value| value.to_string())
+            }
+            IexecPreComputeMaxExtractedEntries => {
+                self.max_extracted_entries.map(|value| value.to_string())
+            }
+            IexecPreComputeMaxAttempts => self.max_attempts.map(|value| value.to_string()),
+            IexecPreComputeInitialBackoff => self.initial_backoff.map(|value| value.to_string()),
+            IexecPreComputeMaxBackoff => self.max_backoff.map(|value| value.to_string()),
+            IexecPreComputeCleanupOnFailure => self.cleanup_on_failure.clone(),
+            IexecPreComputeSmsUrl => self.sms_url.clone(),
+            IexecPreComputeLocalSourceRoot => self.local_source_root.clone(),
+            _ => None,
+        }
+    }
+
+    /// Returns the list field replacing the comma-separated `env_var`, or `None` if the field is
+    /// not set.
+    pub fn list(&self, env_var: &TeeSessionEnvironmentVariable) -> Option<Vec<String>> {
+        match env_var {
+            TeeSessionEnvironmentVariable::IexecIpfsGateways => self.ipfs_gateways.clone(),
+            _ => None,
+        }
+    }
+
+    /// Returns the dataset with the given index, 0 being the main dataset and the following
+    /// ones the bulk datasets.
+    pub(crate) fn dataset(&self, index: usize) -> Option<&SessionDataset> {
+        match index {
+            0 => self.dataset.as_ref(),
+            _ => self.bulk_datasets.get(index - 1),
+        }
+    }
+
+    /// Returns the input file with the given index, starting from 1.
+    fn input_file(&self, index: usize) -> Option<&SessionInputFile> {
+        self.input_files.get(index.checked_sub(1)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TeeSessionEnvironmentVariable::*;
+    use tempfile::TempDir;
+
+    const JSON_SESSION: &str = r#"{
+        "is_dataset_required": true,
+        "dataset": { "url": "https://dataset.url", "filename": "dataset.txt" },
+        "bulk_datasets": [{ "url": "https://bulk-dataset-1.bin", "extract": "zip" }],
+        "input_files": [{ "url": "https://input-1.txt", "checksum": "0xabc" }],
+        "ipfs_gateways": ["https://gateway-1", "https://gateway-2"],
+        "workers": 2
+    }"#;
+
+    const TOML_SESSION: &str = r#"
+        is_dataset_required = true
+        ipfs_gateways = ["https://gateway-1", "https://gateway-2"]
+        workers = 2
+
+        [dataset]
+        url = "https://dataset.url"
+        filename = "dataset.txt"
+
+        [[bulk_datasets]]
+        url = "https://bulk-dataset-1.bin"
+        extract = "zip"
+
+        [[input_files]]
+        url = "https://input-1.txt"
+        checksum = "0xabc"
+    "#;
+
+    fn write_session_file(temp_dir: &TempDir, name: &str, content: &str) -> String {
+        let path = temp_dir.path().join(name);
+        fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn load_parses_json_and_toml_session_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let json_path = write_session_file(&temp_dir, "session.json", JSON_SESSION);
+        let toml_path = write_session_file(&temp_dir, "session.TOML", TOML_SESSION);
+
+        let json_session = SessionFile::load(&json_path).unwrap();
+        let toml_session = SessionFile::load(&toml_path).unwrap();
+
+        assert_eq!(json_session, toml_session);
+        assert_eq!(json_session.bulk_datasets.len(), 1);
+        assert_eq!(json_session.workers, Some(2));
+    }
+
+    #[test]
+    fn load_fails_when_file_missing() {
+        assert_eq!(
+            SessionFile::load("/non-existent/session.json"),
+            Err(ReplicateStatusCause::PreComputeSessionFileReadFailed)
+        );
+    }
+
+    #[test]
+    fn load_fails_when_document_invalid() {
+        let temp_dir = TempDir::new().unwrap();
+        for (name, content) in [
+            ("malformed.json", "{"),
+            ("wrong-type.json", r#"{ "workers": "many" }"#),
+            ("unknown-field.json", r#"{ "worker": 2 }"#),
+            ("malformed.toml", "workers ="),
+        ] {
+            let path = write_session_file(&temp_dir, name, content);
+
+            assert_eq!(
+                SessionFile::load(&path),
+                Err(ReplicateStatusCause::PreComputeInvalidSessionFile),
+                "{name} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn value_formats_fields_like_environment_variables() {
+        let session: SessionFile = serde_json::from_str(JSON_SESSION).unwrap();
+
+        assert_eq!(session.value(&IsDatasetRequired), Some("true".to_string()));
+        assert_eq!(session.value(&IexecBulkSliceSize), Some("1".to_string()));
+        assert_eq!(
+            session.value(&IexecDatasetUrl(0)),
+            Some("https://dataset.url".to_string())
+        );
+        assert_eq!(session.value(&IexecDatasetKey(0)), None);
+        assert_eq!(
+            session.value(&IexecDatasetUrl(1)),
+            Some("https://bulk-dataset-1.bin".to_string())
+        );
+        assert_eq!(
+            session.value(&IexecDatasetExtract(1)),
+            Some("zip".to_string())
+        );
+        assert_eq!(session.value(&IexecDatasetUrl(2)), None);
+        assert_eq!(session.value(&IexecInputFilesNumber), Some("1".to_string()));
+        assert_eq!(
+            session.value(&IexecInputFileUrlPrefix(1)),
+            Some("https://input-1.txt".to_string())
+        );
+        assert_eq!(session.value(&IexecInputFileUrlPrefix(0)), None);
+        assert_eq!(
+            session.value(&IexecPreComputeWorkers),
+            Some("2".to_string())
+        );
+        assert_eq!(session.value(&IexecPreComputeMaxFileSize), None);
+        assert_eq!(session.value(&IexecTaskId), None);
+    }
+
+    #[test]
+    fn list_returns_list_fields_as_is() {
+        let session: SessionFile = serde_json::from_str(JSON_SESSION).unwrap();
+        assert_eq!(
+            session.list(&IexecIpfsGateways),
+            Some(vec![
+                "https://gateway-1".to_string(),
+                "https://gateway-2".to_string()
+            ])
+        );
+        assert_eq!(session.value(&IexecIpfsGateways), None);
+
+        let session: SessionFile = serde_json::from_str(r#"{ "ipfs_gateways": [] }"#).unwrap();
+        assert_eq!(session.list(&IexecIpfsGateways), Some(vec![]));
+        assert_eq!(SessionFile::default().list(&IexecIpfsGateways), None);
+    }
+}
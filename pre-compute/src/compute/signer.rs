@@ -1,6 +1,7 @@
 use crate::compute::errors::ReplicateStatusCause;
 use crate::compute::utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error};
 use crate::compute::utils::hash_utils::{concatenate_and_hash, hex_string_to_byte_array};
+use crate::compute::utils::secret_utils::SecretString;
 use alloy_signer::{Signature, SignerSync};
 use alloy_signer_local::PrivateKeySigner;
 
@@ -106,10 +107,11 @@ pub fn get_challenge(chain_task_id: &str) -> Result<String, ReplicateStatusCause
     let tee_challenge_private_key = get_env_var_or_error(
         TeeSessionEnvironmentVariable::SignTeeChallengePrivateKey,
         ReplicateStatusCause::PreComputeTeeChallengePrivateKeyMissing,
-    )?;
+    )
+    .map(SecretString::from)?;
 
     let message_hash = concatenate_and_hash(&[chain_task_id, &worker_address]);
-    sign_enclave_challenge(&message_hash, &tee_challenge_private_key)
+    sign_enclave_challenge(&message_hash, tee_challenge_private_key.expose_secret())
 }
 
 #[cfg(test)]
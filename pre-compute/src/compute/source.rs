@@ -0,0 +1,486 @@
+use crate::compute::dataset::{IpfsGatewayConfig, IpfsSourceResolver};
+use crate::compute::utils::file_utils::{DownloadOptions, open_download_stream};
+use crate::compute::utils::quota_utils::DownloadQuota;
+use crate::compute::utils::retry_utils::{DownloadAttempts, RetryPolicy};
+use log::{error, info};
+use reqwest::Url;
+use std::fs::{self, File, OpenOptions};
+use std::io::Read;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// Content to open with a [`SourceResolver`].
+pub struct SourceRequest<'a> {
+    /// The chain task ID for logging.
+    pub chain_task_id: &'a str,
+    /// Location of the content, e.g. an HTTP(S) URL or an IPFS multi-address.
+    pub url: &'a str,
+    /// Expected SHA-256 checksum of the content, hex-encoded and prefixed with `0x`, if known.
+    ///
+    /// Resolvers may use it to choose between several copies of the content, the caller
+    /// remaining responsible for verifying the content it reads.
+    pub checksum: Option<&'a str>,
+    /// The retries of transient failures.
+    pub retry_policy: &'a RetryPolicy,
+    /// The size limits of downloads, applied to the returned content.
+    pub quota: &'a DownloadQuota,
+}
+
+/// Content opened by a [`SourceResolver`].
+pub struct SourceStream {
+    /// The content, failing to be read once it exceeds the quota of the request.
+    pub reader: Box<dyn Read>,
+    /// Filename suggested by the source, if any. It must be sanitized before use, see
+    /// [`sanitize_filename`](crate::compute::utils::file_utils::sanitize_filename).
+    pub filename: Option<String>,
+}
+
+/// Reason why a [`SourceResolver`] could not open a content.
+#[derive(Debug, PartialEq)]
+pub enum SourceError {
+    /// The content could not be reached, with the history of the failed attempts.
+    Unavailable(DownloadAttempts),
+    /// The location holds no verifiable CID, or no copy of the content matched it.
+    InvalidCid,
+    /// No copy of the content matched the checksum of the request.
+    ChecksumMismatch,
+    /// No copy of the content was within the file size limit of the quota.
+    TooLarge,
+}
+
+/// Opens the content of a location, for the locations it supports.
+///
+/// Datasets and input files are opened through [`SourceResolvers`], so that a new kind of
+/// location is supported by implementing this trait and registering the implementation,
+/// see [`SourceResolvers::register`].
+pub trait SourceResolver: Send + Sync {
+    /// Returns whether `url` is a location handled by this resolver.
+    fn supports(&self, url: &str) -> bool;
+
+    /// Opens the content located at `request.url`, bounded by `request.quota`.
+    fn open(&self, request: &SourceRequest) -> Result<SourceStream, SourceError>;
+}
+
+/// Ordered set of [`SourceResolver`], a location being opened by the first resolver supporting it.
+///
+/// # Example
+///
+/// ```rust
+/// use tee_worker_pre_compute::compute::dataset::IpfsGatewayConfig;
+/// use tee_worker_pre_compute::compute::source::SourceResolvers;
+///
+/// let sources = SourceResolvers::new(IpfsGatewayConfig::default(), None);
+/// assert!(sources.supports("https://iex.ec/dataset.bin"));
+/// assert!(!sources.supports("file:///datasets/dataset.bin"));
+/// ```
+pub struct SourceResolvers {
+    resolvers: Vec<Box<dyn SourceResolver>>,
+}
+
+impl SourceResolvers {
+    /// Creates the resolvers of HTTP(S) URLs, IPFS multi-addresses resolved through
+    /// `ipfs_gateways` and, when `local_root` is set, local files below it, see
+    /// [`LocalSourceResolver`].
+    pub fn new(ipfs_gateways: IpfsGatewayConfig, local_root: Option<PathBuf>) -> Self {
+        let mut sources = SourceResolvers {
+            resolvers: Vec::new(),
+        };
+        sources.register(Box::new(HttpSourceResolver));
+        sources.register(Box::new(IpfsSourceResolver::new(ipfs_gateways)));
+        if let Some(root) = local_root {
+            sources.register(Box::new(LocalSourceResolver::new(root)));
+        }
+        sources
+    }
+
+    /// Adds a resolver, used for the locations not supported by the resolvers added before it.
+    pub fn register(&mut self, resolver: Box<dyn SourceResolver>) {
+        self.resolvers.push(resolver);
+    }
+
+    /// Returns whether a resolver supports `url`.
+    pub fn supports(&self, url: &str) -> bool {
+        self.resolvers.iter().any(|resolver| resolver.supports(url))
+    }
+
+    /// Opens the content located at `request.url` with the first resolver supporting it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SourceStream)` if the content is opened.
+    /// * `Err(SourceError::Unavailable)` if no resolver supports the location.
+    /// * `Err(SourceError)` returned by the resolver otherwise.
+    pub fn open(&self, request: &SourceRequest) -> Result<SourceStream, SourceError> {
+        match self
+            .resolvers
+            .iter()
+            .find(|resolver| resolver.supports(request.url))
+        {
+            Some(resolver) => resolver.open(request),
+            None => {
+                error!(
+                    "Unsupported source [chainTaskId:{}, url:{}]",
+                    request.chain_task_id, request.url
+                );
+                let mut attempts = DownloadAttempts::default();
+                attempts.record(request.url, "unsupported source");
+                Err(SourceError::Unavailable(attempts))
+            }
+        }
+    }
+}
+
+impl Default for SourceResolvers {
+    fn default() -> Self {
+        SourceResolvers::new(IpfsGatewayConfig::default(), None)
+    }
+}
+
+/// Downloads `http` and `https` URLs, see [`open_download_stream`].
+pub struct HttpSourceResolver;
+
+impl SourceResolver for HttpSourceResolver {
+    fn supports(&self, url: &str) -> bool {
+        Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+    }
+
+    fn open(&self, request: &SourceRequest) -> Result<SourceStream, SourceError> {
+        let options = DownloadOptions {
+            retry_policy: request.retry_policy.clone(),
+            ..DownloadOptions::default()
+        };
+        let stream =
+            open_download_stream(request.url, &options).map_err(SourceError::Unavailable)?;
+        let filename = stream.filename().map(str::to_string);
+        let content_length = stream.content_length();
+        Ok(SourceStream {
+            reader: Box::new(request.quota.reader(stream, content_length)),
+            filename,
+        })
+    }
+}
+
+/// Reads files of a local volume mounted in the enclave, given as `file://` URLs or absolute
+/// paths.
+///
+/// Only regular files below `root` can be read: a location resolving outside of it, through
+/// `..` components or symbolic links, is rejected, as is a location whose last component is a
+/// symbolic link. The root is expected to be canonical, as returned by [`fs::canonicalize`],
+/// otherwise no location is found below it.
+///
+/// Checks are made on the opened file rather than on its path, so that the file cannot be
+/// swapped between the checks and the read.
+pub struct LocalSourceResolver {
+    root: PathBuf,
+}
+
+impl LocalSourceResolver {
+    pub fn new(root: PathBuf) -> Self {
+        LocalSourceResolver { root }
+    }
+
+    /// Opens the regular file located at `url` if below the root, returning it with its
+    /// canonical path.
+    pub fn open_file(&self, url: &str) -> Result<(File, PathBuf), String> {
+        let path = if url.starts_with("file:") {
+            Url::parse(url)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .ok_or_else(|| "invalid file URL".to_string())?
+        } else {
+            PathBuf::from(url)
+        };
+        // O_NONBLOCK keeps the open of a FIFO from hanging, the file type being checked after.
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NOFOLLOW | libc::O_NONBLOCK)
+            .open(&path)
+            .map_err(|e| format!("failed to open file: {e}"))?;
+        let path = fs::read_link(format!("/proc/self/fd/{}", file.as_raw_fd()))
+            .map_err(|e| format!("failed to resolve path: {e}"))?;
+        if !path.starts_with(&self.root) {
+            return Err(format!("path outside of {}", self.root.display()));
+        }
+        let metadata = file
+            .metadata()
+            .map_err(|e| format!("failed to read metadata: {e}"))?;
+        if !metadata.is_file() {
+            return Err("not a regular file".to_string());
+        }
+        Ok((file, path))
+    }
+}
+
+impl SourceResolver for LocalSourceResolver {
+    fn supports(&self, url: &str) -> bool {
+        is_local_source(url)
+    }
+
+    fn open(&self, request: &SourceRequest) -> Result<SourceStream, SourceError> {
+        let (chain_task_id, url) = (request.chain_task_id, request.url);
+        let (file, path) = self.open_file(url).map_err(|failure| {
+            error!(
+                "Failed to open local source [chainTaskId:{chain_task_id}, url:{url}, error:{failure}]"
+            );
+            let mut attempts = DownloadAttempts::default();
+            attempts.record(url, &failure);
+            SourceError::Unavailable(attempts)
+        })?;
+        let size = file.metadata().ok().map(|metadata| metadata.len());
+        info!(
+            "Local source opened [chainTaskId:{chain_task_id}, path:{}, size:{size:?}]",
+            path.display()
+        );
+        Ok(SourceStream {
+            reader: Box::new(request.quota.reader(file, size)),
+            filename: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+        })
+    }
+}
+
+/// Returns whether `url` is a `file://` URL or an absolute path, see [`LocalSourceResolver`].
+///
+/// IPFS multi-addresses are absolute paths too, and are resolved before local sources by
+/// [`SourceResolvers`].
+pub fn is_local_source(url: &str) -> bool {
+    url.starts_with("file://") || Path::new(url).is_absolute()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::utils::quota_utils::{SizeLimitError, size_limit_error};
+    use tempfile::TempDir;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const CHAIN_TASK_ID: &str = "0x123456789abcdef";
+    const CONTENT: &str = "Some very useful data.";
+
+    fn request<'a>(url: &'a str, quota: &'a DownloadQuota) -> SourceRequest<'a> {
+        const NO_RETRY: RetryPolicy = RetryPolicy {
+            max_attempts: 1,
+            initial_backoff: std::time::Duration::ZERO,
+            max_backoff: std::time::Duration::ZERO,
+        };
+        SourceRequest {
+            chain_task_id: CHAIN_TASK_ID,
+            url,
+            checksum: None,
+            retry_policy: &NO_RETRY,
+            quota,
+        }
+    }
+
+    fn read_all(stream: SourceStream) -> std::io::Result<String> {
+        let mut content = String::new();
+        let mut reader = stream.reader;
+        reader.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    /// Creates a root containing `dataset.bin`, and a file outside of it.
+    fn local_root() -> (TempDir, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("datasets");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join("dataset.bin"), CONTENT).unwrap();
+        fs::write(temp_dir.path().join("outside.bin"), CONTENT).unwrap();
+        let root = fs::canonicalize(root).unwrap();
+        (temp_dir, root)
+    }
+
+    fn unavailable(url: &str, failure: &str) -> Result<String, SourceError> {
+        let mut attempts = DownloadAttempts::default();
+        attempts.record(url, failure);
+        Err(SourceError::Unavailable(attempts))
+    }
+
+    // region SourceResolvers
+    #[test]
+    fn open_dispatches_http_url_to_http_resolver() {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/dataset.bin"))
+                .respond_with(ResponseTemplate::new(200).set_body_string(CONTENT))
+                .mount(&server)
+                .await;
+            server
+        });
+        let url = format!("{}/dataset.bin", server.uri());
+        let quota = DownloadQuota::default();
+
+        let stream = SourceResolvers::default()
+            .open(&request(&url, &quota))
+            .unwrap();
+
+        assert_eq!(read_all(stream).unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn open_fails_when_no_resolver_supports_url() {
+        let quota = DownloadQuota::default();
+        for url in ["ftp://iex.ec/dataset.bin", "file:///datasets/dataset.bin"] {
+            let result = SourceResolvers::default()
+                .open(&request(url, &quota))
+                .map(|stream| read_all(stream).unwrap());
+
+            assert_eq!(result, unavailable(url, "unsupported source"), "{url}");
+        }
+    }
+
+    #[test]
+    fn open_uses_registered_resolver_for_new_scheme() {
+        struct StaticResolver;
+        impl SourceResolver for StaticResolver {
+            fn supports(&self, url: &str) -> bool {
+                url.starts_with("static:")
+            }
+            fn open(&self, _: &SourceRequest) -> Result<SourceStream, SourceError> {
+                Ok(SourceStream {
+                    reader: Box::new(CONTENT.as_bytes()),
+                    filename: None,
+                })
+            }
+        }
+        let mut sources = SourceResolvers::default();
+        sources.register(Box::new(StaticResolver));
+        let quota = DownloadQuota::default();
+
+        let stream = sources.open(&request("static:dataset", &quota)).unwrap();
+
+        assert_eq!(read_all(stream).unwrap(), CONTENT);
+    }
+    // endregion
+
+    // region LocalSourceResolver
+    #[test]
+    fn local_resolver_reads_file_url_and_absolute_path_below_root() {
+        let (_temp_dir, root) = local_root();
+        let sources = SourceResolvers::new(IpfsGatewayConfig::default(), Some(root.clone()));
+        let quota = DownloadQuota::default();
+        let file_path = root.join("dataset.bin");
+        let file_url = Url::from_file_path(&file_path).unwrap().to_string();
+
+        for url in [file_url.as_str(), file_path.to_str().unwrap()] {
+            let stream = sources.open(&request(url, &quota)).unwrap();
+
+            assert_eq!(stream.filename.as_deref(), Some("dataset.bin"), "{url}");
+            assert_eq!(read_all(stream).unwrap(), CONTENT, "{url}");
+        }
+    }
+
+    #[test]
+    fn local_resolver_rejects_path_outside_root() {
+        let (temp_dir, root) = local_root();
+        std::os::unix::fs::symlink(temp_dir.path(), root.join("parent")).unwrap();
+        let resolver = LocalSourceResolver::new(root.clone());
+        let quota = DownloadQuota::default();
+
+        for url in [
+            temp_dir.path().join("outside.bin"),
+            root.join("../outside.bin"),
+            root.join("parent/outside.bin"),
+        ] {
+            let url = url.to_str().unwrap();
+            let result = resolver
+                .open(&request(url, &quota))
+                .map(|stream| read_all(stream).unwrap());
+
+            assert_eq!(
+                result,
+                unavailable(url, &format!("path outside of {}", root.display())),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn local_resolver_rejects_symbolic_link() {
+        let (temp_dir, root) = local_root();
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("outside.bin"),
+            root.join("outside.bin"),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(root.join("dataset.bin"), root.join("inside.bin")).unwrap();
+        let resolver = LocalSourceResolver::new(root.clone());
+
+        for name in ["outside.bin", "inside.bin"] {
+            let result = resolver.open_file(root.join(name).to_str().unwrap());
+
+            assert!(
+                result.is_err_and(|e| e.starts_with("failed to open file:")),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn local_resolver_rejects_missing_file_and_non_regular_file() {
+        let (_temp_dir, root) = local_root();
+        let fifo = root.join("fifo");
+        assert!(
+            std::process::Command::new("mkfifo")
+                .arg(&fifo)
+                .status()
+                .unwrap()
+                .success()
+        );
+        let resolver = LocalSourceResolver::new(root.clone());
+
+        assert!(
+            resolver
+                .open_file(root.join("missing.bin").to_str().unwrap())
+                .is_err()
+        );
+        for path in [root.clone(), fifo] {
+            assert_eq!(
+                resolver
+                    .open_file(path.to_str().unwrap())
+                    .map(|(_, path)| path),
+                Err("not a regular file".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn local_resolver_returns_canonical_path_of_opened_file() {
+        let (_temp_dir, root) = local_root();
+        fs::create_dir(root.join("dir")).unwrap();
+        let resolver = LocalSourceResolver::new(root.clone());
+
+        let (mut file, path) = resolver
+            .open_file(root.join("dir/../dataset.bin").to_str().unwrap())
+            .unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+
+        assert_eq!(path, root.join("dataset.bin"));
+        assert_eq!(content, CONTENT);
+    }
+
+    #[test]
+    fn local_resolver_applies_quota() {
+        let (_temp_dir, root) = local_root();
+        let resolver = LocalSourceResolver::new(root.clone());
+        let quota = DownloadQuota::new(Some(4), None);
+        let url = root.join("dataset.bin");
+
+        let stream = resolver
+            .open(&request(url.to_str().unwrap(), &quota))
+            .unwrap();
+
+        assert_eq!(
+            read_all(stream).map_err(|e| size_limit_error(&e)),
+            Err(Some(SizeLimitError::FileTooLarge(4)))
+        );
+    }
+    // endregion
+}
@@ -1,3 +1,7 @@
+pub mod cid_utils;
 pub mod env_utils;
 pub mod file_utils;
 pub mod hash_utils;
+pub mod quota_utils;
+pub mod retry_utils;
+pub mod secret_utils;
@@ -0,0 +1,822 @@
+use log::{error, warn};
+use sha2::{Digest, Sha256};
+use std::io::{self, ErrorKind, Read};
+use tee_worker_unixfs::{UNIXFS_CHUNK_SIZE, UnixFsFileHasher};
+use thiserror::Error;
+
+/// Media type of CARv1 responses served by trustless IPFS gateways.
+///
+/// Blocks are requested in depth-first order with duplicates, so that a file can be
+/// verified and reassembled while streaming without keeping any block in memory.
+pub const CAR_ACCEPT_HEADER: &str = "application/vnd.ipld.car; version=1; order=dfs; dups=y";
+/// Largest block accepted in a CAR response, IPFS blocks being at most 1 MiB in practice.
+pub const CAR_MAX_BLOCK_SIZE: usize = 2 * 1024 * 1024;
+
+const SHA2_256_CODE: u64 = 0x12;
+const SHA2_256_LENGTH: usize = 32;
+const DAG_PB_CODEC: u64 = 0x70;
+const RAW_CODEC: u64 = 0x55;
+const UNIXFS_RAW_TYPE: u64 = 0;
+const UNIXFS_FILE_TYPE: u64 = 2;
+
+/// Error raised when some content does not match the IPFS CID it was requested for.
+///
+/// It is wrapped in an [`io::Error`] of kind [`ErrorKind::InvalidData`] by the verifying
+/// readers of this module, see [`is_cid_verification_error`].
+#[derive(Debug, Error)]
+#[error("IPFS content verification failed: {0}")]
+pub struct CidVerificationError(String);
+
+/// Returns `true` if the I/O error was raised because content did not match its CID.
+pub fn is_cid_verification_error(e: &io::Error) -> bool {
+    e.get_ref()
+        .is_some_and(|inner| inner.is::<CidVerificationError>())
+}
+
+fn verification_error(reason: String) -> io::Error {
+    error!("IPFS content verification failed [reason:{reason}]");
+    io::Error::new(ErrorKind::InvalidData, CidVerificationError(reason))
+}
+
+/// Content identifier of an IPFS block, restricted to SHA2-256 multihashes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cid {
+    pub codec: u64,
+    pub multihash: Vec<u8>,
+}
+
+impl Cid {
+    /// Builds the CIDv0 of a dag-pb block from its multihash, such as the one returned by
+    /// [`UnixFsFileHasher::finalize`].
+    fn v0(multihash: Vec<u8>) -> Self {
+        Cid {
+            codec: DAG_PB_CODEC,
+            multihash,
+        }
+    }
+
+    /// Parses a binary CID (v0 or v1) at the start of `bytes`, returning it with its length.
+    fn parse(bytes: &[u8]) -> Option<(Self, usize)> {
+        if bytes.len() >= 2 + SHA2_256_LENGTH
+            && bytes[0] == SHA2_256_CODE as u8
+            && bytes[1] == SHA2_256_LENGTH as u8
+        {
+            let cid = Cid {
+                codec: DAG_PB_CODEC,
+                multihash: bytes[..2 + SHA2_256_LENGTH].to_vec(),
+            };
+            return Some((cid, 2 + SHA2_256_LENGTH));
+        }
+
+        let mut pos = 0;
+        if read_varint(bytes, &mut pos)? != 1 {
+            return None;
+        }
+        let codec = read_varint(bytes, &mut pos)?;
+        let multihash_start = pos;
+        read_varint(bytes, &mut pos)?;
+        let digest_length = read_varint(bytes, &mut pos)? as usize;
+        let end = pos
+            .checked_add(digest_length)
+            .filter(|end| *end <= bytes.len())?;
+        let cid = Cid {
+            codec,
+            multihash: bytes[multihash_start..end].to_vec(),
+        };
+        Some((cid, end))
+    }
+
+    /// Checks that `data` hashes to the multihash of this CID.
+    fn verify(&self, data: &[u8]) -> Result<(), io::Error> {
+        let expected_prefix = [SHA2_256_CODE as u8, SHA2_256_LENGTH as u8];
+        if self.multihash.len() != 2 + SHA2_256_LENGTH || self.multihash[..2] != expected_prefix {
+            return Err(verification_error(
+                "unsupported multihash, only SHA2-256 is supported".to_string(),
+            ));
+        }
+        if self.multihash[2..] != Sha256::digest(data)[..] {
+            return Err(verification_error("block hash mismatch".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the CID of an IPFS path such as `/ipfs/QmUVhChbLFiuzNK1g2GsWyWEiad7SXPqARnWzGumgziwEp`
+/// or `/ipfs/bafybeic3o4vo2yftv5iydtzmdqxplsd4lcsdnr5j4xu43nezqfxa6oyxce`.
+///
+/// # Returns
+///
+/// * `Some(Cid)` if the path is `/ipfs/` followed by a CIDv0 in base58 or a multibase CIDv1.
+/// * `None` otherwise.
+pub fn cid_from_multi_address(multi_address: &str) -> Option<Cid> {
+    let encoded = multi_address.strip_prefix("/ipfs/")?;
+    if encoded.is_empty() || encoded.contains('/') {
+        return None;
+    }
+    let bytes = if encoded.starts_with("Qm") {
+        bs58::decode(encoded).into_vec().ok()?
+    } else {
+        multibase::decode(encoded).ok()?.1
+    };
+    match Cid::parse(&bytes) {
+        Some((cid, length)) if length == bytes.len() => Some(cid),
+        _ => None,
+    }
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Value of a protobuf field, only varint and length-delimited fields being used by dag-pb and UnixFS.
+enum ProtobufValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Other,
+}
+
+/// Iterates over the fields of a protobuf message, failing on malformed content.
+fn parse_protobuf(bytes: &[u8]) -> Result<Vec<(u64, ProtobufValue<'_>)>, io::Error> {
+    let malformed = || verification_error("malformed protobuf message".to_string());
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let tag = read_varint(bytes, &mut pos).ok_or_else(malformed)?;
+        let value = match tag & 0x07 {
+            0 => ProtobufValue::Varint(read_varint(bytes, &mut pos).ok_or_else(malformed)?),
+            1 | 5 => {
+                pos += if tag & 0x07 == 1 { 8 } else { 4 };
+                ProtobufValue::Other
+            }
+            2 => {
+                let length = read_varint(bytes, &mut pos).ok_or_else(malformed)? as usize;
+                let end = pos
+                    .checked_add(length)
+                    .filter(|end| *end <= bytes.len())
+                    .ok_or_else(malformed)?;
+                let value = ProtobufValue::Bytes(&bytes[pos..end]);
+                pos = end;
+                value
+            }
+            _ => return Err(malformed()),
+        };
+        fields.push((tag >> 3, value));
+    }
+    if pos != bytes.len() {
+        return Err(malformed());
+    }
+    Ok(fields)
+}
+
+/// Reader checking that the content read from `inner` is the file identified by `expected_cid`.
+///
+/// The CID is recomputed with [`UnixFsFileHasher`] while the content is read, and compared
+/// once `inner` reaches its end. On mismatch, the final read fails with an error for which
+/// [`is_cid_verification_error`] returns `true` instead of signaling the end of the content.
+pub struct UnixFsVerifyingReader<R> {
+    inner: R,
+    expected_cid: Cid,
+    hasher: Option<UnixFsFileHasher>,
+}
+
+impl<R: Read> UnixFsVerifyingReader<R> {
+    pub fn new(inner: R, expected_cid: Cid) -> Self {
+        UnixFsVerifyingReader {
+            inner,
+            expected_cid,
+            hasher: Some(UnixFsFileHasher::default()),
+        }
+    }
+}
+
+impl<R: Read> Read for UnixFsVerifyingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            if let Some(hasher) = self.hasher.as_mut() {
+                hasher.update(&buf[..read]);
+            }
+        } else if let Some(hasher) = self.hasher.take()
+            && Cid::v0(hasher.finalize()) != self.expected_cid
+        {
+            return Err(verification_error(
+                "recomputed CID does not match the requested CID".to_string(),
+            ));
+        }
+        Ok(read)
+    }
+}
+
+/// Reader checking that the content read from `inner` is the file identified by `expected_cid`,
+/// whatever the parameters used to add it to IPFS.
+///
+/// The CID is first recomputed like [`UnixFsVerifyingReader`] does, which only matches content
+/// added with default parameters. On mismatch, `open_car` is called to fetch the same file as a
+/// CAR response, verified block by block with [`CarFileReader`]. The content read from `inner`
+/// is accepted if it has the same size and SHA-256 hash as the file extracted from the CAR
+/// response, so files added with another chunker or layout are verified at the cost of a second
+/// download. Otherwise, or if the CAR response cannot be fetched, the final read fails with the
+/// original error, for which [`is_cid_verification_error`] returns `true`.
+pub struct CarFallbackVerifyingReader<R, F> {
+    inner: UnixFsVerifyingReader<R>,
+    open_car: Option<F>,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<R, F> CarFallbackVerifyingReader<R, F>
+where
+    R: Read,
+    F: FnOnce() -> io::Result<Box<dyn Read>>,
+{
+    pub fn new(inner: R, expected_cid: Cid, open_car: F) -> Self {
+        CarFallbackVerifyingReader {
+            inner: UnixFsVerifyingReader::new(inner, expected_cid),
+            open_car: Some(open_car),
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+
+    fn verify_with_car(&mut self, open_car: F) -> io::Result<()> {
+        warn!(
+            "Content does not match its CID with default parameters, verifying it against a CAR response"
+        );
+        let mut car = CarFileReader::new(open_car()?, self.inner.expected_cid.clone());
+        let mut car_hasher = Sha256::new();
+        let mut car_size: u64 = 0;
+        let mut buffer = vec![0u8; UNIXFS_CHUNK_SIZE];
+        loop {
+            match car.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    car_hasher.update(&buffer[..read]);
+                    car_size += read as u64;
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        let hasher = std::mem::take(&mut self.hasher);
+        if car_size != self.size || car_hasher.finalize() != hasher.finalize() {
+            return Err(verification_error(
+                "content does not match the file of the CAR response".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R, F> Read for CarFallbackVerifyingReader<R, F>
+where
+    R: Read,
+    F: FnOnce() -> io::Result<Box<dyn Read>>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.read(buf) {
+            Ok(read) => {
+                self.hasher.update(&buf[..read]);
+                self.size += read as u64;
+                Ok(read)
+            }
+            Err(e) if is_cid_verification_error(&e) => match self.open_car.take() {
+                Some(open_car) => self.verify_with_car(open_car).map(|_| 0).map_err(|car_e| {
+                    warn!("Content could not be verified against a CAR response [error:{car_e}]");
+                    e
+                }),
+                None => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Reader extracting a UnixFS file from a CARv1 stream, verifying every block on the fly.
+///
+/// Blocks must be sent in depth-first order, duplicates included, starting with the block of
+/// `root`, as requested with [`CAR_ACCEPT_HEADER`]. Each block is checked against the CID
+/// expected at its position in the DAG, so only content reachable from `root` is accepted and
+/// the file content is produced in order without buffering more than one block. Any error
+/// makes reads fail with an error for which [`is_cid_verification_error`] returns `true`.
+pub struct CarFileReader<R> {
+    inner: R,
+    header_read: bool,
+    expected_cids: Vec<Cid>,
+    output: Vec<u8>,
+    output_position: usize,
+}
+
+impl<R: Read> CarFileReader<R> {
+    pub fn new(inner: R, root: Cid) -> Self {
+        CarFileReader {
+            inner,
+            header_read: false,
+            expected_cids: vec![root],
+            output: Vec::new(),
+            output_position: 0,
+        }
+    }
+
+    /// Reads a varint, returning `None` if the stream ends before its first byte.
+    fn read_section_length(&mut self) -> io::Result<Option<usize>> {
+        let mut value: u64 = 0;
+        for (index, shift) in (0..64).step_by(7).enumerate() {
+            let mut byte = [0u8; 1];
+            if let Err(e) = self.inner.read_exact(&mut byte) {
+                return match e.kind() {
+                    ErrorKind::UnexpectedEof if index == 0 => Ok(None),
+                    ErrorKind::UnexpectedEof => {
+                        Err(verification_error("truncated CAR stream".to_string()))
+                    }
+                    _ => Err(e),
+                };
+            }
+            value |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(value as usize));
+            }
+        }
+        Err(verification_error("invalid CAR section length".to_string()))
+    }
+
+    fn read_section(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let Some(length) = self.read_section_length()? else {
+            return Ok(None);
+        };
+        if length == 0 || length > CAR_MAX_BLOCK_SIZE {
+            return Err(verification_error(format!(
+                "invalid CAR section length {length}"
+            )));
+        }
+        let mut section = vec![0u8; length];
+        self.inner
+            .read_exact(&mut section)
+            .map_err(|e| match e.kind() {
+                ErrorKind::UnexpectedEof => verification_error("truncated CAR stream".to_string()),
+                _ => e,
+            })?;
+        Ok(Some(section))
+    }
+
+    fn read_header(&mut self) -> io::Result<()> {
+        let header = self
+            .read_section()?
+            .ok_or_else(|| verification_error("empty CAR stream".to_string()))?;
+        match car_header_version(&header) {
+            Some(1) => Ok(()),
+            version => Err(verification_error(format!(
+                "unsupported CAR version {version:?}"
+            ))),
+        }
+    }
+
+    /// Reads and verifies the next block, queuing its children and buffering its file content.
+    fn read_next_block(&mut self) -> io::Result<()> {
+        let Some(expected_cid) = self.expected_cids.pop() else {
+            return Ok(());
+        };
+        let section = self.read_section()?.ok_or_else(|| {
+            verification_error("CAR stream ended before all blocks were received".to_string())
+        })?;
+        let (cid, cid_length) = Cid::parse(&section)
+            .ok_or_else(|| verification_error("invalid block CID".to_string()))?;
+        if cid != expected_cid {
+            return Err(verification_error(
+                "unexpected block in CAR stream".to_string(),
+            ));
+        }
+        let data = &section[cid_length..];
+        cid.verify(data)?;
+
+        self.output.clear();
+        self.output_position = 0;
+        match cid.codec {
+            RAW_CODEC => self.output.extend_from_slice(data),
+            DAG_PB_CODEC => {
+                let mut children = Vec::new();
+                for (field, value) in parse_protobuf(data)? {
+                    match (field, value) {
+                        (1, ProtobufValue::Bytes(unixfs)) => {
+                            self.output.extend_from_slice(unixfs_file_data(unixfs)?)
+                        }
+                        (2, ProtobufValue::Bytes(link)) => children.push(link_cid(link)?),
+                        _ => {}
+                    }
+                }
+                self.expected_cids.extend(children.into_iter().rev());
+            }
+            codec => {
+                return Err(verification_error(format!(
+                    "unsupported block codec 0x{codec:x}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for CarFileReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.header_read {
+            self.read_header()?;
+            self.header_read = true;
+        }
+        while self.output_position == self.output.len() {
+            if self.expected_cids.is_empty() {
+                return match self.read_section_length()? {
+                    None => Ok(0),
+                    Some(_) => Err(verification_error(
+                        "unexpected trailing block in CAR stream".to_string(),
+                    )),
+                };
+            }
+            self.read_next_block()?;
+        }
+        let read = buf.len().min(self.output.len() - self.output_position);
+        buf[..read].copy_from_slice(&self.output[self.output_position..][..read]);
+        self.output_position += read;
+        Ok(read)
+    }
+}
+
+/// Returns the file content held by a UnixFS node, rejecting directories and other node types.
+fn unixfs_file_data(unixfs: &[u8]) -> io::Result<&[u8]> {
+    let mut node_type = None;
+    let mut data: &[u8] = &[];
+    for (field, value) in parse_protobuf(unixfs)? {
+        match (field, value) {
+            (1, ProtobufValue::Varint(value)) => node_type = Some(value),
+            (2, ProtobufValue::Bytes(bytes)) => data = bytes,
+            _ => {}
+        }
+    }
+    match node_type {
+        Some(UNIXFS_FILE_TYPE | UNIXFS_RAW_TYPE) => Ok(data),
+        other => Err(verification_error(format!(
+            "unsupported UnixFS node type {other:?}"
+        ))),
+    }
+}
+
+fn link_cid(link: &[u8]) -> io::Result<Cid> {
+    parse_protobuf(link)?
+        .into_iter()
+        .find_map(|(field, value)| match (field, value) {
+            (1, ProtobufValue::Bytes(hash)) => match Cid::parse(hash) {
+                Some((cid, length)) if length == hash.len() => Some(cid),
+                _ => None,
+            },
+            _ => None,
+        })
+        .ok_or_else(|| verification_error("invalid link in dag-pb node".to_string()))
+}
+
+/// Reads the `version` entry of a CAR header, encoded as a DAG-CBOR map.
+fn car_header_version(header: &[u8]) -> Option<u64> {
+    let mut pos = 0;
+    let (major, entries) = read_cbor_head(header, &mut pos)?;
+    if major != 5 {
+        return None;
+    }
+    let mut version = None;
+    for _ in 0..entries {
+        let (key_major, key_length) = read_cbor_head(header, &mut pos)?;
+        if key_major != 3 {
+            return None;
+        }
+        let key = header.get(pos..pos.checked_add(key_length as usize)?)?;
+        pos += key_length as usize;
+        if key == b"version" {
+            match read_cbor_head(header, &mut pos)? {
+                (0, value) => version = Some(value),
+                _ => return None,
+            }
+        } else {
+            skip_cbor_item(header, &mut pos, 0)?;
+        }
+    }
+    version
+}
+
+fn read_cbor_head(bytes: &[u8], pos: &mut usize) -> Option<(u8, u64)> {
+    let initial = *bytes.get(*pos)?;
+    *pos += 1;
+    let major = initial >> 5;
+    let length = match initial & 0x1f {
+        value @ 0..=23 => return Some((major, u64::from(value))),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return None,
+    };
+    let argument = bytes.get(*pos..*pos + length)?;
+    *pos += length;
+    Some((
+        major,
+        argument
+            .iter()
+            .fold(0u64, |value, byte| (value << 8) | u64::from(*byte)),
+    ))
+}
+
+fn skip_cbor_item(bytes: &[u8], pos: &mut usize, depth: usize) -> Option<()> {
+    if depth > 16 {
+        return None;
+    }
+    let (major, argument) = read_cbor_head(bytes, pos)?;
+    match major {
+        0 | 1 | 7 => {}
+        2 | 3 => {
+            *pos = pos
+                .checked_add(argument as usize)
+                .filter(|end| *end <= bytes.len())?;
+        }
+        4 => {
+            for _ in 0..argument {
+                skip_cbor_item(bytes, pos, depth + 1)?;
+            }
+        }
+        5 => {
+            for _ in 0..argument * 2 {
+                skip_cbor_item(bytes, pos, depth + 1)?;
+            }
+        }
+        6 => skip_cbor_item(bytes, pos, depth + 1)?,
+        _ => return None,
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO_WORLD_CID: &str = "/ipfs/QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+    const DATASET_CID: &str = "/ipfs/QmUVhChbLFiuzNK1g2GsWyWEiad7SXPqARnWzGumgziwEp";
+    const EMPTY_FILE_CID: &str = "/ipfs/QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH";
+    const MULTI_CHUNK_FILE_CID: &str = "/ipfs/Qmeb988ZjF9Ui6AVPR8Sjg5sAv1B6DauS5rUjCoNs7ftZ1";
+
+    fn recompute_cid(content: &[u8], chunk_size: usize) -> Cid {
+        let mut hasher = UnixFsFileHasher::default();
+        for chunk in content.chunks(chunk_size) {
+            hasher.update(chunk);
+        }
+        Cid::v0(hasher.finalize())
+    }
+
+    fn put_varint(buffer: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            buffer.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        buffer.push(value as u8);
+    }
+
+    fn put_varint_field(buffer: &mut Vec<u8>, field: u64, value: u64) {
+        put_varint(buffer, field << 3);
+        put_varint(buffer, value);
+    }
+
+    fn put_bytes_field(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+        put_varint(buffer, (field << 3) | 2);
+        put_varint(buffer, bytes.len() as u64);
+        buffer.extend_from_slice(bytes);
+    }
+
+    /// Builds the CIDv0 of a dag-pb block.
+    fn block_cid(block: &[u8]) -> Cid {
+        let mut multihash = vec![SHA2_256_CODE as u8, SHA2_256_LENGTH as u8];
+        multihash.extend_from_slice(&Sha256::digest(block));
+        Cid::v0(multihash)
+    }
+
+    fn read_all<R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    /// Builds a DFS-ordered CAR stream of the DAG `UnixFsFileHasher` would build for `content`.
+    fn build_car(content: &[u8], chunk_size: usize) -> (Cid, Vec<u8>) {
+        let leaves: Vec<(Cid, Vec<u8>)> = content
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let mut unixfs = Vec::new();
+                put_varint_field(&mut unixfs, 1, UNIXFS_FILE_TYPE);
+                put_bytes_field(&mut unixfs, 2, chunk);
+                put_varint_field(&mut unixfs, 3, chunk.len() as u64);
+                let mut block = Vec::new();
+                put_bytes_field(&mut block, 1, &unixfs);
+                (block_cid(&block), block)
+            })
+            .collect();
+        let mut root = Vec::new();
+        for (cid, block) in &leaves {
+            let mut pb_link = Vec::new();
+            put_bytes_field(&mut pb_link, 1, &cid.multihash);
+            put_varint_field(&mut pb_link, 3, block.len() as u64);
+            put_bytes_field(&mut root, 2, &pb_link);
+        }
+        let mut unixfs = Vec::new();
+        put_varint_field(&mut unixfs, 1, UNIXFS_FILE_TYPE);
+        put_bytes_field(&mut root, 1, &unixfs);
+        let root_cid = block_cid(&root);
+
+        let mut car = Vec::new();
+        let header = [
+            &[0xa2, 0x65][..],
+            b"roots",
+            &[0x81, 0xd8, 0x2a, 0x58, 0x23, 0x00],
+            &root_cid.multihash,
+            &[0x67],
+            b"version",
+            &[0x01],
+        ]
+        .concat();
+        put_varint(&mut car, header.len() as u64);
+        car.extend_from_slice(&header);
+        for (cid, block) in std::iter::once((root_cid.clone(), root)).chain(leaves) {
+            put_varint(&mut car, (cid.multihash.len() + block.len()) as u64);
+            car.extend_from_slice(&cid.multihash);
+            car.extend_from_slice(&block);
+        }
+        (root_cid, car)
+    }
+
+    // region cid_from_multi_address
+    #[test]
+    fn cid_from_multi_address_returns_cid_when_ipfs_path() {
+        let cid = cid_from_multi_address(DATASET_CID).unwrap();
+        assert_eq!(cid.codec, DAG_PB_CODEC);
+        assert_eq!(cid.multihash.len(), 34);
+    }
+
+    #[test]
+    fn cid_from_multi_address_returns_none_when_not_ipfs_path() {
+        assert!(cid_from_multi_address("/ip4/127.0.0.1/tcp/80").is_none());
+        assert!(cid_from_multi_address("https://example.com/file").is_none());
+    }
+
+    #[test]
+    fn cid_from_multi_address_returns_cid_when_ipfs_path_holds_cid_v1() {
+        let dag_pb_cid = cid_from_multi_address(
+            "/ipfs/bafybeic3o4vo2yftv5iydtzmdqxplsd4lcsdnr5j4xu43nezqfxa6oyxce",
+        )
+        .unwrap();
+        assert_eq!(dag_pb_cid, cid_from_multi_address(DATASET_CID).unwrap());
+
+        let raw_cid = cid_from_multi_address(
+            "/ipfs/bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e",
+        )
+        .unwrap();
+        assert_eq!(raw_cid.codec, RAW_CODEC);
+        assert!(raw_cid.verify(b"hello world").is_ok());
+    }
+
+    #[test]
+    fn cid_from_multi_address_returns_none_when_cid_invalid() {
+        assert!(cid_from_multi_address("/ipfs/").is_none());
+        assert!(cid_from_multi_address("/ipfs/QmInvalid0").is_none());
+        assert!(cid_from_multi_address("/ipfs/bafy-not-a-cid").is_none());
+        assert!(cid_from_multi_address(&format!("{DATASET_CID}/file")).is_none());
+    }
+    // endregion
+
+    // region UnixFsFileHasher
+    #[test]
+    fn unixfs_file_hasher_matches_known_cids() {
+        let multi_chunk_content = vec![0u8; 4 * UNIXFS_CHUNK_SIZE + 1];
+        let test_cases: [(&[u8], &str); 4] = [
+            (b"hello world\n", HELLO_WORLD_CID),
+            (b"hello world !\n", DATASET_CID),
+            (b"", EMPTY_FILE_CID),
+            // head -c 1048577 /dev/zero | ipfs add -Q
+            (&multi_chunk_content, MULTI_CHUNK_FILE_CID),
+        ];
+        for (content, expected) in test_cases {
+            assert_eq!(
+                recompute_cid(content, 3000),
+                cid_from_multi_address(expected).unwrap(),
+                "{expected}"
+            );
+        }
+    }
+    // endregion
+
+    // region UnixFsVerifyingReader
+    #[test]
+    fn unixfs_verifying_reader_succeeds_when_content_matches_cid() {
+        let reader = UnixFsVerifyingReader::new(
+            &b"hello world !\n"[..],
+            cid_from_multi_address(DATASET_CID).unwrap(),
+        );
+        assert_eq!(read_all(reader).unwrap(), b"hello world !\n");
+    }
+
+    #[test]
+    fn unixfs_verifying_reader_fails_when_content_does_not_match_cid() {
+        let reader = UnixFsVerifyingReader::new(
+            &b"hello world ?\n"[..],
+            cid_from_multi_address(DATASET_CID).unwrap(),
+        );
+        let error = read_all(reader).unwrap_err();
+        assert!(is_cid_verification_error(&error));
+    }
+    // endregion
+
+    // region CarFallbackVerifyingReader
+    fn no_car() -> io::Result<Box<dyn Read>> {
+        panic!("CAR response must not be requested")
+    }
+
+    #[test]
+    fn car_fallback_verifying_reader_does_not_fetch_car_when_content_matches_cid() {
+        let reader = CarFallbackVerifyingReader::new(
+            &b"hello world !\n"[..],
+            cid_from_multi_address(DATASET_CID).unwrap(),
+            no_car,
+        );
+        assert_eq!(read_all(reader).unwrap(), b"hello world !\n");
+    }
+
+    #[test]
+    fn car_fallback_verifying_reader_accepts_content_added_with_other_chunker() {
+        let content: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let (root, car) = build_car(&content, 100);
+        let reader = CarFallbackVerifyingReader::new(&content[..], root, move || {
+            Ok(Box::new(io::Cursor::new(car)) as Box<dyn Read>)
+        });
+        assert_eq!(read_all(reader).unwrap(), content);
+    }
+
+    #[test]
+    fn car_fallback_verifying_reader_fails_when_content_differs_from_car_file() {
+        let content = vec![7u8; 1000];
+        let (root, car) = build_car(&content, 100);
+        let mut tampered = content.clone();
+        tampered[500] = 8;
+        let reader = CarFallbackVerifyingReader::new(&tampered[..], root, move || {
+            Ok(Box::new(io::Cursor::new(car)) as Box<dyn Read>)
+        });
+        let error = read_all(reader).unwrap_err();
+        assert!(is_cid_verification_error(&error));
+    }
+    // endregion
+
+    // region CarFileReader
+    #[test]
+    fn car_file_reader_returns_file_content_when_blocks_are_valid() {
+        let content: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let (root, car) = build_car(&content, 100);
+        assert_eq!(
+            read_all(CarFileReader::new(&car[..], root)).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn car_file_reader_fails_when_block_tampered() {
+        let content = vec![7u8; 1000];
+        let (root, mut car) = build_car(&content, 100);
+        let last = car.len() - 1;
+        car[last] ^= 0x01;
+        let error = read_all(CarFileReader::new(&car[..], root)).unwrap_err();
+        assert!(is_cid_verification_error(&error));
+    }
+
+    #[test]
+    fn car_file_reader_fails_when_root_is_not_requested_cid() {
+        let (_, car) = build_car(b"some content", 4);
+        let other_root = cid_from_multi_address(DATASET_CID).unwrap();
+        let error = read_all(CarFileReader::new(&car[..], other_root)).unwrap_err();
+        assert!(is_cid_verification_error(&error));
+    }
+
+    #[test]
+    fn car_file_reader_fails_when_stream_truncated() {
+        let (root, car) = build_car(b"some content", 4);
+        let error = read_all(CarFileReader::new(&car[..car.len() - 10], root)).unwrap_err();
+        assert!(is_cid_verification_error(&error));
+    }
+
+    #[test]
+    fn car_file_reader_fails_when_version_unsupported() {
+        let (root, mut car) = build_car(b"some content", 4);
+        // The version is the last byte of the header, right after its one-byte length.
+        let version_position = car[0] as usize;
+        car[version_position] = 0x02;
+        let error = read_all(CarFileReader::new(&car[..], root)).unwrap_err();
+        assert!(is_cid_verification_error(&error));
+    }
+    // endregion
+}
@@ -4,12 +4,31 @@ use std::env;
 pub enum TeeSessionEnvironmentVariable {
     IexecBulkSliceSize,
     IexecDatasetChecksum(usize),
+    IexecDatasetExtract(usize),
     IexecDatasetFilename(usize),
     IexecDatasetKey(usize),
     IexecDatasetUrl(usize),
+    IexecInputFileChecksumPrefix(usize),
     IexecInputFileUrlPrefix(usize),
     IexecInputFilesNumber,
+    IexecInputFilesOriginalNames,
+    IexecIpfsGatewayTimeout,
+    IexecIpfsGateways,
+    IexecIpfsGatewaysRace,
+    IexecIpfsGatewaysTrustless,
+    IexecPreComputeCleanupOnFailure,
+    IexecPreComputeInitialBackoff,
+    IexecPreComputeLocalSourceRoot,
+    IexecPreComputeMaxAttempts,
+    IexecPreComputeMaxBackoff,
+    IexecPreComputeMaxExtractedEntries,
+    IexecPreComputeMaxExtractedSize,
+    IexecPreComputeMaxFileSize,
+    IexecPreComputeMaxTotalSize,
     IexecPreComputeOut,
+    IexecPreComputeSessionFile,
+    IexecPreComputeSmsUrl,
+    IexecPreComputeWorkers,
     IexecTaskId,
     IsDatasetRequired,
     SignTeeChallengePrivateKey,
@@ -27,6 +46,11 @@ impl TeeSessionEnvironmentVariable {
                 format!("IEXEC_DATASET_{index}_CHECKSUM")
             }
 
+            Self::IexecDatasetExtract(0) => "IEXEC_DATASET_EXTRACT".to_string(),
+            Self::IexecDatasetExtract(index) => {
+                format!("IEXEC_DATASET_{index}_EXTRACT")
+            }
+
             Self::IexecDatasetFilename(0) => "IEXEC_DATASET_FILENAME".to_string(),
             Self::IexecDatasetFilename(index) => {
                 format!("IEXEC_DATASET_{index}_FILENAME")
@@ -42,11 +66,39 @@ impl TeeSessionEnvironmentVariable {
                 format!("IEXEC_DATASET_{index}_URL")
             }
 
+            Self::IexecInputFileChecksumPrefix(index) => {
+                format!("IEXEC_INPUT_FILE_CHECKSUM_{index}")
+            }
             Self::IexecInputFileUrlPrefix(index) => {
                 format!("IEXEC_INPUT_FILE_URL_{index}")
             }
             Self::IexecInputFilesNumber => "IEXEC_INPUT_FILES_NUMBER".to_string(),
+            Self::IexecInputFilesOriginalNames => "IEXEC_INPUT_FILES_ORIGINAL_NAMES".to_string(),
+            Self::IexecIpfsGatewayTimeout => "IEXEC_IPFS_GATEWAY_TIMEOUT".to_string(),
+            Self::IexecIpfsGateways => "IEXEC_IPFS_GATEWAYS".to_string(),
+            Self::IexecIpfsGatewaysRace => "IEXEC_IPFS_GATEWAYS_RACE".to_string(),
+            Self::IexecIpfsGatewaysTrustless => "IEXEC_IPFS_GATEWAYS_TRUSTLESS".to_string(),
+            Self::IexecPreComputeCleanupOnFailure => {
+                "IEXEC_PRE_COMPUTE_CLEANUP_ON_FAILURE".to_string()
+            }
+            Self::IexecPreComputeInitialBackoff => "IEXEC_PRE_COMPUTE_INITIAL_BACKOFF".to_string(),
+            Self::IexecPreComputeLocalSourceRoot => {
+                "IEXEC_PRE_COMPUTE_LOCAL_SOURCE_ROOT".to_string()
+            }
+            Self::IexecPreComputeMaxAttempts => "IEXEC_PRE_COMPUTE_MAX_ATTEMPTS".to_string(),
+            Self::IexecPreComputeMaxBackoff => "IEXEC_PRE_COMPUTE_MAX_BACKOFF".to_string(),
+            Self::IexecPreComputeMaxExtractedEntries => {
+                "IEXEC_PRE_COMPUTE_MAX_EXTRACTED_ENTRIES".to_string()
+            }
+            Self::IexecPreComputeMaxExtractedSize => {
+                "IEXEC_PRE_COMPUTE_MAX_EXTRACTED_SIZE".to_string()
+            }
+            Self::IexecPreComputeMaxFileSize => "IEXEC_PRE_COMPUTE_MAX_FILE_SIZE".to_string(),
+            Self::IexecPreComputeMaxTotalSize => "IEXEC_PRE_COMPUTE_MAX_TOTAL_SIZE".to_string(),
             Self::IexecPreComputeOut => "IEXEC_PRE_COMPUTE_OUT".to_string(),
+            Self::IexecPreComputeSessionFile => "IEXEC_PRE_COMPUTE_SESSION_FILE".to_string(),
+            Self::IexecPreComputeSmsUrl => "IEXEC_PRE_COMPUTE_SMS_URL".to_string(),
+            Self::IexecPreComputeWorkers => "IEXEC_PRE_COMPUTE_WORKERS".to_string(),
             Self::IexecTaskId => "IEXEC_TASK_ID".to_string(),
             Self::IsDatasetRequired => "IS_DATASET_REQUIRED".to_string(),
             Self::SignTeeChallengePrivateKey => "SIGN_TEE_CHALLENGE_PRIVATE_KEY".to_string(),
@@ -84,10 +136,67 @@ mod tests {
             TeeSessionEnvironmentVariable::IexecInputFilesNumber.name(),
             "IEXEC_INPUT_FILES_NUMBER"
         );
+
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecInputFilesOriginalNames.name(),
+            "IEXEC_INPUT_FILES_ORIGINAL_NAMES"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecIpfsGatewayTimeout.name(),
+            "IEXEC_IPFS_GATEWAY_TIMEOUT"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecIpfsGateways.name(),
+            "IEXEC_IPFS_GATEWAYS"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecIpfsGatewaysRace.name(),
+            "IEXEC_IPFS_GATEWAYS_RACE"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecIpfsGatewaysTrustless.name(),
+            "IEXEC_IPFS_GATEWAYS_TRUSTLESS"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeCleanupOnFailure.name(),
+            "IEXEC_PRE_COMPUTE_CLEANUP_ON_FAILURE"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeLocalSourceRoot.name(),
+            "IEXEC_PRE_COMPUTE_LOCAL_SOURCE_ROOT"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeMaxExtractedEntries.name(),
+            "IEXEC_PRE_COMPUTE_MAX_EXTRACTED_ENTRIES"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeMaxExtractedSize.name(),
+            "IEXEC_PRE_COMPUTE_MAX_EXTRACTED_SIZE"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeMaxFileSize.name(),
+            "IEXEC_PRE_COMPUTE_MAX_FILE_SIZE"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeMaxTotalSize.name(),
+            "IEXEC_PRE_COMPUTE_MAX_TOTAL_SIZE"
+        );
         assert_eq!(
             TeeSessionEnvironmentVariable::IexecPreComputeOut.name(),
             "IEXEC_PRE_COMPUTE_OUT"
         );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeSessionFile.name(),
+            "IEXEC_PRE_COMPUTE_SESSION_FILE"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeSmsUrl.name(),
+            "IEXEC_PRE_COMPUTE_SMS_URL"
+        );
+        assert_eq!(
+            TeeSessionEnvironmentVariable::IexecPreComputeWorkers.name(),
+            "IEXEC_PRE_COMPUTE_WORKERS"
+        );
         assert_eq!(
             TeeSessionEnvironmentVariable::IexecTaskId.name(),
             "IEXEC_TASK_ID"
@@ -120,6 +229,12 @@ mod tests {
         let env_var = TeeSessionEnvironmentVariable::IexecDatasetChecksum(10);
         assert_eq!(env_var.name(), "IEXEC_DATASET_10_CHECKSUM");
 
+        // Test IexecDatasetExtract
+        let env_var = TeeSessionEnvironmentVariable::IexecDatasetExtract(0);
+        assert_eq!(env_var.name(), "IEXEC_DATASET_EXTRACT");
+        let env_var = TeeSessionEnvironmentVariable::IexecDatasetExtract(2);
+        assert_eq!(env_var.name(), "IEXEC_DATASET_2_EXTRACT");
+
         // Test IexecDatasetFilename
         let env_var = TeeSessionEnvironmentVariable::IexecDatasetFilename(0);
         assert_eq!(env_var.name(), "IEXEC_DATASET_FILENAME");
@@ -144,6 +259,12 @@ mod tests {
         let env_var = TeeSessionEnvironmentVariable::IexecDatasetUrl(99);
         assert_eq!(env_var.name(), "IEXEC_DATASET_99_URL");
 
+        // Test IexecInputFileChecksumPrefix
+        let env_var = TeeSessionEnvironmentVariable::IexecInputFileChecksumPrefix(1);
+        assert_eq!(env_var.name(), "IEXEC_INPUT_FILE_CHECKSUM_1");
+        let env_var = TeeSessionEnvironmentVariable::IexecInputFileChecksumPrefix(123);
+        assert_eq!(env_var.name(), "IEXEC_INPUT_FILE_CHECKSUM_123");
+
         // Test IexecInputFileUrlPrefix
         let env_var = TeeSessionEnvironmentVariable::IexecInputFileUrlPrefix(0);
         assert_eq!(env_var.name(), "IEXEC_INPUT_FILE_URL_0");
@@ -1,7 +1,93 @@
-use log::{error, info};
-use reqwest::blocking::get;
-use std::fs;
+use crate::compute::input_file::INPUTS_MANIFEST_FILENAME;
+use crate::compute::utils::retry_utils::{DownloadAttempts, RetryPolicy, send_with_retry};
+use log::{error, info, warn};
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{
+    ACCEPT, ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_RANGE, CONTENT_TYPE, ETAG, HeaderValue,
+    IF_RANGE, LAST_MODIFIED, RANGE,
+};
+use reqwest::{StatusCode, Url};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Suffix of the file a download is written to until it is complete.
+pub const PARTIAL_FILE_SUFFIX: &str = ".part";
+/// Size of the buffer of zeros written over files by [`secure_delete`].
+const SECURE_DELETE_BUFFER_SIZE: usize = 64 * 1024;
+/// Maximum length in bytes of a filename on common filesystems.
+const MAX_FILENAME_LENGTH: usize = 255;
+/// Filenames of files written by the pre-compute itself, which cannot be given to datasets.
+pub const RESERVED_FILENAMES: &[&str] = &[INPUTS_MANIFEST_FILENAME];
+
+/// Reason why a filename is not a [`SafeFilename`].
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum FilenameError {
+    #[error("filename is empty")]
+    Empty,
+    #[error("filename contains a NUL byte")]
+    NulByte,
+    #[error("filename is an absolute path")]
+    Absolute,
+    #[error("filename contains a parent directory component")]
+    ParentComponent,
+    #[error("filename contains a path separator")]
+    PathSeparator,
+    #[error("filename is reserved")]
+    Reserved,
+}
+
+/// Filename validated to designate a file directly inside a folder.
+///
+/// Joining a `SafeFilename` onto a folder path never designates a location outside of that
+/// folder: absolute paths, parent directory (`..`) components, path separators and NUL bytes
+/// are rejected, as well as `.` and the [`RESERVED_FILENAMES`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SafeFilename(String);
+
+impl SafeFilename {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for SafeFilename {
+    type Err = FilenameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(FilenameError::Empty);
+        }
+        if s.contains('\0') {
+            return Err(FilenameError::NulByte);
+        }
+        let path = Path::new(s);
+        if path.has_root() || s.starts_with('\\') {
+            return Err(FilenameError::Absolute);
+        }
+        if s.split(['/', '\\']).any(|component| component == "..") {
+            return Err(FilenameError::ParentComponent);
+        }
+        if s.contains(['/', '\\']) {
+            return Err(FilenameError::PathSeparator);
+        }
+        if s == "." || RESERVED_FILENAMES.contains(&s) {
+            return Err(FilenameError::Reserved);
+        }
+        Ok(SafeFilename(s.to_string()))
+    }
+}
+
+impl fmt::Display for SafeFilename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
 
 /// Writes content to a file at the specified path, with proper error handling and logging.
 ///
@@ -45,29 +131,149 @@ pub fn write_file(content: &[u8], file_path: &Path, context: &str) -> Result<(),
                 "Failed to write file [{context}, path:{}]",
                 file_path.display()
             );
-            if file_path.exists() {
-                match fs::remove_file(file_path) {
-                    Ok(_) => {
-                        info!("File deleted [path:{}]", file_path.display());
-                    }
-                    Err(e) => {
-                        error!(
-                            "Failed to delete file [path:{}, error:{e}]",
-                            file_path.display()
-                        );
-                    }
-                }
-            }
+            delete_file(file_path);
+            Err(e)
+        }
+    }
+}
+
+/// Streams content from a reader to a file at the specified path, with proper error handling and logging.
+///
+/// Unlike [`write_file`], the content is never fully loaded into memory: it is copied
+/// chunk by chunk from `reader` to the file. If reading or writing fails midway, the
+/// partially written file is deleted.
+///
+/// # Arguments
+///
+/// * `reader` - The source of the content to write to the file
+/// * `file_path` - The path where the file should be written
+/// * `context` - A context string for logging (e.g., "url:https://iex.ec/file.txt" or "chainTaskId:0x123")
+///
+/// # Returns
+///
+/// * `Ok(u64)` with the number of bytes written if the file is successfully written
+/// * `Err(std::io::Error)` if reading from `reader` or writing the file fails
+///
+/// # Example
+///
+/// ```rust
+/// use std::path::PathBuf;
+/// use tee_worker_pre_compute::compute::utils::file_utils::write_stream;
+///
+/// let mut content: &[u8] = b"Hello, world!";
+/// let path = PathBuf::from("/tmp/test-stream.txt");
+/// if let Ok(size) = write_stream(&mut content, &path, "test context") {
+///     println!("{size} bytes written successfully");
+/// }
+/// ```
+pub fn write_stream<R: Read>(
+    reader: &mut R,
+    file_path: &Path,
+    context: &str,
+) -> Result<u64, std::io::Error> {
+    match File::create(file_path).and_then(|mut file| {
+        let size = io::copy(reader, &mut file)?;
+        file.flush()?;
+        Ok(size)
+    }) {
+        Ok(size) => {
+            info!(
+                "File written successfully [{context}, path:{}, size:{size}]",
+                file_path.display()
+            );
+            Ok(size)
+        }
+        Err(e) => {
+            error!(
+                "Failed to write file [{context}, path:{}, error:{e}]",
+                file_path.display()
+            );
+            delete_file(file_path);
             Err(e)
         }
     }
 }
 
+/// Deletes a file if it exists, logging the outcome.
+///
+/// This is used to clean up partially written files so that no truncated or
+/// unverified content is left behind after a failure.
+///
+/// # Arguments
+///
+/// * `file_path` - The path of the file to delete
+pub fn delete_file(file_path: &Path) {
+    if file_path.exists() {
+        match fs::remove_file(file_path) {
+            Ok(_) => {
+                info!("File deleted [path:{}]", file_path.display());
+            }
+            Err(e) => {
+                error!(
+                    "Failed to delete file [path:{}, error:{e}]",
+                    file_path.display()
+                );
+            }
+        }
+    }
+}
+
+/// Overwrites a file with zeros and flushes it to disk before deleting it, so that its content
+/// cannot be read back from the volume once deleted.
+///
+/// Directories are processed recursively, and symbolic links are deleted without following
+/// them. A missing path is ignored.
+///
+/// # Errors
+///
+/// Returns the first error encountered while overwriting or deleting an entry.
+pub fn secure_delete(path: &Path) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            secure_delete(&entry?.path())?;
+        }
+        return fs::remove_dir(path);
+    }
+    if metadata.is_file() {
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        let zeros = vec![0u8; SECURE_DELETE_BUFFER_SIZE];
+        let mut remaining = metadata.len();
+        while remaining > 0 {
+            let length = remaining.min(SECURE_DELETE_BUFFER_SIZE as u64) as usize;
+            file.write_all(&zeros[..length])?;
+            remaining -= length as u64;
+        }
+        file.sync_all()?;
+    }
+    fs::remove_file(path)
+}
+
+/// Options of a streaming download, see [`open_download_stream`].
+#[derive(Clone, Debug, Default)]
+pub struct DownloadOptions {
+    /// Media type requested through the `Accept` header. Responses of another type are rejected.
+    pub accept: Option<String>,
+    /// Maximum duration of each network operation: connecting, receiving the response headers,
+    /// then each read of the body. It bounds how long a stalled server is waited for, not the
+    /// total duration of the download, so a server slowly sending the content is not cut off.
+    pub timeout: Option<Duration>,
+    /// Retries of transient failures.
+    pub retry_policy: RetryPolicy,
+}
+
 /// Downloads a file from a given URL and writes it to a specified folder with a specified filename.
 ///
 /// If the download or any file operation fails, the function logs an appropriate error
 /// and returns `None`. It also ensures the parent directory exists, creating it if necessary.
-/// If the file write fails and a partial file was created, it will be cleaned up (deleted).
+/// The content is written to a partial file, suffixed with [`PARTIAL_FILE_SUFFIX`], which is
+/// renamed once the download completes, and deleted if it fails. A download interrupted by a
+/// dropped connection resumes where it stopped when the server supports it, see [`DownloadStream`].
+/// Transient failures are retried with the default [`RetryPolicy`], see [`download_file_with_retry`].
 ///
 /// # Arguments
 ///
@@ -95,49 +301,95 @@ pub fn write_file(content: &[u8], file_path: &Path, context: &str) -> Result<(),
 /// # Notes
 ///
 /// - This function uses **blocking** I/O (`reqwest::blocking`) and is not suitable for async contexts.
-/// - The downloaded content is fully loaded into memory before being written to disk.
+/// - The downloaded content is streamed to disk and never fully loaded into memory.
 pub fn download_file(url: &str, parent_dir: &str, filename: &str) -> Option<PathBuf> {
+    download_file_with_retry(url, parent_dir, filename, &RetryPolicy::default()).ok()
+}
+
+/// Downloads a file like [`download_file`], retrying transient failures according to `retry_policy`.
+///
+/// # Returns
+///
+/// - `Ok(PathBuf)` with the full path to the downloaded file if successful.
+/// - `Err(DownloadAttempts)` with the history of the failed attempts if any validation, download,
+///   directory creation, or file writing fails.
+pub fn download_file_with_retry(
+    url: &str,
+    parent_dir: &str,
+    filename: &str,
+    retry_policy: &RetryPolicy,
+) -> Result<PathBuf, DownloadAttempts> {
     if url.is_empty() {
         error!("Invalid file url [url:{url}]");
-        return None;
+        return Err(DownloadAttempts::default());
     }
     if parent_dir.is_empty() {
         error!("Invalid parent folder path [url:{url}, parent_dir:{parent_dir}]");
-        return None;
+        return Err(DownloadAttempts::default());
     }
     if filename.is_empty() {
         error!("Invalid output filename [url:{url}, parent_dir:{parent_dir}, filename:{filename}]");
-        return None;
+        return Err(DownloadAttempts::default());
     }
 
-    let bytes = match download_from_url(url) {
-        Some(b) => b,
-        None => {
-            error!("Failed to download file [url:{url}]");
-            return None;
-        }
+    let options = DownloadOptions {
+        retry_policy: retry_policy.clone(),
+        ..DownloadOptions::default()
     };
+    let mut stream = open_download_stream(url, &options).inspect_err(|_| {
+        error!("Failed to download file [url:{url}]");
+    })?;
 
-    let parent_path = Path::new(parent_dir);
-    let parent_existed = parent_path.exists();
+    save_stream(&mut stream, parent_dir, filename, &format!("url:{url}")).map_err(|e| {
+        let mut attempts = DownloadAttempts::default();
+        attempts.record(url, &format!("failed to write file: {e}"));
+        attempts
+    })
+}
 
-    if !parent_existed && fs::create_dir_all(parent_path).is_err() {
-        error!("Failed to create parent folder [url:{url}, parent_dir:{parent_dir}]");
-        return None;
+/// Writes the content of `reader` to the file `filename` of `parent_dir`, creating the directory
+/// if needed.
+///
+/// The content is written to a partial file, suffixed with [`PARTIAL_FILE_SUFFIX`], which is
+/// renamed once the whole content has been read. If reading or writing fails, the partial file
+/// is removed and an existing destination is left untouched, so a reader verifying its content once it reaches its end guarantees that only
+/// verified content is ever written to the destination. Errors raised by `reader` are returned
+/// unchanged.
+pub fn save_stream<R: Read>(
+    reader: &mut R,
+    parent_dir: &str,
+    filename: &str,
+    context: &str,
+) -> io::Result<PathBuf> {
+    let parent_path = Path::new(parent_dir);
+    if !parent_path.exists() {
+        fs::create_dir_all(parent_path).inspect_err(|e| {
+            error!(
+                "Failed to create parent folder [{context}, parent_dir:{parent_dir}, error:{e}]"
+            );
+        })?;
     }
 
     let file_path = parent_path.join(filename);
+    let partial_file_path = parent_path.join(format!("{filename}{PARTIAL_FILE_SUFFIX}"));
 
-    match write_file(&bytes, &file_path, &format!("url:{url}")) {
-        Ok(_) => Some(file_path),
-        Err(_) => None,
+    write_stream(reader, &partial_file_path, context)?;
+    if let Err(e) = fs::rename(&partial_file_path, &file_path) {
+        error!(
+            "Failed to rename partial file [{context}, path:{}, error:{e}]",
+            partial_file_path.display()
+        );
+        delete_file(&partial_file_path);
+        return Err(e);
     }
+    Ok(file_path)
 }
 
 /// Downloads the content from the given URL and returns it as a byte vector.
 ///
 /// This function supports any HTTP/HTTPS URL, including IPFS gateway URLs.
-/// It performs a blocking GET request and returns the full response body as bytes.
+/// It performs a blocking GET request, retrying transient failures with the default
+/// [`RetryPolicy`], and returns the full response body as bytes.
 ///
 /// # Arguments
 ///
@@ -165,43 +417,427 @@ pub fn download_file(url: &str, parent_dir: &str, filename: &str) -> Option<Path
 /// - This function uses blocking I/O and is not suitable for async contexts.
 /// - The entire response body is loaded into memory.
 pub fn download_from_url(url: &str) -> Option<Vec<u8>> {
+    let mut stream = open_download_stream(url, &DownloadOptions::default()).ok()?;
+    let mut bytes = Vec::new();
+    match stream.read_to_end(&mut bytes) {
+        Ok(_) => {
+            info!("Successfully downloaded {} bytes from {url}", bytes.len());
+            Some(bytes)
+        }
+        Err(e) => {
+            error!("Failed to download from {url}: {e}");
+            None
+        }
+    }
+}
+
+/// Opens a streaming download from the given URL.
+///
+/// This function supports any HTTP/HTTPS URL, including IPFS gateway URLs.
+/// It performs a blocking GET request and returns the response once the status
+/// has been checked, without reading the body. The caller consumes the body
+/// through the [`Read`] implementation of [`DownloadStream`], which keeps memory usage
+/// bounded to the caller's buffer size regardless of the content length.
+/// Transient failures are retried with the default [`RetryPolicy`].
+///
+/// # Arguments
+///
+/// * `url` - The URL to download from. Must not be empty.
+///
+/// # Returns
+///
+/// * `Some(DownloadStream)` if the request succeeds with a successful status code.
+/// * `None` if the URL is empty, the request fails, or the response status is not successful.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Read;
+/// use tee_worker_pre_compute::compute::utils::file_utils::download_stream_from_url;
+///
+/// if let Some(mut response) = download_stream_from_url("https://httpbin.org/json") {
+///     let mut buffer = [0u8; 1024];
+///     let read = response.read(&mut buffer).unwrap_or_default();
+///     println!("Read {read} bytes");
+/// }
+/// ```
+pub fn download_stream_from_url(url: &str) -> Option<DownloadStream> {
+    open_download_stream(url, &DownloadOptions::default()).ok()
+}
+
+/// Opens a streaming download from the given URL with the given options.
+///
+/// Same as [`download_stream_from_url`], except that the retry policy, the timeout of each
+/// network operation and the requested media type are taken from `options`. When a media type is
+/// requested, the response is rejected if its `Content-Type` does not start with it, which
+/// happens when the server ignores the `Accept` header.
+///
+/// # Arguments
+///
+/// * `url` - The URL to download from. Must not be empty.
+/// * `options` - The retry policy, timeout and media type of the download.
+///
+/// # Returns
+///
+/// * `Ok(DownloadStream)` if an attempt succeeds with a successful status code and the requested
+///   media type. Reads from the stream resume the download when the connection drops.
+/// * `Err(DownloadAttempts)` with the history of the failed attempts otherwise.
+pub fn open_download_stream(
+    url: &str,
+    options: &DownloadOptions,
+) -> Result<DownloadStream, DownloadAttempts> {
+    let mut attempts = DownloadAttempts::default();
     if url.is_empty() {
         error!("Invalid URL: empty string");
+        return Err(attempts);
+    }
+
+    let mut client = Client::builder();
+    if let Some(timeout) = options.timeout {
+        client = client.timeout(timeout);
+    }
+    let client = match client.build() {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to build HTTP client [url:{url}, error:{e}]");
+            return Err(attempts);
+        }
+    };
+
+    info!("Attempting to open download stream from {url}");
+
+    let mut request = client.get(url);
+    if let Some(accept) = &options.accept {
+        request = request.header(ACCEPT, accept);
+    }
+    let Some(response) = send_with_retry(url, request, &options.retry_policy, &mut attempts) else {
+        error!("Failed to open download stream from {url}");
+        return Err(attempts);
+    };
+
+    if let Some(accept) = &options.accept {
+        let media_type = accept.split(';').next().unwrap_or_default().trim();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if !content_type.starts_with(media_type) {
+            error!(
+                "Unexpected content type [url:{url}, expected:{media_type}, actual:{content_type}]"
+            );
+            let actual = if content_type.is_empty() {
+                "none"
+            } else {
+                content_type
+            };
+            attempts.record(url, &format!("unexpected content type {actual}"));
+            return Err(attempts);
+        }
+    }
+    let validator = range_validator(&response);
+    info!(
+        "Download stream opened [url:{url}, content_length:{:?}, resumable:{}]",
+        response.content_length(),
+        validator.is_some()
+    );
+    Ok(DownloadStream {
+        client,
+        url: url.to_string(),
+        accept: options.accept.clone(),
+        retry_policy: options.retry_policy.clone(),
+        content_length: response.content_length(),
+        filename: response
+            .headers()
+            .get(CONTENT_DISPOSITION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(content_disposition_filename),
+        response,
+        validator,
+        position: 0,
+        resumptions: 0,
+    })
+}
+
+/// Streaming body of a download, resuming where it stopped when the connection drops.
+///
+/// When the server advertises `Accept-Ranges: bytes` along with an `ETag` or `Last-Modified`
+/// validator, a failed read is recovered by requesting the rest of the content with a `Range`
+/// request. The validator is sent in an `If-Range` header, so a server whose content changed in
+/// the meantime answers with the whole new content instead, which is rejected: bytes of two
+/// versions of the content are never mixed. At most `max_attempts - 1` resumptions of the
+/// [`RetryPolicy`] are made, each request being retried like the initial one.
+///
+/// Without range support or a validator, a failed read fails the download.
+#[derive(Debug)]
+pub struct DownloadStream {
+    client: Client,
+    url: String,
+    accept: Option<String>,
+    retry_policy: RetryPolicy,
+    response: Response,
+    content_length: Option<u64>,
+    filename: Option<String>,
+    validator: Option<HeaderValue>,
+    position: u64,
+    resumptions: u32,
+}
+
+impl DownloadStream {
+    /// Returns the length of the whole content, if advertised by the server.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Returns the filename suggested by the `Content-Disposition` header of the server, if any.
+    ///
+    /// The filename is returned as sent and must be sanitized before use, see [`sanitize_filename`].
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    fn can_resume(&self) -> bool {
+        self.validator.is_some() && self.resumptions + 1 < self.retry_policy.max_attempts
+    }
+
+    fn resume(&mut self) -> io::Result<()> {
+        let Some(validator) = self.validator.clone() else {
+            return Err(io::Error::other("server does not support range requests"));
+        };
+        self.resumptions += 1;
+        let delay = self.retry_policy.backoff(self.resumptions);
+        warn!(
+            "Resuming interrupted download [url:{}, position:{}, resumption:{}, retryIn:{}ms]",
+            self.url,
+            self.position,
+            self.resumptions,
+            delay.as_millis()
+        );
+        thread::sleep(delay);
+
+        let mut request = self
+            .client
+            .get(&self.url)
+            .header(RANGE, format!("bytes={}-", self.position))
+            .header(IF_RANGE, validator);
+        if let Some(accept) = &self.accept {
+            request = request.header(ACCEPT, accept);
+        }
+        let mut attempts = DownloadAttempts::default();
+        let response = send_with_retry(&self.url, request, &self.retry_policy, &mut attempts)
+            .ok_or_else(|| io::Error::other(format!("failed to resume download{attempts}")))?;
+        if response.status() != StatusCode::PARTIAL_CONTENT
+            || content_range_start(&response) != Some(self.position)
+        {
+            error!(
+                "Content changed since the download started [url:{}, status:{}]",
+                self.url,
+                response.status()
+            );
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "content changed since the download started",
+            ));
+        }
+        self.response = response;
+        Ok(())
+    }
+}
+
+impl Read for DownloadStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.response.read(buf) {
+                Ok(read) => {
+                    self.position += read as u64;
+                    return Ok(read);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => return Err(e),
+                Err(e) if self.can_resume() => {
+                    warn!(
+                        "Download interrupted [url:{}, position:{}, error:{e}]",
+                        self.url, self.position
+                    );
+                    self.resume()?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Returns the validator identifying the version of the content, if ranges are supported.
+///
+/// Weak entity tags cannot be used in `If-Range`, the `Last-Modified` date is used instead.
+fn range_validator(response: &Response) -> Option<HeaderValue> {
+    let headers = response.headers();
+    let accepts_ranges = headers
+        .get(ACCEPT_RANGES)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|unit| unit.trim() == "bytes"));
+    if !accepts_ranges {
         return None;
     }
+    headers
+        .get(ETAG)
+        .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+        .or_else(|| headers.get(LAST_MODIFIED))
+        .cloned()
+}
 
-    info!("Attempting to download from {url}");
+/// Reads the first byte position of a `Content-Range: bytes <start>-<end>/<length>` header.
+fn content_range_start(response: &Response) -> Option<u64> {
+    let value = response.headers().get(CONTENT_RANGE)?.to_str().ok()?;
+    let range = value.strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
 
-    match get(url)
-        .and_then(|response| response.error_for_status())
-        .and_then(|response| response.bytes())
-    {
-        Ok(bytes) => {
-            info!("Successfully downloaded {} bytes from {url}", bytes.len());
-            Some(bytes.to_vec())
+/// Extracts the filename of a `Content-Disposition` header value.
+///
+/// The extended `filename*` parameter, percent-encoded in UTF-8, takes precedence over the
+/// `filename` parameter as specified by RFC 6266.
+pub fn content_disposition_filename(value: &str) -> Option<String> {
+    let mut filename = None;
+    for parameter in split_unquoted(value, ';').into_iter().skip(1) {
+        let Some((name, value)) = parameter.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                let mut parts = value.splitn(3, '\'');
+                let (Some(charset), Some(_language), Some(encoded)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                if charset.eq_ignore_ascii_case("utf-8")
+                    && let Some(decoded) = percent_decode(encoded)
+                {
+                    return Some(decoded);
+                }
+            }
+            "filename" => {
+                let unquoted = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                    Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+                    None => value.to_string(),
+                };
+                filename = Some(unquoted);
+            }
+            _ => {}
         }
-        Err(e) => {
-            error!("Failed to download from {url}: {e}");
-            None
+    }
+    filename
+}
+
+/// Returns the last segment of the path of a URL, percent-decoded, if it is not empty.
+pub fn url_filename(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let segment = url.path_segments()?.next_back()?;
+    percent_decode(segment).filter(|filename| !filename.is_empty())
+}
+
+/// Turns a filename received from a remote server into a name safe to create in a folder.
+///
+/// Only the last component of the name is kept, so that it cannot escape its folder, and
+/// control characters are dropped. Names made of dots only are rejected, and names longer
+/// than [`MAX_FILENAME_LENGTH`] bytes are truncated.
+///
+/// # Returns
+///
+/// * `Some(String)` with the sanitized filename.
+/// * `None` if nothing usable remains of the name.
+pub fn sanitize_filename(filename: &str) -> Option<String> {
+    let last_component = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let mut sanitized: String = last_component
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if sanitized.chars().all(|c| c == '.') {
+        return None;
+    }
+    if sanitized.len() > MAX_FILENAME_LENGTH {
+        let mut end = MAX_FILENAME_LENGTH;
+        while !sanitized.is_char_boundary(end) {
+            end -= 1;
         }
+        sanitized.truncate(end);
     }
+    Some(sanitized)
+}
+
+/// Splits `value` on `separator`, ignoring separators in double-quoted strings.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+/// Decodes a percent-encoded UTF-8 string, returning `None` if it is malformed.
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = value.get(index + 1..index + 3)?;
+            if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Read;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
     use tempfile::TempDir;
     use testcontainers::core::WaitFor;
     use testcontainers::runners::SyncRunner;
     use testcontainers::{Container, GenericImage};
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{header, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     const EXPECTED_DATA_PATH: &str = "src/tests_resources/httpbin.json";
     const URL: &str = "https://httpbin.org/json";
     const PARENT_DIR: &str = "/tmp";
     const FILE_NAME: &str = "test.json";
+    const NO_BACKOFF: RetryPolicy = RetryPolicy {
+        max_attempts: 2,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
 
     fn assert_json_eq_from_file(actual: &[u8], file_path: &str) {
         let expected_bytes =
@@ -286,6 +922,68 @@ mod tests {
 
         let _ = fs::remove_file(&path);
     }
+
+    #[test]
+    fn download_file_with_retry_succeeds_after_transient_error() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mock_server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/file"))
+                .respond_with(ResponseTemplate::new(503))
+                .up_to_n_times(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/file"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(b"content"))
+                .mount(&server)
+                .await;
+            server
+        });
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = download_file_with_retry(
+            &format!("{}/file", mock_server.uri()),
+            temp_dir.path().to_str().unwrap(),
+            FILE_NAME,
+            &NO_BACKOFF,
+        );
+
+        assert_eq!(fs::read(result.unwrap()).unwrap(), b"content");
+    }
+
+    #[test]
+    fn download_file_with_retry_reports_attempts_when_all_fail() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mock_server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/file"))
+                .respond_with(ResponseTemplate::new(503))
+                .expect(2)
+                .mount(&server)
+                .await;
+            server
+        });
+        let temp_dir = TempDir::new().unwrap();
+        let url = format!("{}/file", mock_server.uri());
+
+        let result = download_file_with_retry(
+            &url,
+            temp_dir.path().to_str().unwrap(),
+            FILE_NAME,
+            &NO_BACKOFF,
+        );
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                " after 2 attempts: [1] {url}: HTTP 503 Service Unavailable; [2] {url}: HTTP 503 Service Unavailable"
+            )
+        );
+        assert!(!temp_dir.path().join(FILE_NAME).exists());
+    }
     // endregion
 
     // region download_from_url
@@ -334,34 +1032,54 @@ mod tests {
     }
 
     #[test]
-    #[cfg(unix)]
-    fn download_from_url_fails_and_file_deleted_when_write_partialy_fails() {
-        use std::os::unix::fs::PermissionsExt;
-        let (_container, container_url) = start_container();
+    fn test_download_from_url_with_server_error() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
 
-        let temp_dir = TempDir::new().unwrap();
-        let parent_path = temp_dir.path();
-        let file_path = parent_path.join("test.json");
+        let mock_server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/error"))
+                .respond_with(ResponseTemplate::new(500))
+                .mount(&server)
+                .await;
+            server
+        });
 
-        // Pre-create a read-only file to force write failure
-        fs::write(&file_path, b"old content").unwrap();
-        let mut perms = fs::metadata(&file_path).unwrap().permissions();
-        perms.set_mode(0o444);
-        fs::set_permissions(&file_path, perms).unwrap();
+        let server_uri = mock_server.uri();
+        let result = download_from_url(&format!("{server_uri}/error"));
 
-        let result = download_file(&container_url, parent_path.to_str().unwrap(), "test.json");
         assert!(result.is_none());
+    }
 
-        // Parent directory should still exist. File should be deleted on cleanup
-        assert!(parent_path.exists());
-        assert!(
-            !file_path.exists(),
-            "File should have been cleaned up after write failure"
-        );
+    #[test]
+    fn test_download_stream_from_url_success() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let mock_server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/stream"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(b"streamed content"))
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let mut response = download_stream_from_url(&format!("{}/stream", mock_server.uri()))
+            .expect("Stream should be opened");
+        let mut content = Vec::new();
+        response.read_to_end(&mut content).unwrap();
+
+        assert_eq!(content, b"streamed content");
     }
 
     #[test]
-    fn test_download_from_url_with_server_error() {
+    fn test_download_stream_from_url_with_empty_url() {
+        assert!(download_stream_from_url("").is_none());
+    }
+
+    #[test]
+    fn test_download_stream_from_url_with_server_error() {
         let rt = tokio::runtime::Runtime::new().unwrap();
 
         let mock_server = rt.block_on(async {
@@ -374,11 +1092,258 @@ mod tests {
             server
         });
 
-        let server_uri = mock_server.uri();
-        let result = download_from_url(&format!("{server_uri}/error"));
+        let result = download_stream_from_url(&format!("{}/error", mock_server.uri()));
 
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_open_download_stream_with_accept_success() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let mock_server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/car"))
+                .and(header("accept", "application/vnd.ipld.car; version=1"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-type", "application/vnd.ipld.car; version=1")
+                        .set_body_bytes(b"car content"),
+                )
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let options = DownloadOptions {
+            accept: Some("application/vnd.ipld.car; version=1".to_string()),
+            ..DownloadOptions::default()
+        };
+        let mut response = open_download_stream(&format!("{}/car", mock_server.uri()), &options)
+            .expect("Stream should be opened");
+        let mut content = Vec::new();
+        response.read_to_end(&mut content).unwrap();
+
+        assert_eq!(content, b"car content");
+    }
+
+    #[test]
+    fn test_open_download_stream_with_accept_fails_when_content_type_differs() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let mock_server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/car"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-type", "application/octet-stream")
+                        .set_body_bytes(b"raw content"),
+                )
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let options = DownloadOptions {
+            accept: Some("application/vnd.ipld.car; version=1".to_string()),
+            ..DownloadOptions::default()
+        };
+        let url = format!("{}/car", mock_server.uri());
+        let result = open_download_stream(&url, &options);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                " after 1 attempt: [1] {url}: unexpected content type application/octet-stream"
+            )
+        );
+    }
+
+    #[test]
+    fn test_open_download_stream_retries_when_server_too_slow() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let mock_server = rt.block_on(async {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/slow"))
+                .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+                .mount(&server)
+                .await;
+            server
+        });
+
+        let options = DownloadOptions {
+            timeout: Some(Duration::from_millis(200)),
+            retry_policy: NO_BACKOFF,
+            ..DownloadOptions::default()
+        };
+        let result = open_download_stream(&format!("{}/slow", mock_server.uri()), &options);
+
+        assert_eq!(result.unwrap_err().len(), 2);
+    }
+    // endregion
+
+    // region DownloadStream
+    const RANGE_CONTENT_LENGTH: usize = 1000;
+    const RANGE_DROP_POSITION: usize = 400;
+
+    fn range_content() -> Vec<u8> {
+        (0..RANGE_CONTENT_LENGTH).map(|i| i as u8).collect()
+    }
+
+    fn raw_response(status: &str, headers: &[&str], body: &[u8], content_length: usize) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {status}\r\ncontent-length: {content_length}\r\n");
+        for header in headers {
+            response.push_str(&format!("{header}\r\n"));
+        }
+        response.push_str("connection: close\r\n\r\n");
+        let mut response = response.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    /// Answers each connection with the next raw HTTP response, then closes it.
+    ///
+    /// Responses shorter than their `content-length` simulate a dropped connection.
+    fn start_raw_server(responses: Vec<Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded_requests = requests.clone();
+        thread::spawn(move || {
+            for response in responses {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut request = Vec::new();
+                let mut byte = [0u8; 1];
+                while !request.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap_or(0) == 1 {
+                    request.push(byte[0]);
+                }
+                recorded_requests
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&request).to_lowercase());
+                let _ = stream.write_all(&response);
+            }
+        });
+        (url, requests)
+    }
+
+    fn first_range_response(headers: &[&str]) -> Vec<u8> {
+        raw_response(
+            "200 OK",
+            headers,
+            &range_content()[..RANGE_DROP_POSITION],
+            RANGE_CONTENT_LENGTH,
+        )
+    }
+
+    #[test]
+    fn download_file_resumes_after_dropped_connection() {
+        let content = range_content();
+        let (url, requests) = start_raw_server(vec![
+            first_range_response(&["accept-ranges: bytes", "etag: \"v1\""]),
+            raw_response(
+                "206 Partial Content",
+                &[
+                    "accept-ranges: bytes",
+                    "etag: \"v1\"",
+                    "content-range: bytes 400-999/1000",
+                ],
+                &content[RANGE_DROP_POSITION..],
+                RANGE_CONTENT_LENGTH - RANGE_DROP_POSITION,
+            ),
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = download_file_with_retry(
+            &url,
+            temp_dir.path().to_str().unwrap(),
+            FILE_NAME,
+            &NO_BACKOFF,
+        );
+
+        assert_eq!(fs::read(result.unwrap()).unwrap(), content);
+        assert!(!temp_dir.path().join("test.json.part").exists());
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("range: bytes=400-\r\n"));
+        assert!(requests[1].contains("if-range: \"v1\"\r\n"));
+    }
+
+    #[test]
+    fn download_file_resumes_with_last_modified_when_etag_is_weak() {
+        let content = range_content();
+        let last_modified = "last-modified: Wed, 21 Oct 2015 07:28:00 GMT";
+        let (url, requests) = start_raw_server(vec![
+            first_range_response(&["accept-ranges: bytes", "etag: W/\"v1\"", last_modified]),
+            raw_response(
+                "206 Partial Content",
+                &["content-range: bytes 400-999/1000"],
+                &content[RANGE_DROP_POSITION..],
+                RANGE_CONTENT_LENGTH - RANGE_DROP_POSITION,
+            ),
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = download_file_with_retry(
+            &url,
+            temp_dir.path().to_str().unwrap(),
+            FILE_NAME,
+            &NO_BACKOFF,
+        );
+
+        assert_eq!(fs::read(result.unwrap()).unwrap(), content);
+        assert!(
+            requests.lock().unwrap()[1].contains("if-range: wed, 21 oct 2015 07:28:00 gmt\r\n")
+        );
+    }
+
+    #[test]
+    fn download_file_fails_when_content_changed_before_resuming() {
+        let (url, _requests) = start_raw_server(vec![
+            first_range_response(&["accept-ranges: bytes", "etag: \"v1\""]),
+            raw_response(
+                "200 OK",
+                &["accept-ranges: bytes", "etag: \"v2\""],
+                &[0u8; RANGE_CONTENT_LENGTH],
+                RANGE_CONTENT_LENGTH,
+            ),
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = download_file_with_retry(
+            &url,
+            temp_dir.path().to_str().unwrap(),
+            FILE_NAME,
+            &NO_BACKOFF,
+        );
+
+        assert!(result.is_err());
+        assert!(!temp_dir.path().join(FILE_NAME).exists());
+        assert!(!temp_dir.path().join("test.json.part").exists());
+    }
+
+    #[test]
+    fn download_file_does_not_resume_without_range_support() {
+        let (url, requests) = start_raw_server(vec![first_range_response(&["etag: \"v1\""])]);
+        let temp_dir = TempDir::new().unwrap();
+
+        let result = download_file_with_retry(
+            &url,
+            temp_dir.path().to_str().unwrap(),
+            FILE_NAME,
+            &NO_BACKOFF,
+        );
+
+        assert!(result.is_err());
+        assert!(!temp_dir.path().join("test.json.part").exists());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
     // endregion
 
     // region write_file
@@ -418,4 +1383,220 @@ mod tests {
         assert_eq!(data, content2);
     }
     // endregion
+
+    // region write_stream
+    #[test]
+    fn test_write_stream_success() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test_write_stream.txt");
+        let mut content: &[u8] = b"hello streamed world!";
+        let result = write_stream(&mut content, &file_path, "test_write_stream_success");
+        assert_eq!(result.unwrap(), 21);
+        assert_eq!(fs::read(&file_path).unwrap(), b"hello streamed world!");
+    }
+
+    #[test]
+    fn test_write_stream_deletes_file_when_reader_fails() {
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("connection reset"))
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test_write_stream.txt");
+        let result = write_stream(&mut FailingReader, &file_path, "test_write_stream_failure");
+        assert!(result.is_err());
+        assert!(!file_path.exists());
+    }
+
+    #[test]
+    fn save_stream_keeps_existing_file_when_reader_fails() {
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("connection reset"))
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let parent_dir = temp_dir.path().to_str().unwrap();
+        let file_path = temp_dir.path().join("test.json");
+        fs::write(&file_path, b"old content").unwrap();
+
+        let result = save_stream(&mut FailingReader, parent_dir, "test.json", "test");
+        assert_eq!(result.unwrap_err().to_string(), "connection reset");
+        assert_eq!(fs::read(&file_path).unwrap(), b"old content");
+        assert!(!temp_dir.path().join("test.json.part").exists());
+    }
+
+    #[test]
+    fn save_stream_fails_and_removes_partial_file_when_rename_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let parent_dir = temp_dir.path().to_str().unwrap();
+        let file_path = temp_dir.path().join("test.json");
+        fs::create_dir_all(file_path.join("not-empty")).unwrap();
+
+        let mut content: &[u8] = b"new content";
+        let result = save_stream(&mut content, parent_dir, "test.json", "test");
+        assert!(result.is_err());
+        assert!(file_path.join("not-empty").is_dir());
+        assert!(!temp_dir.path().join("test.json.part").exists());
+    }
+    // endregion
+
+    // region filenames
+    #[test]
+    fn content_disposition_filename_reads_filename_parameters() {
+        let test_cases = [
+            ("attachment; filename=report.pdf", Some("report.pdf")),
+            (
+                r#"attachment; filename="my report; v2.pdf""#,
+                Some("my report; v2.pdf"),
+            ),
+            (
+                r#"attachment; filename="quoted \"name\".txt""#,
+                Some(r#"quoted "name".txt"#),
+            ),
+            (
+                "attachment; filename=\"fallback.txt\"; filename*=UTF-8''r%C3%A9sum%C3%A9.txt",
+                Some("résumé.txt"),
+            ),
+            ("attachment; filename*=ISO-8859-1''caf%E9.txt", None),
+            ("inline", None),
+        ];
+        for (value, expected) in test_cases {
+            assert_eq!(
+                content_disposition_filename(value).as_deref(),
+                expected,
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn url_filename_returns_last_decoded_path_segment() {
+        assert_eq!(
+            url_filename("https://iex.ec/data/my%20file.csv?version=2").as_deref(),
+            Some("my file.csv")
+        );
+        assert_eq!(url_filename("https://iex.ec/data/"), None);
+        assert_eq!(url_filename("https://iex.ec"), None);
+        assert_eq!(url_filename("https://iex.ec/%ZZ"), None);
+        assert_eq!(url_filename("not-a-url"), None);
+    }
+
+    #[test]
+    fn sanitize_filename_keeps_filename_inside_its_folder() {
+        let test_cases = [
+            ("report.pdf", Some("report.pdf")),
+            ("../../etc/passwd", Some("passwd")),
+            ("/absolute/path.txt", Some("path.txt")),
+            ("..\\..\\windows.ini", Some("windows.ini")),
+            ("  na\u{0}me\n.txt ", Some("name.txt")),
+            ("..", None),
+            ("dir/", None),
+            ("", None),
+        ];
+        for (filename, expected) in test_cases {
+            assert_eq!(
+                sanitize_filename(filename).as_deref(),
+                expected,
+                "{filename}"
+            );
+        }
+    }
+
+    #[test]
+    fn sanitize_filename_truncates_long_filenames() {
+        let sanitized = sanitize_filename(&"é".repeat(200)).unwrap();
+        assert_eq!(sanitized, "é".repeat(127));
+    }
+    // endregion
+
+    // region secure_delete
+    #[test]
+    fn secure_delete_removes_files_and_directories_recursively() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("extracted");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.txt"), "secret").unwrap();
+        fs::write(
+            dir.join("nested").join("b.txt"),
+            vec![1u8; 3 * 64 * 1024 + 1],
+        )
+        .unwrap();
+        let file = temp_dir.path().join("plain.txt");
+        fs::write(&file, "secret").unwrap();
+
+        assert!(secure_delete(&dir).is_ok());
+        assert!(secure_delete(&file).is_ok());
+
+        assert!(!dir.exists());
+        assert!(!file.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn secure_delete_does_not_follow_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target.txt");
+        fs::write(&target, "kept").unwrap();
+        let link = temp_dir.path().join("link.txt");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(secure_delete(&link).is_ok());
+
+        assert!(fs::symlink_metadata(&link).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "kept");
+    }
+
+    #[test]
+    fn secure_delete_ignores_missing_path() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(secure_delete(&temp_dir.path().join("missing")).is_ok());
+    }
+    // endregion
+
+    // region SafeFilename
+    #[test]
+    fn safe_filename_accepts_plain_filenames() {
+        for filename in [
+            "dataset.txt",
+            "0xDatasetAddress",
+            ".hidden",
+            "name with spaces.csv",
+        ] {
+            assert_eq!(
+                SafeFilename::from_str(filename).map(|safe| safe.to_string()),
+                Ok(filename.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn safe_filename_rejects_filenames_escaping_their_folder() {
+        let test_cases = [
+            ("", FilenameError::Empty),
+            ("data\0set.txt", FilenameError::NulByte),
+            ("/etc/passwd", FilenameError::Absolute),
+            ("\\windows\\system.ini", FilenameError::Absolute),
+            ("..", FilenameError::ParentComponent),
+            ("../dataset.txt", FilenameError::ParentComponent),
+            ("data\\..\\..\\dataset.txt", FilenameError::ParentComponent),
+            (".", FilenameError::Reserved),
+            ("data/dataset.txt", FilenameError::PathSeparator),
+            ("dataset.txt/", FilenameError::PathSeparator),
+            (INPUTS_MANIFEST_FILENAME, FilenameError::Reserved),
+        ];
+        for (filename, expected) in test_cases {
+            assert_eq!(
+                SafeFilename::from_str(filename),
+                Err(expected),
+                "{filename:?}"
+            );
+        }
+    }
+    // endregion
 }
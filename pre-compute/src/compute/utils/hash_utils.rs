@@ -1,5 +1,13 @@
+use log::error;
 use sha3::{Digest, Keccak256};
 use sha256::digest;
+use std::fmt;
+use std::io::{self, ErrorKind, Read, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Length in bytes of the digests of the supported [`HashAlgorithm`]s.
+const DIGEST_LENGTH: usize = 32;
 
 pub fn concatenate_and_hash(hexa_strings: &[&str]) -> String {
     let mut hasher = Keccak256::default();
@@ -17,7 +25,7 @@ pub fn hex_string_to_byte_array(input: &str) -> Vec<u8> {
     }
 
     let mut data: Vec<u8> = vec![];
-    let start_idx = if len % 2 != 0 {
+    let start_idx = if !len.is_multiple_of(2) {
         let byte = u8::from_str_radix(&clean_input[0..1], 16).expect("");
         data.push(byte);
         1
@@ -44,6 +52,234 @@ pub fn sha256_from_bytes(bytes: &[u8]) -> String {
     format!("0x{}", digest(bytes))
 }
 
+/// Hash algorithm of a [`Checksum`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Keccak256,
+}
+
+impl HashAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Keccak256 => "keccak256",
+        }
+    }
+}
+
+/// Expected digest of some content.
+///
+/// A checksum is written `<algorithm>:<digest>`, where the algorithm is `sha256` or
+/// `keccak256` and the digest is 32 hex-encoded bytes, optionally prefixed with `0x`.
+/// A digest without algorithm is a SHA-256 digest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checksum {
+    pub algorithm: HashAlgorithm,
+    /// Lowercase hex-encoded digest, prefixed with `0x`.
+    pub digest: String,
+}
+
+impl FromStr for Checksum {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (algorithm, digest) = match s.trim().split_once(':') {
+            Some((name, digest)) => match name.to_ascii_lowercase().as_str() {
+                "sha256" => (HashAlgorithm::Sha256, digest),
+                "keccak256" => (HashAlgorithm::Keccak256, digest),
+                _ => return Err(format!("unsupported hash algorithm {name}")),
+            },
+            None => (HashAlgorithm::Sha256, s.trim()),
+        };
+        let digest = clean_hex_prefix(digest);
+        if digest.len() != 2 * DIGEST_LENGTH || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "digest must be {DIGEST_LENGTH} hex-encoded bytes, got {digest}"
+            ));
+        }
+        Ok(Checksum {
+            algorithm,
+            digest: format!("0x{}", digest.to_ascii_lowercase()),
+        })
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm.name(), self.digest)
+    }
+}
+
+/// Incremental hasher computing the digest of a content for a given [`HashAlgorithm`].
+pub enum ChecksumHasher {
+    Sha256(sha2::Sha256),
+    Keccak256(Box<Keccak256>),
+}
+
+impl ChecksumHasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha256 => ChecksumHasher::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Keccak256 => ChecksumHasher::Keccak256(Box::new(Keccak256::new())),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            ChecksumHasher::Sha256(hasher) => hasher.update(data),
+            ChecksumHasher::Keccak256(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Returns the lowercase hex-encoded digest, prefixed with `0x`.
+    pub fn finalize(self) -> String {
+        match self {
+            ChecksumHasher::Sha256(hasher) => format!("0x{:x}", hasher.finalize()),
+            ChecksumHasher::Keccak256(hasher) => format!("0x{:x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Reader computing the digest and the size of the content read from an inner reader.
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: ChecksumHasher,
+    algorithm: HashAlgorithm,
+    size: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R, algorithm: HashAlgorithm) -> Self {
+        HashingReader {
+            inner,
+            hasher: ChecksumHasher::new(algorithm),
+            algorithm,
+            size: 0,
+        }
+    }
+
+    /// Returns the checksum and the size of the content read so far.
+    pub fn finalize(self) -> (Checksum, u64) {
+        let checksum = Checksum {
+            algorithm: self.algorithm,
+            digest: self.hasher.finalize(),
+        };
+        (checksum, self.size)
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        self.size += read as u64;
+        Ok(read)
+    }
+}
+
+/// Writer computing the digest and the size of the content written to an inner writer.
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: ChecksumHasher,
+    algorithm: HashAlgorithm,
+    size: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W, algorithm: HashAlgorithm) -> Self {
+        HashingWriter {
+            inner,
+            hasher: ChecksumHasher::new(algorithm),
+            algorithm,
+            size: 0,
+        }
+    }
+
+    /// Returns the checksum and the size of the content written so far.
+    pub fn finalize(self) -> (Checksum, u64) {
+        let checksum = Checksum {
+            algorithm: self.algorithm,
+            digest: self.hasher.finalize(),
+        };
+        (checksum, self.size)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Error raised when some content does not match its expected [`Checksum`].
+///
+/// It is wrapped in an [`io::Error`] of kind [`ErrorKind::InvalidData`] by
+/// [`ChecksumVerifyingReader`], see [`is_checksum_mismatch`].
+#[derive(Debug, Error)]
+#[error("checksum mismatch: expected {expected}, got {actual}")]
+pub struct ChecksumMismatchError {
+    expected: String,
+    actual: String,
+}
+
+/// Returns `true` if the I/O error was raised because content did not match its checksum.
+pub fn is_checksum_mismatch(e: &io::Error) -> bool {
+    e.get_ref()
+        .is_some_and(|inner| inner.is::<ChecksumMismatchError>())
+}
+
+/// Reader hashing the content of an inner reader and checking it against an expected [`Checksum`].
+///
+/// Content is passed through as it is read. Once the inner reader reaches its end, the digest
+/// of the whole content is compared with the expected one and, if they differ, the read fails
+/// with an error for which [`is_checksum_mismatch`] returns `true`. Consumers must therefore
+/// read the content to its end before using it.
+pub struct ChecksumVerifyingReader<R> {
+    inner: R,
+    hasher: Option<ChecksumHasher>,
+    expected: Checksum,
+}
+
+impl<R: Read> ChecksumVerifyingReader<R> {
+    pub fn new(inner: R, expected: Checksum) -> Self {
+        ChecksumVerifyingReader {
+            inner,
+            hasher: Some(ChecksumHasher::new(expected.algorithm)),
+            expected,
+        }
+    }
+}
+
+impl<R: Read> Read for ChecksumVerifyingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            if let Some(hasher) = self.hasher.as_mut() {
+                hasher.update(&buf[..read]);
+            }
+        } else if let Some(hasher) = self.hasher.take() {
+            let actual = hasher.finalize();
+            if actual != self.expected.digest {
+                let error = ChecksumMismatchError {
+                    expected: self.expected.to_string(),
+                    actual: format!("{}:{actual}", self.expected.algorithm.name()),
+                };
+                error!("Content verification failed [{error}]");
+                return Err(io::Error::new(ErrorKind::InvalidData, error));
+            }
+        }
+        Ok(read)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +337,98 @@ mod tests {
             sha256(String::from("utf8String"))
         )
     }
+
+    // region Checksum
+    const CONTENT: &[u8] = b"utf8String";
+    const CONTENT_SHA256: &str =
+        "0xb33845db05fb0822f1f1e3677cc6787b8a1a7a21f3c12f9e97c70cb596222218";
+
+    fn keccak256_of(content: &[u8]) -> String {
+        format!("0x{:x}", Keccak256::digest(content))
+    }
+
+    #[test]
+    fn checksum_from_str_defaults_to_sha256() {
+        let checksum = Checksum::from_str(&CONTENT_SHA256[2..]).unwrap();
+        assert_eq!(checksum.algorithm, HashAlgorithm::Sha256);
+        assert_eq!(checksum.digest, CONTENT_SHA256);
+    }
+
+    #[test]
+    fn checksum_from_str_reads_algorithm_and_normalizes_digest() {
+        let digest = keccak256_of(CONTENT);
+        let checksum =
+            Checksum::from_str(&format!("KECCAK256:{}", digest[2..].to_ascii_uppercase())).unwrap();
+        assert_eq!(checksum.algorithm, HashAlgorithm::Keccak256);
+        assert_eq!(checksum.digest, digest);
+        assert_eq!(checksum.to_string(), format!("keccak256:{digest}"));
+    }
+
+    #[test]
+    fn checksum_from_str_fails_with_invalid_values() {
+        for value in [
+            "",
+            "0x1234",
+            "md5:b33845db05fb0822f1f1e3677cc6787b8a1a7a21f3c12f9e97c70cb596222218",
+            "sha256:z33845db05fb0822f1f1e3677cc6787b8a1a7a21f3c12f9e97c70cb596222218",
+        ] {
+            assert!(
+                Checksum::from_str(value).is_err(),
+                "{value} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn checksum_verifying_reader_passes_matching_content_through() {
+        for checksum in [
+            format!("sha256:{CONTENT_SHA256}"),
+            format!("keccak256:{}", keccak256_of(CONTENT)),
+        ] {
+            let mut reader =
+                ChecksumVerifyingReader::new(CONTENT, Checksum::from_str(&checksum).unwrap());
+            let mut output = Vec::new();
+            reader.read_to_end(&mut output).unwrap();
+            assert_eq!(output, CONTENT);
+        }
+    }
+
+    #[test]
+    fn checksum_verifying_reader_fails_at_end_of_mismatching_content() {
+        let checksum = Checksum::from_str(CONTENT_SHA256).unwrap();
+        let mut reader = ChecksumVerifyingReader::new(&b"other content"[..], checksum);
+
+        let error = reader.read_to_end(&mut Vec::new()).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(is_checksum_mismatch(&error));
+        assert!(!is_checksum_mismatch(&io::Error::other("other")));
+    }
+
+    #[test]
+    fn hashing_reader_returns_checksum_and_size_of_content_read() {
+        let mut reader = HashingReader::new(CONTENT, HashAlgorithm::Sha256);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+
+        let (checksum, size) = reader.finalize();
+
+        assert_eq!(output, CONTENT);
+        assert_eq!(checksum.to_string(), format!("sha256:{CONTENT_SHA256}"));
+        assert_eq!(size, CONTENT.len() as u64);
+    }
+
+    #[test]
+    fn hashing_writer_returns_checksum_and_size_of_content_written() {
+        let mut output = Vec::new();
+        let mut writer = HashingWriter::new(&mut output, HashAlgorithm::Sha256);
+        writer.write_all(CONTENT).unwrap();
+
+        let (checksum, size) = writer.finalize();
+
+        assert_eq!(output, CONTENT);
+        assert_eq!(checksum.to_string(), format!("sha256:{CONTENT_SHA256}"));
+        assert_eq!(size, CONTENT.len() as u64);
+    }
+    // endregion
 }
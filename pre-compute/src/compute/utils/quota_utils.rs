@@ -0,0 +1,191 @@
+use log::error;
+use std::io::{self, ErrorKind, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+
+/// Limit exceeded by a download, see [`QuotaReader`].
+#[derive(Clone, Copy, Debug, Error, PartialEq)]
+pub enum SizeLimitError {
+    #[error("file exceeds the maximum file size of {0} bytes")]
+    FileTooLarge(u64),
+    #[error("downloads exceed the maximum total size of {0} bytes")]
+    QuotaExceeded(u64),
+}
+
+/// Returns the size limit exceeded if the I/O error was raised by a [`QuotaReader`].
+pub fn size_limit_error(e: &io::Error) -> Option<SizeLimitError> {
+    e.get_ref()
+        .and_then(|inner| inner.downcast_ref::<SizeLimitError>())
+        .copied()
+}
+
+/// Bounds the number of bytes downloaded by the pre-compute, per file and in total.
+///
+/// Clones share the count of bytes downloaded, so a single quota is used for all the datasets
+/// and input files of a task, whatever the number of workers downloading them.
+/// Limits are not enforced when unset.
+#[derive(Clone, Debug, Default)]
+pub struct DownloadQuota {
+    pub max_file_size: Option<u64>,
+    pub max_total_size: Option<u64>,
+    used: Arc<AtomicU64>,
+}
+
+impl DownloadQuota {
+    pub fn new(max_file_size: Option<u64>, max_total_size: Option<u64>) -> Self {
+        DownloadQuota {
+            max_file_size,
+            max_total_size,
+            used: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Returns a new quota with the same file size limit, and no total size limit.
+    ///
+    /// It bounds downloads whose content may be discarded, such as the losers of an IPFS
+    /// gateway race, which must not be counted in the total.
+    pub fn file_limit_only(&self) -> Self {
+        DownloadQuota::new(self.max_file_size, None)
+    }
+
+    /// Returns the number of bytes downloaded so far under this quota.
+    pub fn used(&self) -> u64 {
+        self.used.load(Ordering::SeqCst)
+    }
+
+    /// Wraps `inner` in a reader counting the bytes read against this quota.
+    ///
+    /// `content_length` is the size of the content announced by the server, if any.
+    pub fn reader<R: Read>(&self, inner: R, content_length: Option<u64>) -> QuotaReader<R> {
+        QuotaReader {
+            inner,
+            quota: self.clone(),
+            content_length,
+            read: 0,
+        }
+    }
+
+    fn check(&self, file_size: u64, total_size: u64) -> Result<(), SizeLimitError> {
+        if let Some(max_file_size) = self.max_file_size
+            && file_size > max_file_size
+        {
+            return Err(SizeLimitError::FileTooLarge(max_file_size));
+        }
+        if let Some(max_total_size) = self.max_total_size
+            && total_size > max_total_size
+        {
+            return Err(SizeLimitError::QuotaExceeded(max_total_size));
+        }
+        Ok(())
+    }
+}
+
+/// Reader failing as soon as the content read from an inner reader exceeds a [`DownloadQuota`].
+///
+/// The announced content length is checked on the first read, so that a content known to be
+/// too large is rejected before any byte is transferred, and the bytes actually read are counted
+/// on every read, so that a server announcing a wrong length or none at all cannot exceed the
+/// quota either. Errors can be identified with [`size_limit_error`].
+pub struct QuotaReader<R> {
+    inner: R,
+    quota: DownloadQuota,
+    content_length: Option<u64>,
+    read: u64,
+}
+
+impl<R: Read> Read for QuotaReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(content_length) = self.content_length.take() {
+            self.quota
+                .check(content_length, self.quota.used() + content_length)
+                .map_err(limit_exceeded)?;
+        }
+        let read = self.inner.read(buf)?;
+        self.read += read as u64;
+        let used = self.quota.used.fetch_add(read as u64, Ordering::SeqCst) + read as u64;
+        self.quota.check(self.read, used).map_err(limit_exceeded)?;
+        Ok(read)
+    }
+}
+
+fn limit_exceeded(e: SizeLimitError) -> io::Error {
+    error!("Download size limit exceeded [reason:{e}]");
+    io::Error::new(ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &[u8] = b"0123456789";
+
+    fn read_all<R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    #[test]
+    fn reader_passes_content_through_when_no_limit() {
+        let quota = DownloadQuota::default();
+
+        let content = read_all(quota.reader(CONTENT, Some(10))).unwrap();
+
+        assert_eq!(content, CONTENT);
+        assert_eq!(quota.used(), 10);
+    }
+
+    #[test]
+    fn reader_fails_before_reading_when_content_length_too_large() {
+        let quota = DownloadQuota::new(Some(5), None);
+
+        let error = read_all(quota.reader(CONTENT, Some(10))).unwrap_err();
+
+        assert_eq!(
+            size_limit_error(&error),
+            Some(SizeLimitError::FileTooLarge(5))
+        );
+        assert_eq!(quota.used(), 0);
+    }
+
+    #[test]
+    fn reader_fails_when_content_exceeds_announced_length() {
+        let quota = DownloadQuota::new(Some(5), None);
+
+        let error = read_all(quota.reader(CONTENT, Some(4))).unwrap_err();
+
+        assert_eq!(
+            size_limit_error(&error),
+            Some(SizeLimitError::FileTooLarge(5))
+        );
+    }
+
+    #[test]
+    fn reader_fails_when_total_size_exceeded_across_files() {
+        let quota = DownloadQuota::new(None, Some(15));
+
+        assert!(read_all(quota.reader(CONTENT, None)).is_ok());
+        let error = read_all(quota.clone().reader(CONTENT, None)).unwrap_err();
+
+        assert_eq!(
+            size_limit_error(&error),
+            Some(SizeLimitError::QuotaExceeded(15))
+        );
+    }
+
+    #[test]
+    fn file_limit_only_does_not_count_in_total() {
+        let quota = DownloadQuota::new(Some(10), Some(10));
+
+        assert!(read_all(quota.file_limit_only().reader(CONTENT, None)).is_ok());
+
+        assert_eq!(quota.used(), 0);
+        assert!(read_all(quota.reader(CONTENT, None)).is_ok());
+    }
+
+    #[test]
+    fn size_limit_error_returns_none_for_other_errors() {
+        assert_eq!(size_limit_error(&io::Error::other("other")), None);
+    }
+}
@@ -0,0 +1,425 @@
+use log::{error, warn};
+use rand::Rng;
+use reqwest::StatusCode;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::RETRY_AFTER;
+use std::fmt;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Number of attempts made for a download when none is configured.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+/// Delay before the first retry of a download when none is configured.
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound of the delay between two attempts when none is configured.
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Policy deciding how many times, and how far apart, a download is attempted.
+///
+/// Only transient failures are retried: connection errors, timeouts, and HTTP `408`, `429`
+/// and `5xx` responses. Any other failure ends the download immediately.
+///
+/// The delay before the `n`-th retry is drawn at random between half and all of
+/// `initial_backoff * 2^(n-1)`, capped at `max_backoff`, so that concurrent clients do not
+/// retry in lockstep. When the server answers with a `Retry-After` header, the delay it
+/// requests is used instead. A server asking to wait longer than `max_backoff` is not retried
+/// earlier than it asked: the download fails at once, with the requested delay recorded in the
+/// attempt history.
+///
+/// The policy is read from the task configuration by [`PreComputeArgs::read_args`].
+///
+/// [`PreComputeArgs::read_args`]: crate::compute::pre_compute_args::PreComputeArgs::read_args
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the jittered delay to wait before the given retry, starting at 1.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        let half = exponential / 2;
+        rand::thread_rng().gen_range(half..=exponential)
+    }
+}
+
+/// History of the failed attempts of a download, possibly spanning several URLs.
+///
+/// It is displayed as a suffix of the exit cause message, and is empty when no request
+/// could be sent at all, in which case nothing is displayed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DownloadAttempts(Vec<String>);
+
+impl DownloadAttempts {
+    /// Records the failure of an attempt to download `url`.
+    pub fn record(&mut self, url: &str, failure: &str) {
+        self.0.push(format!("{url}: {failure}"));
+    }
+
+    /// Appends the attempts of another download, e.g. the same content on another IPFS gateway.
+    pub fn extend(&mut self, other: DownloadAttempts) {
+        self.0.extend(other.0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for DownloadAttempts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let plural = if self.0.len() > 1 { "s" } else { "" };
+        write!(f, " after {} attempt{plural}: ", self.0.len())?;
+        for (index, attempt) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "[{}] {attempt}", index + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends `request`, retrying transient failures according to `retry_policy`.
+///
+/// Every failed attempt is logged and recorded in `attempts`. The response is returned as
+/// soon as its status is successful, without reading the body.
+///
+/// # Arguments
+///
+/// * `url` - The requested URL, for logging
+/// * `request` - The request to send, cloned for each attempt
+/// * `retry_policy` - The number of attempts and delays between them
+/// * `attempts` - The history the failed attempts are appended to
+///
+/// # Returns
+///
+/// * `Some(Response)` if an attempt succeeds with a successful status code.
+/// * `None` if a permanent failure occurs or every attempt fails.
+pub fn send_with_retry(
+    url: &str,
+    request: RequestBuilder,
+    retry_policy: &RetryPolicy,
+    attempts: &mut DownloadAttempts,
+) -> Option<Response> {
+    let (client, request) = request.build_split();
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => {
+            error!("Invalid download request [url:{url}, error:{e}]");
+            attempts.record(url, &describe_error(&e));
+            return None;
+        }
+    };
+    let max_attempts = retry_policy.max_attempts.max(1);
+    for attempt in 1..=max_attempts {
+        let Some(request) = request.try_clone() else {
+            error!("Request cannot be retried [url:{url}]");
+            return None;
+        };
+        let (failure, retry_after) = match client.execute(request) {
+            Ok(response) if response.status().is_success() => return Some(response),
+            Ok(response) => {
+                let failure = format!("HTTP {}", response.status());
+                if !is_transient_status(response.status()) {
+                    error!("Download failed with a permanent error [url:{url}, error:{failure}]");
+                    attempts.record(url, &failure);
+                    return None;
+                }
+                (failure, retry_after(&response))
+            }
+            Err(e) => {
+                let failure = describe_error(&e);
+                if !is_transient_error(&e) {
+                    error!("Download failed with a permanent error [url:{url}, error:{e}]");
+                    attempts.record(url, &failure);
+                    return None;
+                }
+                (failure, None)
+            }
+        };
+        if let Some(delay) = retry_after
+            && delay > retry_policy.max_backoff
+        {
+            error!(
+                "Download failed, server asks to retry later than the maximum backoff [url:{url}, error:{failure}, retryAfter:{}s]",
+                delay.as_secs()
+            );
+            attempts.record(
+                url,
+                &format!("{failure} (retry after {}s)", delay.as_secs()),
+            );
+            return None;
+        }
+        attempts.record(url, &failure);
+        if attempt == max_attempts {
+            break;
+        }
+        let delay = retry_after.unwrap_or_else(|| retry_policy.backoff(attempt));
+        warn!(
+            "Download attempt failed, retrying [url:{url}, attempt:{attempt}/{max_attempts}, error:{failure}, retryIn:{}ms]",
+            delay.as_millis()
+        );
+        thread::sleep(delay);
+    }
+    error!("Download failed after {max_attempts} attempts [url:{url}]");
+    None
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+fn is_transient_error(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_connect() || e.is_request()
+}
+
+fn describe_error(e: &reqwest::Error) -> String {
+    if e.is_timeout() {
+        "timed out".to_string()
+    } else if e.is_connect() {
+        "connection failed".to_string()
+    } else {
+        e.to_string()
+    }
+}
+
+/// Reads the `Retry-After` header, given either as a number of seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::blocking::Client;
+    use std::time::Instant;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const NO_BACKOFF: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    fn start_server(responses: Vec<ResponseTemplate>) -> (Runtime, MockServer) {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            let count = responses.len();
+            for (index, response) in responses.into_iter().enumerate() {
+                let mock = Mock::given(method("GET"))
+                    .and(path("/file"))
+                    .respond_with(response);
+                // The last response answers every remaining request.
+                let mock = if index + 1 < count {
+                    mock.up_to_n_times(1)
+                } else {
+                    mock
+                };
+                server.register(mock).await;
+            }
+            server
+        });
+        (rt, server)
+    }
+
+    fn send(server: &MockServer, policy: &RetryPolicy) -> (Option<Response>, DownloadAttempts) {
+        let url = format!("{}/file", server.uri());
+        let mut attempts = DownloadAttempts::default();
+        let response = send_with_retry(&url, Client::new().get(&url), policy, &mut attempts);
+        (response, attempts)
+    }
+
+    // region RetryPolicy
+    #[test]
+    fn backoff_grows_exponentially_with_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        };
+        for (retry, max) in [(1, 100), (2, 200), (3, 400)] {
+            let backoff = policy.backoff(retry);
+            assert!(backoff >= Duration::from_millis(max / 2));
+            assert!(backoff <= Duration::from_millis(max));
+        }
+    }
+
+    #[test]
+    fn backoff_is_capped_by_max_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 50,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        assert!(policy.backoff(40) <= Duration::from_secs(1));
+    }
+    // endregion
+
+    // region DownloadAttempts
+    #[test]
+    fn download_attempts_display_is_empty_without_attempts() {
+        assert_eq!(DownloadAttempts::default().to_string(), "");
+    }
+
+    #[test]
+    fn download_attempts_display_lists_attempts_in_order() {
+        let mut attempts = DownloadAttempts::default();
+        attempts.record("https://a", "HTTP 503 Service Unavailable");
+        attempts.record("https://b", "timed out");
+        assert_eq!(
+            attempts.to_string(),
+            " after 2 attempts: [1] https://a: HTTP 503 Service Unavailable; [2] https://b: timed out"
+        );
+    }
+    // endregion
+
+    // region send_with_retry
+    #[test]
+    fn send_with_retry_records_invalid_request() {
+        let mut attempts = DownloadAttempts::default();
+
+        let response = send_with_retry(
+            "not-a-url",
+            Client::new().get("not-a-url"),
+            &NO_BACKOFF,
+            &mut attempts,
+        );
+
+        assert!(response.is_none());
+        assert_eq!(attempts.len(), 1);
+    }
+
+    #[test]
+    fn send_with_retry_succeeds_after_transient_errors() {
+        let (_rt, server) = start_server(vec![
+            ResponseTemplate::new(503),
+            ResponseTemplate::new(500),
+            ResponseTemplate::new(200).set_body_string("content"),
+        ]);
+
+        let (response, attempts) = send(&server, &NO_BACKOFF);
+
+        assert_eq!(response.unwrap().text().unwrap(), "content");
+        assert_eq!(attempts.len(), 2);
+    }
+
+    #[test]
+    fn send_with_retry_fails_when_all_attempts_fail() {
+        let (_rt, server) = start_server(vec![ResponseTemplate::new(502)]);
+
+        let (response, attempts) = send(&server, &NO_BACKOFF);
+
+        assert!(response.is_none());
+        let url = format!("{}/file", server.uri());
+        assert_eq!(
+            attempts.to_string(),
+            format!(
+                " after 3 attempts: [1] {url}: HTTP 502 Bad Gateway; [2] {url}: HTTP 502 Bad Gateway; [3] {url}: HTTP 502 Bad Gateway"
+            )
+        );
+    }
+
+    #[test]
+    fn send_with_retry_does_not_retry_permanent_errors() {
+        let (_rt, server) =
+            start_server(vec![ResponseTemplate::new(404), ResponseTemplate::new(200)]);
+
+        let (response, attempts) = send(&server, &NO_BACKOFF);
+
+        assert!(response.is_none());
+        assert_eq!(attempts.len(), 1);
+    }
+
+    #[test]
+    fn send_with_retry_honors_retry_after() {
+        let (_rt, server) = start_server(vec![
+            ResponseTemplate::new(429).insert_header("Retry-After", "1"),
+            ResponseTemplate::new(200),
+        ]);
+        let policy = RetryPolicy {
+            max_backoff: Duration::from_secs(5),
+            ..NO_BACKOFF
+        };
+
+        let start = Instant::now();
+        let (response, attempts) = send(&server, &policy);
+
+        assert!(response.is_some());
+        assert_eq!(attempts.len(), 1);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn send_with_retry_fails_when_retry_after_exceeds_max_backoff() {
+        let (_rt, server) = start_server(vec![
+            ResponseTemplate::new(503).insert_header("Retry-After", "3600"),
+            ResponseTemplate::new(200),
+        ]);
+
+        let start = Instant::now();
+        let (response, attempts) = send(&server, &NO_BACKOFF);
+
+        assert!(response.is_none());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            attempts.to_string(),
+            format!(
+                " after 1 attempt: [1] {}/file: HTTP 503 Service Unavailable (retry after 3600s)",
+                server.uri()
+            )
+        );
+    }
+
+    #[test]
+    fn send_with_retry_retries_connection_errors() {
+        let url = "http://127.0.0.1:1/file";
+        let mut attempts = DownloadAttempts::default();
+
+        let response = send_with_retry(url, Client::new().get(url), &NO_BACKOFF, &mut attempts);
+
+        assert!(response.is_none());
+        assert_eq!(
+            attempts.to_string(),
+            format!(
+                " after 3 attempts: [1] {url}: connection failed; [2] {url}: connection failed; [3] {url}: connection failed"
+            )
+        );
+    }
+    // endregion
+}
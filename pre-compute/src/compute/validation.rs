@@ -0,0 +1,605 @@
+use crate::compute::dataset::{AES_KEY_LENGTH, Dataset, is_multi_address, parse_dataset_checksum};
+use crate::compute::errors::ReplicateStatusCause;
+use crate::compute::input_file::InputFile;
+use crate::compute::pre_compute_args::PreComputeArgs;
+use crate::compute::source::{LocalSourceResolver, is_local_source};
+use crate::compute::utils::cid_utils::cid_from_multi_address;
+use crate::compute::utils::env_utils::{TeeSessionEnvironmentVariable, get_env_var_or_error};
+use crate::compute::utils::secret_utils::SecretBytes;
+use base64::{Engine as _, engine::general_purpose};
+use log::{error, info};
+use reqwest::Url;
+use reqwest::blocking::Client;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Maximum duration of a reachability request to a dataset or input file URL.
+pub const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of a single check of a [`ValidationReport`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    /// What was found, or why the check failed.
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, result: Result<String, String>) -> Self {
+        Check {
+            name,
+            ok: result.is_ok(),
+            detail: result.unwrap_or_else(|e| e),
+        }
+    }
+}
+
+/// Checks of a dataset or an input file of a [`ValidationReport`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ItemReport {
+    pub name: String,
+    pub url: String,
+    pub checks: Vec<Check>,
+}
+
+impl ItemReport {
+    fn is_valid(&self) -> bool {
+        self.checks.iter().all(|check| check.ok)
+    }
+}
+
+/// Structured report of a validate-only run of the pre-compute, see [`validate_configuration`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    /// Whether the pre-compute is expected to succeed with this configuration.
+    pub valid: bool,
+    /// Exit causes the pre-compute would report while reading its configuration.
+    pub exit_causes: Vec<ReplicateStatusCause>,
+    pub output_folder: Check,
+    pub datasets: Vec<ItemReport>,
+    pub input_files: Vec<ItemReport>,
+}
+
+/// Validates the configuration of the pre-compute without downloading or decrypting anything.
+///
+/// The configuration is read like [`PreComputeApp::run`] reads it, except that dataset keys
+/// held by an SMS are not retrieved, see [`PreComputeArgs::read_args_without_sms_keys`], then:
+/// - the output folder must exist,
+/// - dataset and input file URLs must be HTTP(S) URLs, IPFS multi-addresses holding a
+///   verifiable CID, or local paths when local sources are enabled,
+/// - dataset keys must be Base64-encoded AES-256 keys, unless held by an SMS, and dataset
+///   checksums SHA-256 digests, see [`parse_dataset_checksum`],
+/// - valid URLs must answer a `HEAD` request with a successful status within
+///   [`REACHABILITY_TIMEOUT`], multi-addresses being tried on every IPFS gateway until one
+///   answers.
+///
+/// Every check is run whatever the outcome of the others, so that the report lists every
+/// problem at once.
+///
+/// [`PreComputeApp::run`]: crate::compute::pre_compute_app::PreComputeAppTrait::run
+pub fn validate_configuration() -> ValidationReport {
+    info!("Validating pre-compute configuration");
+    let (mut args, mut exit_causes) = PreComputeArgs::read_args_without_sms_keys();
+    let output_folder = match get_env_var_or_error(
+        TeeSessionEnvironmentVariable::IexecPreComputeOut,
+        ReplicateStatusCause::PreComputeOutputPathMissing,
+    ) {
+        Ok(output_dir) => {
+            args.output_dir = output_dir;
+            Check::new("output_folder", check_output_folder(&args.output_dir))
+        }
+        Err(e) => {
+            exit_causes.insert(0, e);
+            Check::new("output_folder", Err("not configured".to_string()))
+        }
+    };
+    // Reachability checks fail when no HTTP client can be built, invalidating the report.
+    let client = Client::builder()
+        .timeout(REACHABILITY_TIMEOUT)
+        .build()
+        .map_err(|e| {
+            error!("Failed to build HTTP client [error:{e}]");
+            format!("HTTP client unavailable: {e}")
+        });
+
+    let datasets: Vec<ItemReport> = args
+        .datasets
+        .iter()
+        .map(|dataset| validate_dataset(&client, dataset, &args))
+        .collect();
+    let mut input_files: Vec<ItemReport> = Vec::new();
+    for input_file in &args.input_files {
+        if !input_files
+            .iter()
+            .any(|report| report.url == input_file.url)
+        {
+            input_files.push(validate_input_file(&client, input_file, &args));
+        }
+    }
+
+    let valid = exit_causes.is_empty()
+        && client.is_ok()
+        && output_folder.ok
+        && datasets.iter().all(ItemReport::is_valid)
+        && input_files.iter().all(ItemReport::is_valid);
+    info!("Pre-compute configuration validated [valid:{valid}]");
+    ValidationReport {
+        valid,
+        exit_causes,
+        output_folder,
+        datasets,
+        input_files,
+    }
+}
+
+fn check_output_folder(output_dir: &str) -> Result<String, String> {
+    if Path::new(output_dir).is_dir() {
+        Ok(output_dir.to_string())
+    } else {
+        Err(format!("{output_dir} is not a directory"))
+    }
+}
+
+fn validate_dataset(
+    client: &Result<Client, String>,
+    dataset: &Dataset,
+    args: &PreComputeArgs,
+) -> ItemReport {
+    let url_check = check_url(&dataset.url, args);
+    let reachable_check = match url_check {
+        Ok(_) => check_source_reachable(client, &dataset.url, args),
+        Err(_) => Err("invalid URL".to_string()),
+    };
+    let key_check = if dataset.key.is_empty() {
+        // Keys held by an SMS are left empty, see `PreComputeArgs::read_args_without_sms_keys`.
+        Ok("retrieved from SMS at run time".to_string())
+    } else {
+        match general_purpose::STANDARD
+            .decode(dataset.key.expose_secret())
+            .map(SecretBytes::from)
+        {
+            Ok(key) if key.len() == AES_KEY_LENGTH => Ok("AES-256 key".to_string()),
+            Ok(key) => Err(format!(
+                "{} bytes key, expected {AES_KEY_LENGTH}",
+                key.len()
+            )),
+            Err(e) => Err(format!("invalid Base64: {e}")),
+        }
+    };
+    let checksum_check =
+        parse_dataset_checksum(&dataset.checksum).map(|_| "SHA-256 digest".to_string());
+
+    ItemReport {
+        name: dataset.filename.to_string(),
+        url: dataset.url.clone(),
+        checks: vec![
+            Check::new("url", url_check),
+            Check::new("reachable", reachable_check),
+            Check::new("key", key_check),
+            Check::new("checksum", checksum_check),
+        ],
+    }
+}
+
+fn validate_input_file(
+    client: &Result<Client, String>,
+    input_file: &InputFile,
+    args: &PreComputeArgs,
+) -> ItemReport {
+    let url_check = check_url(&input_file.url, args);
+    let reachable_check = match url_check {
+        Ok(_) => check_source_reachable(client, &input_file.url, args),
+        Err(_) => Err("invalid URL".to_string()),
+    };
+    let mut checks = vec![
+        Check::new("url", url_check),
+        Check::new("reachable", reachable_check),
+    ];
+    // Invalid checksums are already reported as exit causes by `PreComputeArgs::read_args`
+    if let Some(checksum) = &input_file.checksum {
+        checks.push(Check::new("checksum", Ok(checksum.to_string())));
+    }
+
+    ItemReport {
+        name: input_file.url.clone(),
+        url: input_file.url.clone(),
+        checks,
+    }
+}
+
+/// Checks that `url` designates a location the pre-compute can download from: an IPFS
+/// multi-address holding a verifiable CID, a local path when local sources are enabled, or an
+/// HTTP(S) URL.
+fn check_url(url: &str, args: &PreComputeArgs) -> Result<String, String> {
+    if is_multi_address(url) {
+        cid_from_multi_address(url)
+            .map(|_| "IPFS multi-address".to_string())
+            .ok_or_else(|| "IPFS multi-address without a verifiable CID".to_string())
+    } else if is_local_source(url) {
+        check_local_url(args)
+    } else {
+        check_http_url(url)
+    }
+}
+
+/// Checks that the content at `url`, validated by [`check_url`], can be reached: on any IPFS
+/// gateway for a multi-address, below the local source root for a local path, or directly.
+fn check_source_reachable(
+    client: &Result<Client, String>,
+    url: &str,
+    args: &PreComputeArgs,
+) -> Result<String, String> {
+    if is_multi_address(url) {
+        let mut errors = Vec::new();
+        args.ipfs_gateways
+            .gateways
+            .iter()
+            .map(|gateway| format!("{gateway}{url}"))
+            .find_map(|gateway_url| match check_reachable(client, &gateway_url) {
+                Ok(reached) => Some(reached),
+                Err(e) => {
+                    errors.push(e);
+                    None
+                }
+            })
+            .ok_or_else(|| errors.join(", "))
+    } else if is_local_source(url) {
+        check_local_file(url, args)
+    } else {
+        check_reachable(client, url)
+    }
+}
+
+fn check_http_url(url: &str) -> Result<String, String> {
+    match Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok("HTTP URL".to_string()),
+        Ok(parsed) => Err(format!("unsupported scheme {}", parsed.scheme())),
+        Err(e) => Err(format!("invalid URL: {e}")),
+    }
+}
+
+fn check_local_url(args: &PreComputeArgs) -> Result<String, String> {
+    match args.local_source_root {
+        Some(_) => Ok("local path".to_string()),
+        None => Err(format!(
+            "local sources are disabled, {} is not configured",
+            TeeSessionEnvironmentVariable::IexecPreComputeLocalSourceRoot.name()
+        )),
+    }
+}
+
+fn check_local_file(url: &str, args: &PreComputeArgs) -> Result<String, String> {
+    let root = args.local_source_root.clone().unwrap_or_default();
+    LocalSourceResolver::new(root)
+        .open_file(url)
+        .map(|(_, path)| path.display().to_string())
+}
+
+fn check_reachable(client: &Result<Client, String>, url: &str) -> Result<String, String> {
+    match client.as_ref().map_err(Clone::clone)?.head(url).send() {
+        Ok(response) if response.status().is_success() => Ok(url.to_string()),
+        Ok(response) => Err(format!("{url}: HTTP {}", response.status())),
+        Err(e) => Err(format!("{url}: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::utils::env_utils::TeeSessionEnvironmentVariable::*;
+    use std::fs;
+    use tempfile::TempDir;
+    use tokio::runtime::Runtime;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const CID_MULTI_ADDRESS: &str = "/ipfs/QmUVhChbLFiuzNK1g2GsWyWEiad7SXPqARnWzGumgziwEp";
+    const DATASET_CHECKSUM: &str =
+        "0x02a12ef127dcfbdb294a090c8f0b69a0ca30b7940fc36cabf971f488efd374d7";
+    const DATASET_KEY: &str = "ubA6H9emVPJT91/flYAmnKHC0phSV3cfuqsLxQfgow0=";
+
+    fn start_server() -> (Runtime, MockServer) {
+        let rt = Runtime::new().unwrap();
+        let server = rt.block_on(async {
+            let server = MockServer::start().await;
+            for (route, status) in [
+                ("/dataset.bin", 200),
+                ("/input.txt", 200),
+                ("/missing.txt", 404),
+                (CID_MULTI_ADDRESS, 200),
+            ] {
+                Mock::given(method("HEAD"))
+                    .and(path(route))
+                    .respond_with(ResponseTemplate::new(status))
+                    .mount(&server)
+                    .await;
+            }
+            server
+        });
+        (rt, server)
+    }
+
+    fn check<'a>(report: &'a ItemReport, name: &str) -> &'a Check {
+        report
+            .checks
+            .iter()
+            .find(|check| check.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn validate_configuration_succeeds_with_valid_configuration() {
+        let (_rt, server) = start_server();
+        let temp_dir = TempDir::new().unwrap();
+        let env_vars = vec![
+            (
+                IexecPreComputeOut.name(),
+                temp_dir.path().to_str().map(String::from),
+            ),
+            (IsDatasetRequired.name(), Some("true".to_string())),
+            (
+                IexecDatasetUrl(0).name(),
+                Some(format!("{}/dataset.bin", server.uri())),
+            ),
+            (IexecDatasetKey(0).name(), Some(DATASET_KEY.to_string())),
+            (
+                IexecDatasetChecksum(0).name(),
+                Some(DATASET_CHECKSUM.to_string()),
+            ),
+            (
+                IexecDatasetFilename(0).name(),
+                Some("dataset.txt".to_string()),
+            ),
+            (IexecBulkSliceSize.name(), Some("1".to_string())),
+            (
+                IexecDatasetUrl(1).name(),
+                Some(CID_MULTI_ADDRESS.to_string()),
+            ),
+            (IexecDatasetKey(1).name(), Some(DATASET_KEY.to_string())),
+            (
+                IexecDatasetChecksum(1).name(),
+                Some(DATASET_CHECKSUM[2..].to_uppercase()),
+            ),
+            (IexecDatasetFilename(1).name(), Some("bulk.txt".to_string())),
+            (
+                IexecIpfsGateways.name(),
+                Some(format!("http://127.0.0.1:1,{}", server.uri())),
+            ),
+            (IexecInputFilesNumber.name(), Some("2".to_string())),
+            (
+                IexecInputFileUrlPrefix(1).name(),
+                Some(format!("{}/input.txt", server.uri())),
+            ),
+            (
+                IexecInputFileUrlPrefix(2).name(),
+                Some(CID_MULTI_ADDRESS.to_string()),
+            ),
+        ];
+
+        let report = temp_env::with_vars(env_vars, validate_configuration);
+
+        assert!(report.valid, "{report:?}");
+        assert!(report.exit_causes.is_empty());
+        assert!(report.output_folder.ok);
+        assert_eq!(report.datasets.len(), 2);
+        assert_eq!(
+            check(&report.datasets[1], "reachable").detail,
+            format!("{}{CID_MULTI_ADDRESS}", server.uri())
+        );
+        assert_eq!(report.input_files.len(), 2);
+        assert_eq!(
+            check(&report.input_files[1], "url").detail,
+            "IPFS multi-address"
+        );
+        assert_eq!(
+            check(&report.input_files[1], "reachable").detail,
+            format!("{}{CID_MULTI_ADDRESS}", server.uri())
+        );
+    }
+
+    #[test]
+    fn validate_configuration_reports_every_problem() {
+        let (_rt, server) = start_server();
+        let env_vars = vec![
+            (
+                IexecPreComputeOut.name(),
+                Some("/non-existent-folder".to_string()),
+            ),
+            (IsDatasetRequired.name(), Some("true".to_string())),
+            (
+                IexecDatasetUrl(0).name(),
+                Some("ftp://dataset.bin".to_string()),
+            ),
+            (IexecDatasetKey(0).name(), Some("c2hvcnQ=".to_string())),
+            (IexecDatasetChecksum(0).name(), Some("0x123".to_string())),
+            (
+                IexecDatasetFilename(0).name(),
+                Some("dataset.txt".to_string()),
+            ),
+            (IexecInputFilesNumber.name(), Some("2".to_string())),
+            (
+                IexecInputFileUrlPrefix(1).name(),
+                Some(format!("{}/missing.txt", server.uri())),
+            ),
+            (
+                IexecInputFileChecksumPrefix(2).name(),
+                Some("0x123".to_string()),
+            ),
+        ];
+
+        let report = temp_env::with_vars(env_vars, validate_configuration);
+
+        assert!(!report.valid);
+        assert_eq!(
+            report.exit_causes,
+            vec![ReplicateStatusCause::PreComputeAtLeastOneInputFileUrlMissing(2),]
+        );
+        assert!(!report.output_folder.ok);
+        let dataset = &report.datasets[0];
+        for name in ["url", "reachable", "key", "checksum"] {
+            assert!(!check(dataset, name).ok, "{name} check should fail");
+        }
+        assert_eq!(check(dataset, "key").detail, "5 bytes key, expected 32");
+        let input_file = &report.input_files[0];
+        assert!(check(input_file, "url").ok);
+        assert_eq!(
+            check(input_file, "reachable").detail,
+            format!("{}/missing.txt: HTTP 404 Not Found", server.uri())
+        );
+    }
+
+    #[test]
+    fn validate_configuration_checks_local_sources_against_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("datasets");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join("dataset.bin"), "content").unwrap();
+        let dataset_url = format!("file://{}/dataset.bin", root.display());
+        let outside_path = temp_dir.path().join("outside.txt");
+        fs::write(&outside_path, "content").unwrap();
+        let env_vars = |local_source_root: Option<String>| {
+            vec![
+                (
+                    IexecPreComputeOut.name(),
+                    temp_dir.path().to_str().map(String::from),
+                ),
+                (IsDatasetRequired.name(), Some("true".to_string())),
+                (IexecDatasetUrl(0).name(), Some(dataset_url.clone())),
+                (IexecDatasetKey(0).name(), Some(DATASET_KEY.to_string())),
+                (
+                    IexecDatasetChecksum(0).name(),
+                    Some(DATASET_CHECKSUM.to_string()),
+                ),
+                (
+                    IexecDatasetFilename(0).name(),
+                    Some("dataset.txt".to_string()),
+                ),
+                (IexecInputFilesNumber.name(), Some("1".to_string())),
+                (
+                    IexecInputFileUrlPrefix(1).name(),
+                    outside_path.to_str().map(String::from),
+                ),
+                (IexecPreComputeLocalSourceRoot.name(), local_source_root),
+            ]
+        };
+
+        let report = temp_env::with_vars(
+            env_vars(root.to_str().map(String::from)),
+            validate_configuration,
+        );
+
+        assert!(!report.valid);
+        assert_eq!(check(&report.datasets[0], "url").detail, "local path");
+        assert_eq!(
+            check(&report.datasets[0], "reachable").detail,
+            fs::canonicalize(root.join("dataset.bin"))
+                .unwrap()
+                .display()
+                .to_string()
+        );
+        assert!(check(&report.input_files[0], "url").ok);
+        assert!(!check(&report.input_files[0], "reachable").ok);
+
+        let report = temp_env::with_vars(env_vars(None), validate_configuration);
+
+        assert!(!check(&report.datasets[0], "url").ok);
+        assert!(!check(&report.input_files[0], "url").ok);
+    }
+
+    #[test]
+    fn validate_configuration_does_not_request_sms_for_dataset_keys() {
+        let (rt, server) = start_server();
+        rt.block_on(async {
+            Mock::given(path("/tee/tasks/0x123456789abcdef/datasets/0/key"))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(0)
+                .mount(&server)
+                .await;
+        });
+        let temp_dir = TempDir::new().unwrap();
+        let env_vars = vec![
+            (
+                IexecPreComputeOut.name(),
+                temp_dir.path().to_str().map(String::from),
+            ),
+            (IsDatasetRequired.name(), Some("true".to_string())),
+            (
+                IexecDatasetUrl(0).name(),
+                Some(format!("{}/dataset.bin", server.uri())),
+            ),
+            (IexecDatasetKey(0).name(), None),
+            (
+                IexecDatasetChecksum(0).name(),
+                Some(DATASET_CHECKSUM.to_string()),
+            ),
+            (
+                IexecDatasetFilename(0).name(),
+                Some("dataset.txt".to_string()),
+            ),
+            (IexecInputFilesNumber.name(), Some("0".to_string())),
+            (IexecPreComputeSmsUrl.name(), Some(server.uri())),
+            (IexecTaskId.name(), Some("0x123456789abcdef".to_string())),
+            (
+                SignWorkerAddress.name(),
+                Some("0xabcdef123456789".to_string()),
+            ),
+            (
+                SignTeeChallengePrivateKey.name(),
+                Some(
+                    "0xdd3b993ec21c71c1f6d63a5240850e0d4d8dd83ff70d29e49247958548c1d479"
+                        .to_string(),
+                ),
+            ),
+        ];
+
+        let report = temp_env::with_vars(env_vars, validate_configuration);
+
+        assert!(report.valid, "{report:?}");
+        assert_eq!(
+            check(&report.datasets[0], "key").detail,
+            "retrieved from SMS at run time"
+        );
+    }
+
+    #[test]
+    fn check_source_reachable_fails_when_http_client_unavailable() {
+        let (_rt, server) = start_server();
+        let client = Err("HTTP client unavailable: TLS backend".to_string());
+
+        assert_eq!(
+            check_source_reachable(
+                &client,
+                &format!("{}/input.txt", server.uri()),
+                &PreComputeArgs::default()
+            ),
+            Err("HTTP client unavailable: TLS backend".to_string())
+        );
+    }
+
+    #[test]
+    fn validate_configuration_reports_missing_output_folder() {
+        let env_vars = vec![
+            (IexecPreComputeOut.name(), None),
+            (IsDatasetRequired.name(), Some("false".to_string())),
+            (IexecInputFilesNumber.name(), Some("0".to_string())),
+        ];
+
+        let report = temp_env::with_vars(env_vars, validate_configuration);
+
+        assert!(!report.valid);
+        assert_eq!(
+            report.exit_causes,
+            vec![ReplicateStatusCause::PreComputeOutputPathMissing]
+        );
+        let serialized = serde_json::to_value(&report).unwrap();
+        assert!(serialized["exitCauses"].is_array());
+        assert!(serialized["inputFiles"].is_array());
+        assert_eq!(
+            serialized["outputFolder"],
+            serde_json::json!({"name": "output_folder", "ok": false, "detail": "not configured"})
+        );
+    }
+}
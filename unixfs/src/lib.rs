@@ -0,0 +1,228 @@
+use sha2::{Digest, Sha256};
+
+/// Size of the chunks a file is split into by default when added to IPFS.
+pub const UNIXFS_CHUNK_SIZE: usize = 256 * 1024;
+/// Maximum number of links of a node in the default balanced UnixFS layout.
+pub const UNIXFS_MAX_LINKS: usize = 174;
+
+const SHA2_256_CODE: u8 = 0x12;
+const SHA2_256_LENGTH: u8 = 32;
+const UNIXFS_FILE_TYPE: u64 = 2;
+
+/// Link to a node of a UnixFS DAG being built.
+struct DagLink {
+    /// SHA2-256 multihash of the node's block, which is also its binary CIDv0.
+    multihash: Vec<u8>,
+    /// Size of the node's block plus the sizes of all blocks below it.
+    cumulative_size: u64,
+    /// Size of the file content held by the node and all nodes below it.
+    file_size: u64,
+}
+
+/// Incremental builder of the CIDv0 a file gets when added to IPFS with default parameters.
+///
+/// The default parameters are a fixed-size chunker of [`UNIXFS_CHUNK_SIZE`] bytes, dag-pb
+/// leaves and a balanced layout with at most [`UNIXFS_MAX_LINKS`] links per node.
+///
+/// Memory usage is bounded by one chunk plus one pending node per level of the DAG.
+///
+/// # Example
+///
+/// ```rust
+/// use tee_worker_unixfs::UnixFsFileHasher;
+///
+/// let mut hasher = UnixFsFileHasher::default();
+/// hasher.update(b"hello world\n");
+/// let multihash = hasher.finalize();
+/// assert_eq!(multihash.len(), 34);
+/// assert_eq!(multihash[..2], [0x12, 0x20]);
+/// ```
+pub struct UnixFsFileHasher {
+    chunk: Vec<u8>,
+    levels: Vec<Vec<DagLink>>,
+}
+
+impl Default for UnixFsFileHasher {
+    fn default() -> Self {
+        UnixFsFileHasher {
+            chunk: Vec::with_capacity(UNIXFS_CHUNK_SIZE),
+            levels: Vec::new(),
+        }
+    }
+}
+
+impl UnixFsFileHasher {
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let taken = data.len().min(UNIXFS_CHUNK_SIZE - self.chunk.len());
+            self.chunk.extend_from_slice(&data[..taken]);
+            data = &data[taken..];
+            if self.chunk.len() == UNIXFS_CHUNK_SIZE {
+                self.add_leaf();
+            }
+        }
+    }
+
+    /// Returns the SHA2-256 multihash of the root block, which is the binary form of the CIDv0
+    /// of the content.
+    pub fn finalize(mut self) -> Vec<u8> {
+        if !self.chunk.is_empty() || self.levels.is_empty() {
+            self.add_leaf();
+        }
+        let mut level = 0;
+        loop {
+            let links = std::mem::take(&mut self.levels[level]);
+            if level + 1 == self.levels.len() && links.len() == 1 {
+                return links.into_iter().next().map(|link| link.multihash).unwrap();
+            }
+            let node = build_file_node(links);
+            self.push_link(level + 1, node);
+            level += 1;
+        }
+    }
+
+    fn add_leaf(&mut self) {
+        let mut unixfs = Vec::with_capacity(self.chunk.len() + 16);
+        put_varint_field(&mut unixfs, 1, UNIXFS_FILE_TYPE);
+        if !self.chunk.is_empty() {
+            put_bytes_field(&mut unixfs, 2, &self.chunk);
+        }
+        put_varint_field(&mut unixfs, 3, self.chunk.len() as u64);
+        let mut block = Vec::with_capacity(unixfs.len() + 8);
+        put_bytes_field(&mut block, 1, &unixfs);
+
+        let leaf = DagLink {
+            multihash: multihash_of(&block),
+            cumulative_size: block.len() as u64,
+            file_size: self.chunk.len() as u64,
+        };
+        self.chunk.clear();
+        self.push_link(0, leaf);
+    }
+
+    /// Adds a link to a level, first turning the level into a node one level up if it is full.
+    fn push_link(&mut self, level: usize, link: DagLink) {
+        if self.levels.len() <= level {
+            self.levels.push(Vec::with_capacity(UNIXFS_MAX_LINKS));
+        }
+        if self.levels[level].len() == UNIXFS_MAX_LINKS {
+            let links = std::mem::take(&mut self.levels[level]);
+            let node = build_file_node(links);
+            self.push_link(level + 1, node);
+        }
+        self.levels[level].push(link);
+    }
+}
+
+fn build_file_node(links: Vec<DagLink>) -> DagLink {
+    let file_size: u64 = links.iter().map(|link| link.file_size).sum();
+    let mut unixfs = Vec::new();
+    put_varint_field(&mut unixfs, 1, UNIXFS_FILE_TYPE);
+    put_varint_field(&mut unixfs, 3, file_size);
+    for link in &links {
+        put_varint_field(&mut unixfs, 4, link.file_size);
+    }
+
+    let mut block = Vec::new();
+    for link in &links {
+        let mut pb_link = Vec::with_capacity(48);
+        put_bytes_field(&mut pb_link, 1, &link.multihash);
+        put_bytes_field(&mut pb_link, 2, b"");
+        put_varint_field(&mut pb_link, 3, link.cumulative_size);
+        put_bytes_field(&mut block, 2, &pb_link);
+    }
+    put_bytes_field(&mut block, 1, &unixfs);
+
+    DagLink {
+        multihash: multihash_of(&block),
+        cumulative_size: block.len() as u64
+            + links.iter().map(|link| link.cumulative_size).sum::<u64>(),
+        file_size,
+    }
+}
+
+fn multihash_of(block: &[u8]) -> Vec<u8> {
+    let mut multihash = vec![SHA2_256_CODE, SHA2_256_LENGTH];
+    multihash.extend_from_slice(&Sha256::digest(block));
+    multihash
+}
+
+fn put_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn put_varint_field(buffer: &mut Vec<u8>, field: u64, value: u64) {
+    put_varint(buffer, field << 3);
+    put_varint(buffer, value);
+}
+
+fn put_bytes_field(buffer: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(buffer, (field << 3) | 2);
+    put_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recompute_cid(content: &[u8], update_size: usize) -> String {
+        let mut hasher = UnixFsFileHasher::default();
+        for chunk in content.chunks(update_size) {
+            hasher.update(chunk);
+        }
+        bs58::encode(hasher.finalize()).into_string()
+    }
+
+    #[test]
+    fn unixfs_file_hasher_matches_known_cids() {
+        let multi_chunk_content = vec![0u8; 4 * UNIXFS_CHUNK_SIZE + 1];
+        let test_cases: [(&[u8], &str); 4] = [
+            (
+                b"hello world\n",
+                "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o",
+            ),
+            (
+                b"hello world !\n",
+                "QmUVhChbLFiuzNK1g2GsWyWEiad7SXPqARnWzGumgziwEp",
+            ),
+            (b"", "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH"),
+            // head -c 1048577 /dev/zero | ipfs add -Q
+            (
+                &multi_chunk_content,
+                "Qmeb988ZjF9Ui6AVPR8Sjg5sAv1B6DauS5rUjCoNs7ftZ1",
+            ),
+        ];
+        for (content, expected) in test_cases {
+            assert_eq!(recompute_cid(content, 3000), expected);
+        }
+    }
+
+    #[test]
+    fn unixfs_file_hasher_is_independent_from_update_sizes() {
+        let content: Vec<u8> = (0..UNIXFS_CHUNK_SIZE * 3 + 17)
+            .map(|i| (i % 253) as u8)
+            .collect();
+        let expected = recompute_cid(&content, content.len());
+        for update_size in [1000, UNIXFS_CHUNK_SIZE, UNIXFS_CHUNK_SIZE + 1] {
+            assert_eq!(recompute_cid(&content, update_size), expected);
+        }
+    }
+
+    #[test]
+    fn unixfs_file_hasher_builds_deeper_tree_when_links_exceed_maximum() {
+        let mut hasher = UnixFsFileHasher::default();
+        for _ in 0..UNIXFS_MAX_LINKS + 1 {
+            hasher.chunk.push(1);
+            hasher.add_leaf();
+        }
+        assert_eq!(hasher.levels.len(), 2);
+        assert_eq!(hasher.levels[0].len(), 1);
+        assert_eq!(hasher.levels[1].len(), 1);
+        assert_eq!(hasher.finalize().len(), 34);
+    }
+}